@@ -7,7 +7,11 @@
  * Build:  npm run cli:build   →  dist-cli/focusflow.mjs
  * Run:    node dist-cli/focusflow.mjs <command> [...]
  */
-import { readFileSync, writeFileSync, renameSync, existsSync, mkdirSync } from 'node:fs';
+import {
+  readFileSync, writeFileSync, renameSync, existsSync, mkdirSync,
+  openSync, writeSync, closeSync, statSync, unlinkSync
+} from 'node:fs';
+import { randomUUID } from 'node:crypto';
 import { homedir } from 'node:os';
 import { join, dirname } from 'node:path';
 import { execFileSync } from 'node:child_process';
@@ -15,6 +19,7 @@ import { execFileSync } from 'node:child_process';
 import { createTaskFromInput } from '$lib/utils/parser';
 import { applyHighlanderRule, canAddTask } from '$lib/utils/quotaCore';
 import { createDefaultActiveData } from '$lib/types';
+import { setUnitLayout } from '$lib/utils/unitCalc';
 import type { ActiveData, Task, Priority } from '$lib/types';
 
 const APP_ID = 'com.focusflow.app';
@@ -30,7 +35,10 @@ function defaultDataPath(): string {
 function load(path: string): ActiveData {
   if (!existsSync(path)) return createDefaultActiveData();
   try {
-    return JSON.parse(readFileSync(path, 'utf8')) as ActiveData;
+    const data = JSON.parse(readFileSync(path, 'utf8')) as ActiveData;
+    // New tasks get their unitStart from the same layout the app uses
+    setUnitLayout(data.settings?.unitLayout);
+    return data;
   } catch (e) {
     fail(`Failed to read/parse ${path}: ${(e as Error).message}`);
   }
@@ -40,11 +48,100 @@ function save(path: string, data: ActiveData): void {
   data.lastModified = new Date().toISOString();
   const dir = dirname(path);
   if (!existsSync(dir)) mkdirSync(dir, { recursive: true });
-  const tmp = `${path}.tmp`;
+  const tmp = `${path}.${randomUUID().replace(/-/g, '')}.tmp`;
   writeFileSync(tmp, JSON.stringify(data, null, 2), 'utf8');
   renameSync(tmp, path); // atomic
 }
 
+// ---------- data directory lock ----------
+// Same protocol as the desktop app (see src-tauri/src/lock.rs): create
+// `focusflow.lock` next to the data file exclusively, retry every 50 ms, treat
+// a lockfile older than 30 s whose pid is gone (or older than 10 min) as left
+// by a crash, give up after 5 s, and only delete the lockfile while it still
+// holds our token.
+
+const LOCK_RETRY_MS = 50;
+const LOCK_TIMEOUT_MS = 5000;
+const LOCK_STALE_MS = 30_000;
+const LOCK_ABANDONED_MS = 10 * 60_000;
+
+function parseLock(content: string): { pid?: number; token?: string } {
+  try {
+    return JSON.parse(content);
+  } catch {
+    return {};
+  }
+}
+
+function isRunning(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (e) {
+    return (e as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/** The lockfile's content if it was left by a holder that is gone */
+function abandonedLock(lockPath: string): string | null {
+  const age = Date.now() - statSync(lockPath).mtimeMs;
+  const content = readFileSync(lockPath, 'utf8');
+  if (age > LOCK_ABANDONED_MS) return content;
+  if (age <= LOCK_STALE_MS) return null;
+  const { pid } = parseLock(content);
+  return typeof pid !== 'number' || !isRunning(pid) ? content : null;
+}
+
+function sleepSync(ms: number): void {
+  Atomics.wait(new Int32Array(new SharedArrayBuffer(4)), 0, 0, ms);
+}
+
+/** Run a read-modify-write of the data file under the data directory lock. */
+function withLock<T>(path: string, fn: () => T): T {
+  const dir = dirname(path);
+  if (!existsSync(dir)) mkdirSync(dir, { recursive: true });
+  const lockPath = join(dir, 'focusflow.lock');
+  const token = randomUUID().replace(/-/g, '');
+  const started = Date.now();
+
+  for (;;) {
+    try {
+      const fd = openSync(lockPath, 'wx');
+      writeSync(fd, JSON.stringify({ pid: process.pid, owner: 'focusflow-cli', token, acquiredAt: new Date().toISOString() }));
+      closeSync(fd);
+      break;
+    } catch (e) {
+      if ((e as NodeJS.ErrnoException).code !== 'EEXIST') fail(`Failed to create ${lockPath}: ${(e as Error).message}`);
+    }
+    try {
+      const abandoned = abandonedLock(lockPath);
+      if (abandoned !== null) {
+        // Unless another waiter already replaced it with a lock of its own
+        if (readFileSync(lockPath, 'utf8') === abandoned) unlinkSync(lockPath);
+        continue;
+      }
+    } catch {
+      continue; // released in the meantime
+    }
+    if (Date.now() - started >= LOCK_TIMEOUT_MS) fail(`data directory is locked by another process (${lockPath})`);
+    sleepSync(LOCK_RETRY_MS);
+  }
+
+  const release = () => {
+    // A lock taken over as stale belongs to its new holder
+    try {
+      if (parseLock(readFileSync(lockPath, 'utf8')).token === token) unlinkSync(lockPath);
+    } catch { /* already gone */ }
+  };
+  process.once('exit', release); // fail() exits without unwinding
+  try {
+    return fn();
+  } finally {
+    release();
+    process.off('exit', release);
+  }
+}
+
 // ---------- helpers ----------
 
 function fail(msg: string): never {
@@ -238,11 +335,11 @@ function main(): void {
   const dataPath = typeof flags.data === 'string' ? flags.data : defaultDataPath();
 
   switch (cmd) {
-    case 'add': return cmdAdd(positional, flags, dataPath);
+    case 'add': return withLock(dataPath, () => cmdAdd(positional, flags, dataPath));
     case 'list': return cmdList(flags, dataPath);
-    case 'done': return cmdResolve('done', positional, dataPath);
-    case 'cancel': return cmdResolve('cancel', positional, dataPath);
-    case 'import-reminders': return cmdImportReminders(flags, dataPath);
+    case 'done': return withLock(dataPath, () => cmdResolve('done', positional, dataPath));
+    case 'cancel': return withLock(dataPath, () => cmdResolve('cancel', positional, dataPath));
+    case 'import-reminders': return withLock(dataPath, () => cmdImportReminders(flags, dataPath));
     case 'agent-guide':
     case 'help':
     case undefined:
@@ -0,0 +1,33 @@
+/**
+ * Check parseTaskInput against the vectors shared with its Rust port
+ * (src-tauri/src/parser.rs, checked by `cargo test`).
+ *
+ * Run:  npm run test:parser
+ */
+import { readFileSync } from 'node:fs';
+import { isDeepStrictEqual } from 'node:util';
+
+import { parseTaskInput } from '$lib/utils/parser';
+
+interface Vectors {
+  today: string;
+  cases: { input: string; expected: unknown }[];
+}
+
+const vectors = JSON.parse(readFileSync('src/lib/utils/parser.vectors.json', 'utf8')) as Vectors;
+const [year, month, day] = vectors.today.split('-').map(Number);
+const today = new Date(year, month - 1, day);
+
+let failures = 0;
+for (const { input, expected } of vectors.cases) {
+  const actual = parseTaskInput(input, today);
+  if (!isDeepStrictEqual(actual, expected)) {
+    failures++;
+    console.error(`✗ ${JSON.stringify(input)}`);
+    console.error(`  expected ${JSON.stringify(expected)}`);
+    console.error(`  actual   ${JSON.stringify(actual)}`);
+  }
+}
+
+console.log(`${vectors.cases.length - failures}/${vectors.cases.length} parser vectors passed`);
+if (failures > 0) process.exit(1);
@@ -0,0 +1,62 @@
+/**
+ * Check getUnitForDate and checkUnitLayout against the vectors shared with
+ * the Rust implementation (src-tauri/src/units.rs, checked by `cargo test`).
+ *
+ * Run:  npm run test:units
+ */
+import { readFileSync } from 'node:fs';
+import { isDeepStrictEqual } from 'node:util';
+
+import type { UnitLayout } from '$lib/types';
+import { checkUnitLayout, getUnitForDate } from '$lib/utils/unitCalc';
+
+interface ExpectedUnit {
+  date: string;
+  number: number;
+  start: string;
+  end: string;
+  isReviewDay: boolean;
+}
+
+interface Vectors {
+  layouts: { name: string; layout: UnitLayout; error?: boolean; units: ExpectedUnit[] }[];
+}
+
+function localISO(d: Date): string {
+  return `${d.getFullYear()}-${String(d.getMonth() + 1).padStart(2, '0')}-${String(d.getDate()).padStart(2, '0')}`;
+}
+
+const vectors = JSON.parse(readFileSync('src/lib/utils/units.vectors.json', 'utf8')) as Vectors;
+
+let checks = 0;
+let failures = 0;
+for (const { name, layout, error = false, units } of vectors.layouts) {
+  checks++;
+  const problem = checkUnitLayout(layout);
+  if ((problem !== null) !== error) {
+    failures++;
+    console.error(`✗ ${name}: ${problem ?? 'accepted'}`);
+  }
+
+  for (const expected of units) {
+    checks++;
+    const [year, month, day] = expected.date.split('-').map(Number);
+    const unit = getUnitForDate(new Date(year, month - 1, day), layout);
+    const actual: ExpectedUnit = {
+      date: expected.date,
+      number: unit.unitNumber,
+      start: localISO(unit.startDate),
+      end: localISO(unit.endDate),
+      isReviewDay: unit.isReviewDay
+    };
+    if (!isDeepStrictEqual(actual, expected)) {
+      failures++;
+      console.error(`✗ ${name} on ${expected.date}`);
+      console.error(`  expected ${JSON.stringify(expected)}`);
+      console.error(`  actual   ${JSON.stringify(actual)}`);
+    }
+  }
+}
+
+console.log(`${checks - failures}/${checks} unit vectors passed`);
+if (failures > 0) process.exit(1);
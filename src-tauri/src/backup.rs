@@ -0,0 +1,926 @@
+use chrono::{Datelike, Duration, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::commands::{self, DataFileType};
+use crate::lock;
+use crate::model::{ActiveData, RetentionPolicy, Settings};
+
+/// Backups are named `backup_YYYYMMDD_HHMMSS` (directory) or
+/// `backup_YYYYMMDD_HHMMSS.zip` (single-file archive)
+const BACKUP_PREFIX: &str = "backup_";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+const ARCHIVE_EXTENSION: &str = ".zip";
+const LEGACY_FILENAME: &str = "focusflow_data.json";
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+/// On-disk layout of a backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupFormat {
+    /// Loose copies of the data files in a directory
+    Directory,
+    /// A single deflate-compressed zip file
+    Zip,
+}
+
+/// Describes the contents of a backup, stored alongside the data files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub manifest_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    pub name: String,
+    /// The `version` field of the data file, if it has one
+    pub schema_version: Option<String>,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub id: String,
+    pub format: BackupFormat,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub backup_id: String,
+    /// Snapshot of the pre-restore state, for undoing the restore
+    pub safety_backup_id: String,
+    /// File types replaced from the backup (`active`, `archive`, ...)
+    pub restored: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReport {
+    pub policy: RetentionPolicy,
+    pub dry_run: bool,
+    pub kept: Vec<BackupInfo>,
+    pub removed: Vec<BackupInfo>,
+}
+
+/// Files read out of a backup, keyed by file name
+pub struct BackupContents {
+    pub manifest: Option<BackupManifest>,
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+/// Parse the creation time and format out of a backup name
+fn parse_backup_id(id: &str) -> Option<(NaiveDateTime, BackupFormat)> {
+    let name = id.strip_prefix(BACKUP_PREFIX)?;
+    let (timestamp, format) = match name.strip_suffix(ARCHIVE_EXTENSION) {
+        Some(timestamp) => (timestamp, BackupFormat::Zip),
+        None => (name, BackupFormat::Directory),
+    };
+    NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|created| (created, format))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Build a manifest entry for a file about to be backed up
+fn manifest_entry(name: &str, bytes: &[u8]) -> ManifestEntry {
+    let schema_version = serde_json::from_slice::<serde_json::Value>(bytes)
+        .ok()
+        .and_then(|value| value.get("version").and_then(|v| v.as_str()).map(String::from));
+
+    ManifestEntry {
+        name: name.to_string(),
+        schema_version,
+        size_bytes: bytes.len() as u64,
+        sha256: sha256_hex(bytes),
+    }
+}
+
+/// Pick a backup name that no existing backup (of either format) uses.
+/// If a backup was already taken this second, the timestamp is bumped so
+/// existing backups are never written into.
+fn next_backup_stem(data_dir: &Path) -> String {
+    let mut timestamp = Local::now().naive_local();
+    loop {
+        let stem = format!("{}{}", BACKUP_PREFIX, timestamp.format(BACKUP_TIMESTAMP_FORMAT));
+        let archive = format!("{}{}", stem, ARCHIVE_EXTENSION);
+        if !data_dir.join(&stem).exists() && !data_dir.join(&archive).exists() {
+            return stem;
+        }
+        timestamp += Duration::seconds(1);
+    }
+}
+
+/// Create an empty, uniquely named backup directory
+fn create_backup_dir(data_dir: &Path) -> Result<PathBuf, String> {
+    loop {
+        let backup_dir = data_dir.join(next_backup_stem(data_dir));
+        match fs::create_dir(&backup_dir) {
+            Ok(()) => return Ok(backup_dir),
+            // Lost a race with another backup; pick the next name
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+/// Write a zip containing `files` plus the manifest, via a temp file so a
+/// half-written archive never carries a valid backup name
+fn write_zip_backup(
+    path: &Path,
+    files: &[(&'static str, Vec<u8>)],
+    manifest: &[u8],
+) -> Result<(), String> {
+    let temp_path = path.with_extension("zip.tmp");
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    {
+        let file = fs::File::create(&temp_path)
+            .map_err(|e| format!("Failed to create backup archive: {}", e))?;
+        let mut zip = ZipWriter::new(file);
+
+        for (name, bytes) in files
+            .iter()
+            .map(|(name, bytes)| (*name, bytes.as_slice()))
+            .chain(std::iter::once((MANIFEST_FILENAME, manifest)))
+        {
+            zip.start_file(name, options)
+                .map_err(|e| format!("Failed to add {} to archive: {}", name, e))?;
+            zip.write_all(bytes)
+                .map_err(|e| format!("Failed to write {} to archive: {}", name, e))?;
+        }
+
+        let file = zip
+            .finish()
+            .map_err(|e| format!("Failed to finish backup archive: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync backup archive: {}", e))?;
+    }
+
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to rename backup archive: {}", e))
+}
+
+/// Back up the current data files, along with a manifest of their sizes and
+/// checksums. Returns the backup path and the files it contains.
+pub fn create_backup(
+    data_dir: &Path,
+    format: BackupFormat,
+) -> Result<(PathBuf, Vec<&'static str>), String> {
+    fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+
+    // Read all data files (plus the legacy file, if it is still around)
+    let names = DataFileType::ALL
+        .iter()
+        .map(|file_type| file_type.filename())
+        .chain(std::iter::once(LEGACY_FILENAME));
+
+    let mut files = Vec::new();
+    for name in names {
+        let source = data_dir.join(name);
+        if source.exists() {
+            let bytes = fs::read(&source).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            files.push((name, bytes));
+        }
+    }
+
+    let manifest = BackupManifest {
+        manifest_version: MANIFEST_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Local::now().to_rfc3339(),
+        files: files
+            .iter()
+            .map(|(name, bytes)| manifest_entry(name, bytes))
+            .collect(),
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+
+    let backup_path = match format {
+        BackupFormat::Directory => {
+            let backup_dir = create_backup_dir(data_dir)?;
+            for (name, bytes) in &files {
+                fs::write(backup_dir.join(name), bytes).map_err(|e| e.to_string())?;
+            }
+            fs::write(backup_dir.join(MANIFEST_FILENAME), &manifest).map_err(|e| e.to_string())?;
+            backup_dir
+        }
+        BackupFormat::Zip => {
+            let path = data_dir.join(format!("{}{}", next_backup_stem(data_dir), ARCHIVE_EXTENSION));
+            write_zip_backup(&path, &files, &manifest)?;
+            path
+        }
+    };
+
+    Ok((backup_path, files.into_iter().map(|(name, _)| name).collect()))
+}
+
+/// Names of the files stored in a backup (excluding the manifest)
+fn backup_file_names(path: &Path, format: BackupFormat) -> Result<Vec<String>, String> {
+    let mut names = match format {
+        BackupFormat::Directory => {
+            let mut names = Vec::new();
+            for file in fs::read_dir(path).map_err(|e| e.to_string())? {
+                let file = file.map_err(|e| e.to_string())?;
+                if file.path().is_file() {
+                    names.push(file.file_name().to_string_lossy().to_string());
+                }
+            }
+            names
+        }
+        BackupFormat::Zip => {
+            let file = fs::File::open(path).map_err(|e| e.to_string())?;
+            let archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+            archive.file_names().map(String::from).collect()
+        }
+    };
+    names.retain(|name| name != MANIFEST_FILENAME);
+    names.sort();
+    Ok(names)
+}
+
+/// Total size of a backup on disk
+fn backup_size(path: &Path, format: BackupFormat) -> u64 {
+    match format {
+        BackupFormat::Directory => fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok()?.metadata().ok())
+                    .filter(|metadata| metadata.is_file())
+                    .map(|metadata| metadata.len())
+                    .sum()
+            })
+            .unwrap_or(0),
+        BackupFormat::Zip => fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    }
+}
+
+/// List all backups in the data directory, newest first.
+/// Entries that don't follow the backup naming scheme are ignored.
+pub fn list_backups(data_dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    let mut backups = Vec::new();
+
+    if !data_dir.exists() {
+        return Ok(backups);
+    }
+
+    for entry in fs::read_dir(data_dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+
+        let id = entry.file_name().to_string_lossy().to_string();
+        let Some((created, format)) = parse_backup_id(&id) else {
+            continue;
+        };
+        let matches_format = match format {
+            BackupFormat::Directory => path.is_dir(),
+            BackupFormat::Zip => path.is_file(),
+        };
+        if !matches_format {
+            continue;
+        }
+
+        // An unreadable archive is still listed so it can be pruned or inspected
+        let files = backup_file_names(&path, format).unwrap_or_default();
+
+        let created_at = Local
+            .from_local_datetime(&created)
+            .earliest()
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_else(|| created.to_string());
+
+        backups.push(BackupInfo {
+            id,
+            format,
+            created_at,
+            size_bytes: backup_size(&path, format),
+            files,
+        });
+    }
+
+    // Names sort chronologically, so reverse lexical order is newest first
+    backups.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(backups)
+}
+
+/// Read every file of a backup into memory
+pub fn read_backup(data_dir: &Path, backup_id: &str) -> Result<BackupContents, String> {
+    // Only accept our own backup names, which also rules out path traversal
+    let Some((_, format)) = parse_backup_id(backup_id) else {
+        return Err(format!("Invalid backup id: {}", backup_id));
+    };
+    let path = data_dir.join(backup_id);
+    if !path.exists() {
+        return Err(format!("Backup not found: {}", backup_id));
+    }
+
+    let mut files = BTreeMap::new();
+    match format {
+        BackupFormat::Directory => {
+            for name in backup_file_names(&path, format)?
+                .into_iter()
+                .chain(std::iter::once(MANIFEST_FILENAME.to_string()))
+            {
+                let file_path = path.join(&name);
+                if file_path.exists() {
+                    let bytes = fs::read(&file_path)
+                        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+                    files.insert(name, bytes);
+                }
+            }
+        }
+        BackupFormat::Zip => {
+            let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+            let mut archive = ZipArchive::new(file)
+                .map_err(|e| format!("Failed to open backup archive: {}", e))?;
+            for i in 0..archive.len() {
+                let mut entry = archive
+                    .by_index(i)
+                    .map_err(|e| format!("Failed to read backup archive: {}", e))?;
+                let name = entry.name().to_string();
+                let mut bytes = Vec::new();
+                entry
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+                files.insert(name, bytes);
+            }
+        }
+    }
+
+    let manifest = match files.remove(MANIFEST_FILENAME) {
+        Some(bytes) => Some(
+            serde_json::from_slice::<BackupManifest>(&bytes)
+                .map_err(|e| format!("Failed to parse backup manifest: {}", e))?,
+        ),
+        // Backups taken before manifests were introduced
+        None => None,
+    };
+
+    Ok(BackupContents { manifest, files })
+}
+
+impl ManifestEntry {
+    /// Check a file's bytes against the recorded size and checksum
+    fn mismatch(&self, bytes: Option<&[u8]>) -> Option<String> {
+        match bytes {
+            None => Some(format!("{} is missing", self.name)),
+            Some(bytes) if bytes.len() as u64 != self.size_bytes => Some(format!(
+                "{} is {} bytes, expected {}",
+                self.name,
+                bytes.len(),
+                self.size_bytes
+            )),
+            Some(bytes) if sha256_hex(bytes) != self.sha256 => {
+                Some(format!("{} does not match its checksum", self.name))
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+impl BackupContents {
+    /// Compare every file listed in the manifest against its recorded size
+    /// and checksum. Returns one message per mismatch.
+    pub fn checksum_mismatches(&self) -> Vec<String> {
+        let Some(manifest) = &self.manifest else {
+            return Vec::new();
+        };
+
+        manifest
+            .files
+            .iter()
+            .filter_map(|entry| entry.mismatch(self.files.get(&entry.name).map(Vec::as_slice)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVerification {
+    pub name: String,
+    pub ok: bool,
+    pub problems: Vec<String>,
+    pub task_count: Option<usize>,
+    pub session_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupVerification {
+    pub backup_id: String,
+    pub ok: bool,
+    /// Backups taken before manifests existed can only be parse-checked
+    pub has_manifest: bool,
+    pub files: Vec<FileVerification>,
+    /// Problems with the backup as a whole (unreadable archive, bad manifest)
+    pub problems: Vec<String>,
+}
+
+/// Parse one backed-up file and count what it holds
+fn inspect_file(name: &str, bytes: &[u8]) -> FileVerification {
+    let mut problems = Vec::new();
+    let mut task_count = None;
+    let mut session_count = None;
+
+    let file_type = DataFileType::ALL
+        .into_iter()
+        .find(|file_type| file_type.filename() == name);
+
+    match file_type {
+        Some(file_type) => match std::str::from_utf8(bytes) {
+            Ok(content) => {
+                if let Err(e) = file_type.validate_content(content) {
+                    problems.push(e.to_string());
+                }
+                let value = serde_json::from_str::<serde_json::Value>(content).ok();
+                let count = |key: &str| {
+                    value
+                        .as_ref()
+                        .and_then(|v| v.get(key))
+                        .and_then(|v| v.as_array())
+                        .map(Vec::len)
+                };
+                match file_type {
+                    DataFileType::Active | DataFileType::Archive => task_count = count("tasks"),
+                    DataFileType::PomodoroHistory => session_count = count("sessions"),
+                }
+            }
+            Err(_) => problems.push("not valid UTF-8".to_string()),
+        },
+        // Legacy single-file data: only check that it is JSON
+        None => match serde_json::from_slice::<serde_json::Value>(bytes) {
+            Ok(value) => {
+                task_count = value.get("tasks").and_then(|v| v.as_array()).map(Vec::len);
+                session_count = value
+                    .get("pomodoroHistory")
+                    .and_then(|v| v.as_array())
+                    .map(Vec::len);
+            }
+            Err(e) => problems.push(format!("Invalid JSON: {}", e)),
+        },
+    }
+
+    FileVerification {
+        name: name.to_string(),
+        ok: problems.is_empty(),
+        problems,
+        task_count,
+        session_count,
+    }
+}
+
+/// Check that a backup is readable: every file matches the manifest and
+/// parses as valid data. Never fails; problems are reported in the result.
+pub fn verify_backup(data_dir: &Path, backup_id: &str) -> BackupVerification {
+    let backup = match read_backup(data_dir, backup_id) {
+        Ok(backup) => backup,
+        Err(e) => {
+            return BackupVerification {
+                backup_id: backup_id.to_string(),
+                ok: false,
+                has_manifest: false,
+                files: Vec::new(),
+                problems: vec![e],
+            }
+        }
+    };
+
+    let mut files: Vec<FileVerification> = backup
+        .files
+        .iter()
+        .map(|(name, bytes)| inspect_file(name, bytes))
+        .collect();
+
+    let mut problems = Vec::new();
+    if let Some(manifest) = &backup.manifest {
+        for entry in &manifest.files {
+            let bytes = backup.files.get(&entry.name).map(Vec::as_slice);
+            let Some(mismatch) = entry.mismatch(bytes) else {
+                continue;
+            };
+            match files.iter_mut().find(|file| file.name == entry.name) {
+                Some(file) => {
+                    file.ok = false;
+                    file.problems.insert(0, mismatch);
+                }
+                None => problems.push(mismatch),
+            }
+        }
+    }
+
+    if files.is_empty() && problems.is_empty() {
+        problems.push("Backup contains no data files".to_string());
+    }
+
+    BackupVerification {
+        backup_id: backup_id.to_string(),
+        ok: problems.is_empty() && files.iter().all(|file| file.ok),
+        has_manifest: backup.manifest.is_some(),
+        files,
+        problems,
+    }
+}
+
+/// Verify every backup in the data directory, newest first
+pub fn verify_all_backups(data_dir: &Path) -> Result<Vec<BackupVerification>, String> {
+    Ok(list_backups(data_dir)?
+        .iter()
+        .map(|backup| verify_backup(data_dir, &backup.id))
+        .collect())
+}
+
+/// Delay before the first background check, so it doesn't compete with startup
+const VERIFY_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+/// Interval between background checks
+const VERIFY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+/// Periodically verify all backups, calling `on_failure` with the backups
+/// that failed. Runs forever; spawn it on its own thread.
+pub fn verification_loop(data_dir: PathBuf, on_failure: impl Fn(Vec<BackupVerification>)) {
+    std::thread::sleep(VERIFY_INITIAL_DELAY);
+    loop {
+        match verify_all_backups(&data_dir) {
+            Ok(results) => {
+                let failed: Vec<_> = results.into_iter().filter(|r| !r.ok).collect();
+                if !failed.is_empty() {
+                    eprintln!("{} backup(s) failed verification", failed.len());
+                    on_failure(failed);
+                }
+            }
+            Err(e) => eprintln!("Failed to verify backups: {}", e),
+        }
+        std::thread::sleep(VERIFY_INTERVAL);
+    }
+}
+
+/// Select the backups a policy keeps (grandfather-father-son).
+/// `backups` must be sorted newest first; returns the IDs to keep.
+pub fn plan_retention(backups: &[BackupInfo], policy: &RetentionPolicy) -> HashSet<String> {
+    let dated: Vec<(&str, NaiveDateTime)> = backups
+        .iter()
+        .filter_map(|b| parse_backup_id(&b.id).map(|(created, _)| (b.id.as_str(), created)))
+        .collect();
+
+    // The newest backup is always kept, whatever the policy says
+    let mut keep: HashSet<String> = dated
+        .iter()
+        .take(policy.keep_last.max(1))
+        .map(|(id, _)| id.to_string())
+        .collect();
+
+    // For each tier, walk newest-first and keep the first backup seen in each
+    // new period until the tier's quota of periods is used up.
+    let mut keep_per_period = |limit: usize, period: &dyn Fn(&NaiveDateTime) -> (i32, u32)| {
+        let mut seen = HashSet::new();
+        for (id, created) in &dated {
+            if seen.len() >= limit {
+                break;
+            }
+            if seen.insert(period(created)) {
+                keep.insert(id.to_string());
+            }
+        }
+    };
+
+    keep_per_period(policy.daily, &|dt| (dt.year(), dt.ordinal()));
+    keep_per_period(policy.weekly, &|dt| {
+        let week = dt.iso_week();
+        (week.year(), week.week())
+    });
+    keep_per_period(policy.monthly, &|dt| (dt.year(), dt.month()));
+
+    keep
+}
+
+/// Delete backups not selected by the policy. With `dry_run`, only report.
+pub fn prune_backups(
+    data_dir: &Path,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<PruneReport, String> {
+    let backups = list_backups(data_dir)?;
+    let keep = plan_retention(&backups, policy);
+
+    let mut kept = Vec::new();
+    let mut removed = Vec::new();
+    for backup in backups {
+        if keep.contains(&backup.id) {
+            kept.push(backup);
+            continue;
+        }
+
+        if !dry_run {
+            let path = data_dir.join(&backup.id);
+            let result = match backup.format {
+                BackupFormat::Directory => fs::remove_dir_all(&path),
+                BackupFormat::Zip => fs::remove_file(&path),
+            };
+            result.map_err(|e| format!("Failed to remove backup {}: {}", backup.id, e))?;
+        }
+        removed.push(backup);
+    }
+
+    Ok(PruneReport {
+        policy: policy.clone(),
+        dry_run,
+        kept,
+        removed,
+    })
+}
+
+/// Restore data files from a backup.
+///
+/// Every data file in the backup is checked against the manifest and
+/// validated before anything is touched, then the current state is
+/// snapshotted into a new backup, and finally each file is swapped in via
+/// temp-file-then-rename. Files absent from the backup (e.g. an old backup
+/// without `archive.json`) are left as they are.
+pub fn restore_backup(data_dir: &Path, backup_id: &str) -> Result<RestoreReport, String> {
+    let backup = read_backup(data_dir, backup_id)?;
+
+    let mismatches = backup.checksum_mismatches();
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "Backup {} failed verification: {}",
+            backup_id,
+            mismatches.join("; ")
+        ));
+    }
+
+    let mut contents = Vec::new();
+    for file_type in DataFileType::ALL {
+        let Some(bytes) = backup.files.get(file_type.filename()) else {
+            continue;
+        };
+        let content = String::from_utf8(bytes.clone())
+            .map_err(|_| format!("Backup file {} is not valid UTF-8", file_type.filename()))?;
+        file_type
+            .validate_content(&content)
+            .map_err(|e| format!("Backup file {} is invalid: {}", file_type.filename(), e))?;
+        contents.push((file_type, content));
+    }
+
+    if !contents.iter().any(|(file_type, _)| *file_type == DataFileType::Active) {
+        return Err(format!(
+            "Backup {} does not contain {}",
+            backup_id,
+            DataFileType::Active.filename()
+        ));
+    }
+
+    // Held from the safety snapshot through the restore so no write slips in between
+    let _lock = lock::acquire(data_dir)?;
+
+    let (safety_path, _) = create_backup(data_dir, BackupFormat::Directory)?;
+    let safety_backup_id = safety_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // The backed-up content was on disk before, so quotas are checked against
+    // itself rather than the file it replaces: an overflow it already had
+    // neither blocks the restore nor gets re-prioritized
+    let mut restored = Vec::new();
+    for (file_type, content) in contents {
+        commands::write_data_file_over(data_dir, file_type, &content, Some(&content))?;
+        restored.push(file_type.name());
+    }
+
+    Ok(RestoreReport {
+        backup_id: backup_id.to_string(),
+        safety_backup_id,
+        restored,
+    })
+}
+
+/// Read the backup settings from `active.json`, falling back to the
+/// defaults when the file is missing or unreadable.
+fn load_settings(data_dir: &Path) -> Settings {
+    fs::read_to_string(data_dir.join(DataFileType::Active.filename()))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ActiveData>(&content).ok())
+        .map(|data| data.settings)
+        .unwrap_or_default()
+}
+
+/// Retention policy configured in settings
+pub fn load_retention_policy(data_dir: &Path) -> RetentionPolicy {
+    load_settings(data_dir).backup_retention
+}
+
+/// Backup format configured in settings
+pub fn load_backup_format(data_dir: &Path) -> BackupFormat {
+    if load_settings(data_dir).compress_backups {
+        BackupFormat::Zip
+    } else {
+        BackupFormat::Directory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn active(task_id: &str) -> String {
+        json!({
+            "version": "2.0",
+            "lastModified": "2026-03-04T10:00:00Z",
+            "tasks": [{ "id": task_id, "content": task_id, "priority": "C" }]
+        })
+        .to_string()
+    }
+
+    fn info(id: &str) -> BackupInfo {
+        BackupInfo {
+            id: id.to_string(),
+            format: BackupFormat::Directory,
+            created_at: String::new(),
+            size_bytes: 0,
+            files: Vec::new(),
+        }
+    }
+
+    fn policy(keep_last: usize, daily: usize, weekly: usize, monthly: usize) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_last,
+            daily,
+            weekly,
+            monthly,
+            ..RetentionPolicy::default()
+        }
+    }
+
+    fn sorted(ids: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut ids: Vec<String> = ids.into_iter().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Fake a backup directory with the given name
+    fn backup_dir(data_dir: &Path, id: &str) {
+        let dir = data_dir.join(id);
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join(DataFileType::Active.filename()), active("t1")).unwrap();
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_of_each_period() {
+        // Newest first, as list_backups returns them
+        let backups: Vec<BackupInfo> = [
+            "backup_20260304_180000",
+            "backup_20260304_090000",
+            "backup_20260303_120000",
+            "backup_20260301_120000",
+            "backup_20260215_120000",
+            "backup_20260110_120000",
+            "backup_20260105_120000",
+        ]
+        .into_iter()
+        .map(info)
+        .collect();
+
+        let keep = plan_retention(&backups, &policy(1, 2, 0, 3));
+        assert_eq!(
+            sorted(keep),
+            [
+                "backup_20260110_120000",
+                "backup_20260215_120000",
+                "backup_20260303_120000",
+                "backup_20260304_180000",
+            ]
+        );
+
+        // The week of 2026-03-02 and the week before it
+        let keep = plan_retention(&backups, &policy(0, 0, 2, 0));
+        assert_eq!(
+            sorted(keep),
+            ["backup_20260301_120000", "backup_20260304_180000"]
+        );
+    }
+
+    #[test]
+    fn pruning_never_removes_the_newest_or_a_tier_kept_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        for id in [
+            "backup_20260304_180000",
+            "backup_20260304_090000",
+            "backup_20260201_120000",
+        ] {
+            backup_dir(dir.path(), id);
+        }
+        fs::write(dir.path().join("backup_20260101_120000.zip"), b"not a zip").unwrap();
+        // Not a backup name, so never touched
+        fs::create_dir(dir.path().join("backup_notes")).unwrap();
+
+        let dry = prune_backups(dir.path(), &policy(0, 0, 0, 2), true).unwrap();
+        assert_eq!(dry.removed.len(), 2);
+        assert_eq!(list_backups(dir.path()).unwrap().len(), 4);
+
+        let report = prune_backups(dir.path(), &policy(0, 0, 0, 2), false).unwrap();
+        let kept: Vec<&str> = report.kept.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(kept, ["backup_20260304_180000", "backup_20260201_120000"]);
+        assert!(!dir.path().join("backup_20260304_090000").exists());
+        assert!(!dir.path().join("backup_20260101_120000.zip").exists());
+        assert!(dir.path().join("backup_notes").exists());
+
+        // A policy that keeps nothing still keeps the newest
+        let report = prune_backups(dir.path(), &policy(0, 0, 0, 0), false).unwrap();
+        assert_eq!(report.kept[0].id, "backup_20260304_180000");
+        assert_eq!(list_backups(dir.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restore_snapshots_the_current_state_first() {
+        for format in [BackupFormat::Directory, BackupFormat::Zip] {
+            let dir = tempfile::tempdir().unwrap();
+            let active_path = dir.path().join(DataFileType::Active.filename());
+            fs::write(&active_path, active("before")).unwrap();
+            let (path, files) = create_backup(dir.path(), format).unwrap();
+            assert_eq!(files, [DataFileType::Active.filename()]);
+            let backup_id = path.file_name().unwrap().to_string_lossy().to_string();
+
+            fs::write(&active_path, active("after")).unwrap();
+            let report = restore_backup(dir.path(), &backup_id).unwrap();
+            assert_eq!(report.restored, ["active"]);
+            assert!(fs::read_to_string(&active_path).unwrap().contains("\"before\""));
+
+            let safety = read_backup(dir.path(), &report.safety_backup_id).unwrap();
+            let snapshot = &safety.files[DataFileType::Active.filename()];
+            assert!(String::from_utf8_lossy(snapshot).contains("\"after\""));
+            assert!(!dir.path().join(lock::LOCK_FILENAME).exists());
+        }
+    }
+
+    #[test]
+    fn restore_brings_back_an_overflow_the_backup_already_had() {
+        let dir = tempfile::tempdir().unwrap();
+        let active_path = dir.path().join(DataFileType::Active.filename());
+        let overflowing = json!({
+            "version": "2.0",
+            "tasks": [
+                { "id": "a1", "content": "a1", "priority": "A" },
+                { "id": "a2", "content": "a2", "priority": "A" }
+            ],
+            "settings": { "quotaMode": "reject" }
+        })
+        .to_string();
+        fs::write(&active_path, &overflowing).unwrap();
+        let (path, _) = create_backup(dir.path(), BackupFormat::Directory).unwrap();
+        let backup_id = path.file_name().unwrap().to_string_lossy().to_string();
+
+        fs::write(&active_path, active("after")).unwrap();
+        restore_backup(dir.path(), &backup_id).unwrap();
+        assert_eq!(fs::read_to_string(&active_path).unwrap(), overflowing);
+    }
+
+    #[test]
+    fn verification_catches_a_corrupted_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let active_path = dir.path().join(DataFileType::Active.filename());
+        fs::write(&active_path, active("t1")).unwrap();
+        let (path, _) = create_backup(dir.path(), BackupFormat::Directory).unwrap();
+        let backup_id = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(verify_backup(dir.path(), &backup_id).ok);
+
+        // Same length, different bytes: only the checksum can tell
+        fs::write(path.join(DataFileType::Active.filename()), active("t2")).unwrap();
+        let verification = verify_backup(dir.path(), &backup_id);
+        assert!(!verification.ok);
+        assert_eq!(
+            verification.files[0].problems[0],
+            "active.json does not match its checksum"
+        );
+        assert!(restore_backup(dir.path(), &backup_id).is_err());
+        assert!(fs::read_to_string(&active_path).unwrap().contains("\"t1\""));
+
+        // A truncated archive can't be read at all
+        fs::write(dir.path().join("backup_20260101_120000.zip"), b"PK").unwrap();
+        let failed: Vec<String> = verify_all_backups(dir.path())
+            .unwrap()
+            .into_iter()
+            .filter(|v| !v.ok)
+            .map(|v| v.backup_id)
+            .collect();
+        assert_eq!(failed, [backup_id, "backup_20260101_120000.zip".to_string()]);
+    }
+}
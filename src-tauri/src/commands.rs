@@ -1,13 +1,31 @@
-use chrono::Local;
+use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use std::sync::atomic::Ordering;
+use std::io::ErrorKind;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{Manager, State};
 
-use crate::WatcherState;
+use crate::backup::{
+    self, BackupFormat, BackupInfo, BackupVerification, PruneReport, RestoreReport,
+};
+use crate::cycle::{self, Advance};
+use crate::diff::FileChange;
+use crate::durable;
+use crate::error::WriteError;
+use crate::lock;
+use crate::merge::{self, MergeResult, Snapshots};
+use crate::model::{ActiveData, ArchiveData, PomodoroHistoryData, RetentionPolicy};
+use crate::parser::{self, ParsedTask};
+use crate::pomodoro::{Durations, Pomodoro, Snapshot};
+use crate::quota::{self, Enforced, QuotaReport, QuotaStatus};
+use crate::recurrence;
+use crate::recovery::{self, DataFileRead};
+use crate::reminders::{self, Snooze};
+use crate::units::{self, FlexibleUnit, Unit, UnitLayout};
+use crate::watcher::WatcherStatus;
+use crate::{WatcherState, DEFAULT_LEASE};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -25,6 +43,33 @@ pub enum DataFileType {
 }
 
 impl DataFileType {
+    pub const ALL: [DataFileType; 3] = [
+        DataFileType::Active,
+        DataFileType::Archive,
+        DataFileType::PomodoroHistory,
+    ];
+
+    /// Name used by commands and `data-file-changed` events
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataFileType::Active => "active",
+            DataFileType::Archive => "archive",
+            DataFileType::PomodoroHistory => "pomodoro_history",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        DataFileType::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Key of the list of ID-addressed items (tasks or sessions) in the file
+    pub fn list_key(&self) -> &'static str {
+        match self {
+            DataFileType::Active | DataFileType::Archive => "tasks",
+            DataFileType::PomodoroHistory => "sessions",
+        }
+    }
+
     pub fn filename(&self) -> &'static str {
         match self {
             DataFileType::Active => "active.json",
@@ -33,8 +78,21 @@ impl DataFileType {
         }
     }
 
-    pub fn temp_filename(&self) -> String {
-        format!("{}.tmp", self.filename())
+    /// Parse a payload for this file type and check its invariants
+    pub fn validate_content(&self, content: &str) -> Result<(), WriteError> {
+        let issues = match self {
+            DataFileType::Active => serde_json::from_str::<ActiveData>(content)?.validate(),
+            DataFileType::Archive => serde_json::from_str::<ArchiveData>(content)?.validate(),
+            DataFileType::PomodoroHistory => {
+                serde_json::from_str::<PomodoroHistoryData>(content)?.validate()
+            }
+        };
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(WriteError::Invalid { issues })
+        }
     }
 }
 
@@ -82,13 +140,21 @@ pub struct DataFilePaths {
 }
 
 /// Atomic write - write to temp file then rename
-/// This prevents data corruption from incomplete writes
+/// This prevents data corruption from incomplete writes.
+/// The payload is validated against the data model first, so a malformed
+/// write is rejected instead of replacing the existing file.
+/// With `expected_revision` (from `read_data_file` or a previous write), the
+/// write fails with a conflict if the file has changed since. Returns the
+/// revision of the written content.
 #[tauri::command]
 pub fn atomic_write_file(
     app_handle: tauri::AppHandle,
     file_type: String,
     content: String,
-) -> Result<(), String> {
+    expected_revision: Option<String>,
+    snapshots: State<'_, Arc<Snapshots>>,
+    watcher_state: State<'_, Arc<WatcherState>>,
+) -> Result<String, WriteError> {
     let data_dir = app_handle
         .path()
         .app_data_dir()
@@ -103,40 +169,128 @@ pub fn atomic_write_file(
         "active" => DataFileType::Active,
         "archive" => DataFileType::Archive,
         "pomodoro_history" => DataFileType::PomodoroHistory,
-        _ => return Err(format!("Unknown file type: {}", file_type)),
+        _ => return Err(format!("Unknown file type: {}", file_type).into()),
     };
 
-    let final_path = data_dir.join(file_type.filename());
-    let temp_path = data_dir.join(file_type.temp_filename());
-
-    // Step 1: Write to temporary file
-    {
-        let mut temp_file = fs::File::create(&temp_path)
-            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    file_type.validate_content(&content)?;
+
+    // Active data is held to the priority quotas, which may demote tasks
+    let (revision, report) = write_data_file_if_unchanged(
+        &data_dir,
+        file_type,
+        &content,
+        expected_revision.as_deref(),
+        |written| {
+            watcher_state.record_self_write(
+                file_type.filename(),
+                durable::revision(written.as_bytes()),
+            )
+        },
+    )?;
+    // Snapshot what the frontend sent, so that merging after a demotion
+    // takes the demoted priorities from disk instead of writing them back
+    snapshots.set(file_type, content);
+
+    if let Some(report) = report {
+        use tauri::Emitter;
+        if let Err(e) = app_handle.emit("quota-enforced", report) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
 
-        temp_file
-            .write_all(content.as_bytes())
-            .map_err(|e| format!("Failed to write to temp file: {}", e))?;
+    Ok(revision)
+}
 
-        // Ensure data is flushed to disk
-        temp_file
-            .sync_all()
-            .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+/// Replace a data file only if it is still at `expected_revision` (skipped
+/// when `None`). `before_write` sees the content about to be written, after
+/// quota enforcement. Returns its revision and the quota report.
+pub fn write_data_file_if_unchanged(
+    data_dir: &Path,
+    file_type: DataFileType,
+    content: &str,
+    expected_revision: Option<&str>,
+    before_write: impl FnOnce(&str),
+) -> Result<(String, Option<QuotaReport>), WriteError> {
+    let _lock = lock::acquire(data_dir)?;
+
+    let path = data_dir.join(file_type.filename());
+    if let Some(expected) = expected_revision {
+        let actual = durable::current_revision(&path)?;
+        if actual.as_deref() != Some(expected) {
+            return Err(WriteError::Conflict {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
     }
 
-    // Step 2: Atomic rename (replaces existing file)
-    fs::rename(&temp_path, &final_path)
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+    let previous = read_previous(&path)?;
+    let enforced = enforce_quota(file_type, content, previous.as_deref())?;
+    before_write(&enforced.content);
+    durable::write_atomic(&path, enforced.content.as_bytes())?;
 
-    Ok(())
+    Ok((durable::revision(enforced.content.as_bytes()), enforced.report))
+}
+
+/// Replace a data file durably. The caller must hold the data directory lock.
+/// Active data is held to the priority quotas against the file it replaces,
+/// which may demote tasks; returns what was written and the quota report.
+pub fn write_data_file(
+    data_dir: &Path,
+    file_type: DataFileType,
+    content: &str,
+) -> Result<Enforced, WriteError> {
+    let path = data_dir.join(file_type.filename());
+    let previous = read_previous(&path)?;
+    write_data_file_over(data_dir, file_type, content, previous.as_deref())
+}
+
+/// [`write_data_file`] with the quotas checked against `previous` rather than
+/// the file on disk, for content that replaces a file no longer there
+pub fn write_data_file_over(
+    data_dir: &Path,
+    file_type: DataFileType,
+    content: &str,
+    previous: Option<&str>,
+) -> Result<Enforced, WriteError> {
+    let enforced = enforce_quota(file_type, content, previous)?;
+    durable::write_atomic(&data_dir.join(file_type.filename()), enforced.content.as_bytes())?;
+    Ok(enforced)
+}
+
+/// Content of the data file about to be replaced, if there is one
+fn read_previous(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {:?}: {}", path, e)),
+    }
+}
+
+/// Only active data has priority quotas; other files are written as is
+fn enforce_quota(
+    file_type: DataFileType,
+    content: &str,
+    previous: Option<&str>,
+) -> Result<Enforced, WriteError> {
+    if file_type == DataFileType::Active {
+        quota::enforce(content, previous)
+    } else {
+        Ok(Enforced {
+            content: content.to_string(),
+            report: None,
+        })
+    }
 }
 
-/// Read a data file
+/// Read a data file. A corrupt file is quarantined and recovered from the
+/// newest intact copy; the returned report tells the UI what happened.
 #[tauri::command]
 pub fn read_data_file(
     app_handle: tauri::AppHandle,
     file_type: String,
-) -> Result<Option<String>, String> {
+    snapshots: State<'_, Arc<Snapshots>>,
+) -> Result<DataFileRead, String> {
     let data_dir = app_handle
         .path()
         .app_data_dir()
@@ -149,54 +303,79 @@ pub fn read_data_file(
         _ => return Err(format!("Unknown file type: {}", file_type)),
     };
 
-    let file_path = data_dir.join(file_type.filename());
-
-    if !file_path.exists() {
-        return Ok(None);
+    let read = recovery::read_data_file(&data_dir, file_type)?;
+    if let Some(content) = &read.content {
+        snapshots.set(file_type, content.clone());
     }
 
-    let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    Ok(Some(content))
+    Ok(read)
 }
 
-/// Create a backup of the current data files
+/// Merge external changes to a data file into the frontend's in-memory
+/// state (`local_content`). Conflicting edits keep the local value and are
+/// also emitted as `data-merge-conflicts` for the UI.
 #[tauri::command]
-pub fn backup_data(app_handle: tauri::AppHandle) -> Result<String, String> {
+pub fn merge_data_file(
+    app_handle: tauri::AppHandle,
+    file_type: String,
+    local_content: String,
+    snapshots: State<'_, Arc<Snapshots>>,
+) -> Result<MergeResult, String> {
+    use tauri::Emitter;
+
     let data_dir = app_handle
         .path()
         .app_data_dir()
         .map_err(|e| e.to_string())?;
 
-    // Create backup directory with timestamp
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    let backup_dir = data_dir.join(format!("backup_{}", timestamp));
-    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    let file_type = match file_type.as_str() {
+        "active" => DataFileType::Active,
+        "archive" => DataFileType::Archive,
+        "pomodoro_history" => DataFileType::PomodoroHistory,
+        _ => return Err(format!("Unknown file type: {}", file_type)),
+    };
 
-    // Copy all data files
-    let files = [
-        DataFileType::Active,
-        DataFileType::Archive,
-        DataFileType::PomodoroHistory,
-    ];
+    let result = merge::merge_with_disk(&data_dir, file_type, &snapshots, &local_content)?;
 
-    let mut backed_up = Vec::new();
-    for file_type in files {
-        let source = data_dir.join(file_type.filename());
-        if source.exists() {
-            let dest = backup_dir.join(file_type.filename());
-            fs::copy(&source, &dest).map_err(|e| e.to_string())?;
-            backed_up.push(file_type.filename());
+    if !result.conflicts.is_empty() {
+        let payload = serde_json::json!({
+            "fileType": file_type.name(),
+            "conflicts": result.conflicts,
+        });
+        if let Err(e) = app_handle.emit("data-merge-conflicts", payload) {
+            eprintln!("Failed to emit event: {}", e);
         }
     }
 
-    // Also backup legacy file if it exists
-    let legacy_source = data_dir.join("focusflow_data.json");
-    if legacy_source.exists() {
-        let legacy_dest = backup_dir.join("focusflow_data.json");
-        fs::copy(&legacy_source, &legacy_dest).map_err(|e| e.to_string())?;
-        backed_up.push("focusflow_data.json");
+    Ok(result)
+}
+
+/// Create a backup of the current data files, then prune old backups
+/// according to the retention policy in settings.
+/// `compressed` picks a single zip archive over a plain directory; when
+/// omitted, the `compressBackups` setting decides.
+#[tauri::command]
+pub fn backup_data(app_handle: tauri::AppHandle, compressed: Option<bool>) -> Result<String, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let format = match compressed {
+        Some(true) => BackupFormat::Zip,
+        Some(false) => BackupFormat::Directory,
+        None => backup::load_backup_format(&data_dir),
+    };
+    let (backup_dir, backed_up) = backup::create_backup(&data_dir, format)?;
+
+    // Pruning failures must not fail the backup that was just taken
+    let policy = backup::load_retention_policy(&data_dir);
+    match backup::prune_backups(&data_dir, &policy, false) {
+        Ok(report) if !report.removed.is_empty() => {
+            println!("Pruned {} old backup(s)", report.removed.len());
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to prune backups: {}", e),
     }
 
     Ok(format!(
@@ -206,6 +385,85 @@ pub fn backup_data(app_handle: tauri::AppHandle) -> Result<String, String> {
     ))
 }
 
+/// List existing backups, newest first
+#[tauri::command]
+pub fn list_backups(app_handle: tauri::AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    backup::list_backups(&data_dir)
+}
+
+/// Remove backups not selected by the retention policy.
+/// Uses the policy from settings unless one is passed explicitly;
+/// with `dry_run` nothing is deleted and the report shows what would be.
+#[tauri::command]
+pub fn prune_backups(
+    app_handle: tauri::AppHandle,
+    policy: Option<RetentionPolicy>,
+    dry_run: Option<bool>,
+) -> Result<PruneReport, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let policy = policy.unwrap_or_else(|| backup::load_retention_policy(&data_dir));
+    backup::prune_backups(&data_dir, &policy, dry_run.unwrap_or(false))
+}
+
+/// Check that a backup is readable and matches its checksum manifest
+#[tauri::command]
+pub fn verify_backup(
+    app_handle: tauri::AppHandle,
+    backup_id: String,
+) -> Result<BackupVerification, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    Ok(backup::verify_backup(&data_dir, &backup_id))
+}
+
+/// Verify every backup, newest first
+#[tauri::command]
+pub fn verify_all_backups(app_handle: tauri::AppHandle) -> Result<Vec<BackupVerification>, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    backup::verify_all_backups(&data_dir)
+}
+
+/// Restore data files from a backup. The current state is snapshotted
+/// first; the frontend is told to reload every restored file.
+#[tauri::command]
+pub fn restore_backup(
+    app_handle: tauri::AppHandle,
+    backup_id: String,
+) -> Result<RestoreReport, String> {
+    use tauri::Emitter;
+
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let report = backup::restore_backup(&data_dir, &backup_id)?;
+
+    for file_type in &report.restored {
+        if let Err(e) = app_handle.emit("data-file-changed", FileChange::reload(*file_type)) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
+
+    Ok(report)
+}
+
 /// Migrate from legacy single file to separated files
 #[tauri::command]
 pub fn migrate_legacy_data(app_handle: tauri::AppHandle) -> Result<bool, String> {
@@ -222,6 +480,12 @@ pub fn migrate_legacy_data(app_handle: tauri::AppHandle) -> Result<bool, String>
         return Ok(false); // No migration needed
     }
 
+    // Re-check under the lock: another process may have migrated meanwhile
+    let _lock = lock::acquire(&data_dir)?;
+    if !legacy_path.exists() || active_path.exists() {
+        return Ok(false);
+    }
+
     // Read legacy data
     let legacy_content = fs::read_to_string(&legacy_path)
         .map_err(|e| format!("Failed to read legacy file: {}", e))?;
@@ -255,30 +519,21 @@ pub fn migrate_legacy_data(app_handle: tauri::AppHandle) -> Result<bool, String>
         "sessions": legacy_data.get("pomodoroHistory").unwrap_or(&serde_json::json!([]))
     });
 
-    // Write separated files atomically
-    let write_atomic = |path: &PathBuf, data: &serde_json::Value| -> Result<(), String> {
-        let temp_path = path.with_extension("json.tmp");
-        let content = serde_json::to_string_pretty(data)
-            .map_err(|e| format!("Failed to serialize: {}", e))?;
-
-        {
-            let mut file = fs::File::create(&temp_path)
-                .map_err(|e| format!("Failed to create temp file: {}", e))?;
-            file.write_all(content.as_bytes())
-                .map_err(|e| format!("Failed to write temp file: {}", e))?;
-            file.sync_all()
-                .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+    // Write separated files, validated and held to the quotas like any other write
+    for (file_type, mut data) in [
+        (DataFileType::Active, active_data),
+        (DataFileType::Archive, archive_data),
+        (DataFileType::PomodoroHistory, pomodoro_data),
+    ] {
+        // Fields the legacy file didn't have are left out rather than written as null
+        if let Some(fields) = data.as_object_mut() {
+            fields.retain(|_, value| !value.is_null());
         }
-
-        fs::rename(&temp_path, path)
-            .map_err(|e| format!("Failed to rename: {}", e))?;
-
-        Ok(())
-    };
-
-    write_atomic(&data_dir.join(DataFileType::Active.filename()), &active_data)?;
-    write_atomic(&data_dir.join(DataFileType::Archive.filename()), &archive_data)?;
-    write_atomic(&data_dir.join(DataFileType::PomodoroHistory.filename()), &pomodoro_data)?;
+        let content = serde_json::to_string_pretty(&data)
+            .map_err(|e| format!("Failed to serialize: {}", e))?;
+        file_type.validate_content(&content)?;
+        write_data_file(&data_dir, file_type, &content)?;
+    }
 
     // Rename legacy file to indicate migration complete
     let migrated_path = data_dir.join("focusflow_data.migrated.json");
@@ -304,7 +559,7 @@ pub fn trigger_reload(app_handle: tauri::AppHandle, file_type: String) -> Result
     use tauri::Emitter;
 
     app_handle
-        .emit("data-file-changed", file_type)
+        .emit("data-file-changed", FileChange::reload(file_type))
         .map_err(|e| format!("Failed to emit event: {}", e))?;
 
     Ok(())
@@ -315,6 +570,7 @@ pub fn trigger_reload(app_handle: tauri::AppHandle, file_type: String) -> Result
 pub fn append_archive_tasks(
     app_handle: tauri::AppHandle,
     new_tasks_json: String,
+    watcher_state: State<'_, Arc<WatcherState>>,
 ) -> Result<(), String> {
     let data_dir = app_handle
         .path()
@@ -327,7 +583,6 @@ pub fn append_archive_tasks(
     }
 
     let archive_path = data_dir.join(DataFileType::Archive.filename());
-    let temp_path = data_dir.join(DataFileType::Archive.temp_filename());
 
     // Parse new tasks
     let new_tasks: Vec<serde_json::Value> = serde_json::from_str(&new_tasks_json)
@@ -337,6 +592,8 @@ pub fn append_archive_tasks(
         return Ok(());
     }
 
+    let _lock = lock::acquire(&data_dir)?;
+
     // Read existing archive or create default
     let mut archive_data = if archive_path.exists() {
         let content = fs::read_to_string(&archive_path)
@@ -371,43 +628,377 @@ pub fn append_archive_tasks(
         });
     }
 
-    // Write to temp file
-    {
-        let content = serde_json::to_string_pretty(&archive_data)
-            .map_err(|e| format!("Failed to serialize archive data: {}", e))?;
+    let content = serde_json::to_string_pretty(&archive_data)
+        .map_err(|e| format!("Failed to serialize archive data: {}", e))?;
 
-        let mut temp_file = fs::File::create(&temp_path)
-            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    watcher_state.record_self_write(
+        DataFileType::Archive.filename(),
+        durable::revision(content.as_bytes()),
+    );
+    durable::write_atomic(&archive_path, content.as_bytes())
+}
 
-        temp_file
-            .write_all(content.as_bytes())
-            .map_err(|e| format!("Failed to write to temp file: {}", e))?;
+/// Suspend the file watcher (used during drag-and-drop operations).
+/// Returns a lease ID for `resume_watcher`. The lease expires by itself after
+/// `timeout_ms` (default 30 s), so a reloaded or crashed webview can't leave
+/// the watcher paused.
+#[tauri::command]
+pub fn suspend_watcher(
+    watcher_state: State<'_, Arc<WatcherState>>,
+    timeout_ms: Option<u64>,
+) -> Result<String, String> {
+    let timeout = timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_LEASE);
+    let lease_id = watcher_state.suspend(timeout);
+    println!("File watcher suspended (lease {}, {:?})", lease_id, timeout);
+    Ok(lease_id)
+}
 
-        // Ensure data is flushed to disk
-        temp_file
-            .sync_all()
-            .map_err(|e| format!("Failed to sync temp file: {}", e))?;
+/// Release a suspension lease (called after drag-and-drop completes).
+/// Changes made while suspended are delivered once no lease remains.
+#[tauri::command]
+pub fn resume_watcher(
+    watcher_state: State<'_, Arc<WatcherState>>,
+    lease_id: String,
+) -> Result<(), String> {
+    if watcher_state.resume(&lease_id) {
+        println!("File watcher lease released: {}", lease_id);
+    } else {
+        println!("File watcher lease already expired: {}", lease_id);
     }
+    Ok(())
+}
 
-    // Atomic rename
-    fs::rename(&temp_path, &archive_path)
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+/// Health of the file watcher, so the UI can tell when auto-reload is down
+#[tauri::command]
+pub fn watcher_status(
+    watcher_state: State<'_, Arc<WatcherState>>,
+) -> Result<WatcherStatus, String> {
+    Ok(watcher_state.status())
+}
 
-    Ok(())
+/// Parse quick-add text (`!A +project @context ~date ...`) the same way the
+/// frontend's `parseTaskInput` does
+#[tauri::command]
+pub fn parse_task_input(input: String) -> Result<ParsedTask, String> {
+    Ok(parser::parse_task_input(&input))
+}
+
+fn read_active(data_dir: &Path) -> Result<ActiveData, String> {
+    let read = recovery::read_data_file(data_dir, DataFileType::Active)?;
+    // No file yet means no tasks and default settings
+    let content = read.content.unwrap_or_else(|| "{}".to_string());
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse active data: {}", e))
 }
 
-/// Suspend the file watcher (used during drag-and-drop operations)
+/// Used and remaining capacity per priority in `active.json`
 #[tauri::command]
-pub fn suspend_watcher(watcher_state: State<'_, Arc<WatcherState>>) -> Result<(), String> {
-    watcher_state.paused.store(true, Ordering::SeqCst);
-    println!("File watcher suspended");
-    Ok(())
+pub fn quota_status(app_handle: tauri::AppHandle) -> Result<QuotaStatus, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    Ok(quota::status(&read_active(&data_dir)?))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {}: {}", date, e))
 }
 
-/// Resume the file watcher (called after drag-and-drop completes)
+/// The next `n` dates (`YYYY-MM-DD`) after `from` of an RRULE or short
+/// recurrence pattern
 #[tauri::command]
-pub fn resume_watcher(watcher_state: State<'_, Arc<WatcherState>>) -> Result<(), String> {
-    watcher_state.paused.store(false, Ordering::SeqCst);
-    println!("File watcher resumed");
-    Ok(())
+pub fn next_occurrences(rule: String, from: String, n: usize) -> Result<Vec<String>, String> {
+    let from = parse_date(&from)?;
+    let dates = recurrence::next_occurrences(&rule, from, n)?;
+    Ok(dates.iter().map(|d| d.format("%Y-%m-%d").to_string()).collect())
+}
+
+/// The unit a date (`YYYY-MM-DD`, default today) belongs to under the
+/// configured layout, widened by `unitBoundaryFlexHours`
+#[tauri::command]
+pub fn get_unit(
+    app_handle: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<FlexibleUnit, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+    let date = match date {
+        Some(date) => parse_date(&date)?,
+        None => Local::now().date_naive(),
+    };
+
+    let settings = read_active(&data_dir)?.settings;
+    let unit = settings.unit_layout.unit_for(date);
+    Ok(unit.with_flex(settings.unit_boundary_flex_hours))
+}
+
+/// The units and review day of the week containing a date (default today)
+#[tauri::command]
+pub fn get_week_units(
+    app_handle: tauri::AppHandle,
+    date: Option<String>,
+) -> Result<Vec<Unit>, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+    let date = match date {
+        Some(date) => parse_date(&date)?,
+        None => Local::now().date_naive(),
+    };
+
+    Ok(read_active(&data_dir)?.settings.unit_layout.week_units(date))
+}
+
+/// Switch to a new unit layout, re-labelling every task's `unitStart` for it.
+/// Returns how many tasks were re-labelled.
+#[tauri::command]
+pub fn set_unit_layout(app_handle: tauri::AppHandle, layout: UnitLayout) -> Result<usize, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    units::set_layout(&data_dir, &layout)
+}
+
+/// Priority-weighted A-E completion of the period starting at `period_start`;
+/// `None` if nothing was planned in it
+#[tauri::command]
+pub fn period_completion(
+    app_handle: tauri::AppHandle,
+    period_start: String,
+) -> Result<Option<f64>, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let active = read_active(&data_dir)?;
+    let layout = &active.settings.unit_layout;
+    let period_start = layout.unit_start(parse_date(&period_start)?);
+    Ok(cycle::weighted_completion(&active.tasks, layout, period_start))
+}
+
+/// Advance the 2-day cycle to today's unit now instead of at the next
+/// midnight. `None` if it was already there.
+#[tauri::command]
+pub fn evaluate_cycle(app_handle: tauri::AppHandle) -> Result<Option<Advance>, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    cycle::run(&data_dir, Local::now().date_naive())
+}
+
+/// The backend pomodoro timer as it stands now
+#[tauri::command]
+pub fn pomodoro_status(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |_, _| None)
+}
+
+/// Start a work session on a task with the pomodoro lengths from settings,
+/// abandoning any session in progress
+#[tauri::command]
+pub fn start_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+    task_id: String,
+) -> Result<Snapshot, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let durations = Durations::from_settings(&read_active(&data_dir)?.settings);
+    pomodoro.command(&app_handle, |timer, now| Some(timer.start(task_id, durations, now)))
+}
+
+#[tauri::command]
+pub fn pause_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, now| {
+        timer.pause(now);
+        None
+    })
+}
+
+#[tauri::command]
+pub fn resume_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, now| {
+        timer.resume(now);
+        None
+    })
+}
+
+/// Pause a running pomodoro or resume a paused one
+#[tauri::command]
+pub fn toggle_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, now| {
+        timer.toggle(now);
+        None
+    })
+}
+
+/// Stop the timer without recording the current session
+#[tauri::command]
+pub fn stop_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, _| timer.stop())
+}
+
+/// Finish the current phase now; a skipped work session is recorded
+#[tauri::command]
+pub fn skip_pomodoro(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, now| timer.skip(now))
+}
+
+/// Count an interruption of the current work session
+#[tauri::command]
+pub fn record_interruption(
+    app_handle: tauri::AppHandle,
+    pomodoro: State<'_, Arc<Pomodoro>>,
+    reason: Option<String>,
+) -> Result<Snapshot, String> {
+    pomodoro.command(&app_handle, |timer, _| {
+        timer.interrupt(reason);
+        None
+    })
+}
+
+/// Put a task's reminder off, returning the `remindAt` it comes back at
+#[tauri::command]
+pub fn snooze_reminder(
+    app_handle: tauri::AppHandle,
+    task_id: String,
+    snooze: Snooze,
+) -> Result<String, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    reminders::snooze(&data_dir, &task_id, snooze, Local::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn active(mode: &str, priorities: &[&str]) -> String {
+        let tasks: Vec<_> = priorities
+            .iter()
+            .enumerate()
+            .map(|(i, priority)| {
+                json!({
+                    "id": format!("t{}", i),
+                    "content": "task",
+                    "priority": priority,
+                    "createdAt": format!("2026-01-0{}T09:00:00Z", i + 1)
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&json!({
+            "version": "2.0",
+            "tasks": tasks,
+            "settings": { "quotaMode": mode }
+        }))
+        .unwrap()
+    }
+
+    fn priorities(content: &str) -> Vec<String> {
+        let data: ActiveData = serde_json::from_str(content).unwrap();
+        data.tasks
+            .iter()
+            .map(|task| format!("{:?}", task.priority))
+            .collect()
+    }
+
+    #[test]
+    fn active_writes_are_held_to_the_quotas() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let content = active("demote", &["A", "A"]);
+        let written = write_data_file(dir.path(), DataFileType::Active, &content).unwrap();
+
+        assert_eq!(priorities(&written.content), ["B", "A"]);
+        assert!(written.report.is_some());
+        let on_disk = fs::read_to_string(dir.path().join("active.json")).unwrap();
+        assert_eq!(on_disk, written.content);
+    }
+
+    #[test]
+    fn reject_mode_compares_against_the_file_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("active.json");
+        let on_disk = active("reject", &["A", "A"]);
+        fs::write(&path, &on_disk).unwrap();
+
+        // Not worse than what is already there
+        write_data_file(dir.path(), DataFileType::Active, &on_disk).unwrap();
+
+        let more = active("reject", &["A", "A", "A"]);
+        let result = write_data_file(dir.path(), DataFileType::Active, &more);
+        assert!(matches!(result, Err(WriteError::QuotaExceeded { .. })));
+        assert_eq!(fs::read_to_string(&path).unwrap(), on_disk);
+
+        // Against its own content, the same overflow is let through
+        write_data_file_over(dir.path(), DataFileType::Active, &more, Some(&more)).unwrap();
+    }
+
+    #[test]
+    fn checked_writes_see_the_enforced_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("active.json");
+        fs::write(&path, active("demote", &["A"])).unwrap();
+        let revision = durable::current_revision(&path).unwrap();
+
+        let mut seen = None;
+        let (written, report) = write_data_file_if_unchanged(
+            dir.path(),
+            DataFileType::Active,
+            &active("demote", &["A", "A"]),
+            revision.as_deref(),
+            |content| seen = Some(content.to_string()),
+        )
+        .unwrap();
+
+        let seen = seen.unwrap();
+        assert_eq!(priorities(&seen), ["B", "A"]);
+        assert_eq!(written, durable::revision(seen.as_bytes()));
+        assert!(report.is_some());
+
+        let stale = write_data_file_if_unchanged(
+            dir.path(),
+            DataFileType::Active,
+            &active("demote", &["B"]),
+            revision.as_deref(),
+            |_| {},
+        );
+        assert!(matches!(stale, Err(WriteError::Conflict { .. })));
+    }
 }
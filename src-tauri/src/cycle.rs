@@ -0,0 +1,400 @@
+//! The dynamic 2-day cycle (mirror of `src/lib/utils/cycleEngine.ts`).
+//!
+//! The work window normally equals the calendar unit. When a new unit
+//! begins, the period that just ended is scored by priority-weighted A-E
+//! completion and recorded in `cycleHistory`. A score below
+//! `MERGE_THRESHOLD` marks the new window as merged and rolls the period's
+//! unfinished A-E tasks forward, or, with `lowCompletionPrompt` on, leaves a
+//! pending review for the user to resolve. The review day never takes part.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{self, DataFileType};
+use crate::lock;
+use crate::model::{ActiveData, CycleHistoryEntry, CycleState, PendingReview, Priority, Task};
+use crate::quota;
+use crate::units::UnitLayout;
+
+/// A period whose weighted completion is below this ratio is under-completed
+pub const MERGE_THRESHOLD: f64 = 0.30;
+
+/// Scored periods kept in `cycleHistory`
+const HISTORY_LEN: usize = 60;
+
+/// Result of advancing the cycle into a new window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Advance {
+    pub state: CycleState,
+    /// The period that just ended, if it hadn't been scored yet
+    pub scored: Option<CycleHistoryEntry>,
+    /// Tasks rolled forward into the new window
+    pub rolled_task_ids: Vec<String>,
+    #[serde(skip)]
+    history: Vec<CycleHistoryEntry>,
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn is_active(priority: Priority) -> bool {
+    matches!(
+        priority,
+        Priority::A | Priority::B | Priority::C | Priority::D | Priority::E
+    )
+}
+
+/// Importance of an A-E task, from its quota: A weighs 5, E weighs 1
+fn weight(priority: Priority) -> f64 {
+    quota::quota(priority).map_or(1.0, |q| 6.0 - q as f64)
+}
+
+/// The A-E priority a task counts under: completed tasks keep the priority
+/// they had before completion
+fn effective_priority(task: &Task) -> Option<Priority> {
+    let priority = match task.priority {
+        Priority::G => task.original_priority?,
+        priority => priority,
+    };
+    is_active(priority).then_some(priority)
+}
+
+/// Start of the period a task belongs to
+fn period_of(task: &Task, layout: &UnitLayout) -> Option<NaiveDate> {
+    parse_date(&task.unit_start).map(|d| layout.unit_start(d))
+}
+
+/// Priority-weighted completion of the A-E tasks in the period starting at
+/// `period_start`. Cancelled tasks don't count. `None` if nothing was planned.
+pub fn weighted_completion(
+    tasks: &[Task],
+    layout: &UnitLayout,
+    period_start: NaiveDate,
+) -> Option<f64> {
+    let mut total = 0.0;
+    let mut done = 0.0;
+    for task in tasks {
+        if task.priority == Priority::H || period_of(task, layout) != Some(period_start) {
+            continue;
+        }
+        let Some(priority) = effective_priority(task) else {
+            continue;
+        };
+        total += weight(priority);
+        if task.priority == Priority::G && task.completed {
+            done += weight(priority);
+        }
+    }
+    (total > 0.0).then(|| done / total)
+}
+
+/// IDs of the unfinished A-E tasks of the period starting at `period_start`
+pub fn unfinished(tasks: &[Task], layout: &UnitLayout, period_start: NaiveDate) -> Vec<String> {
+    tasks
+        .iter()
+        .filter(|t| {
+            is_active(t.priority) && !t.completed && period_of(t, layout) == Some(period_start)
+        })
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Advance the cycle to the unit containing `today`. `None` if it is
+/// already there, or on the review day. Idempotent within a window.
+pub fn evaluate(data: &ActiveData, today: NaiveDate) -> Option<Advance> {
+    let layout = &data.settings.unit_layout;
+    let unit = layout.unit_for(today);
+    if unit.is_review_day {
+        return None;
+    }
+    let start = format_date(unit.start);
+    let end = format_date(unit.end);
+    let history = data.cycle_history.clone().unwrap_or_default();
+
+    // First run: start at the current unit with nothing to score
+    let Some(state) = &data.cycle_state else {
+        return Some(Advance {
+            state: CycleState {
+                anchor_start: start,
+                window_end: end,
+                merged: false,
+                last_evaluated_start: String::new(),
+                pending_review: None,
+                extra: Map::new(),
+            },
+            scored: None,
+            rolled_task_ids: Vec::new(),
+            history,
+        });
+    };
+    if state.anchor_start == start {
+        return None;
+    }
+
+    let mut advance = Advance {
+        state: CycleState {
+            anchor_start: start,
+            window_end: end,
+            merged: false,
+            last_evaluated_start: state.anchor_start.clone(),
+            pending_review: None,
+            extra: Map::new(),
+        },
+        scored: None,
+        rolled_task_ids: Vec::new(),
+        history,
+    };
+
+    // Score the period that just ended, once
+    if state.last_evaluated_start != state.anchor_start {
+        let period = parse_date(&state.anchor_start);
+        let completion = period.and_then(|p| weighted_completion(&data.tasks, layout, p));
+        let low = completion.is_some_and(|c| c < MERGE_THRESHOLD);
+
+        let entry = CycleHistoryEntry {
+            period_start: state.anchor_start.clone(),
+            completion,
+            merged: low,
+            extra: Map::new(),
+        };
+        advance
+            .history
+            .retain(|h| h.period_start != entry.period_start);
+        advance.history.push(entry.clone());
+        let excess = advance.history.len().saturating_sub(HISTORY_LEN);
+        advance.history.drain(..excess);
+        advance.scored = Some(entry);
+
+        if let (true, Some(period), Some(completion)) = (low, period, completion) {
+            if data.settings.low_completion_prompt {
+                // Leave the merge to the user's micro-review
+                advance.state.pending_review = Some(PendingReview {
+                    period_start: state.anchor_start.clone(),
+                    completion,
+                    extra: Map::new(),
+                });
+            } else {
+                advance.rolled_task_ids = unfinished(&data.tasks, layout, period);
+                advance.state.merged = true;
+            }
+        }
+    }
+
+    Some(advance)
+}
+
+/// Write an advance into `active` (the raw `active.json`), leaving fields the
+/// model doesn't know about intact
+fn apply(active: &mut Value, advance: &Advance) -> Result<(), String> {
+    let Some(obj) = active.as_object_mut() else {
+        return Err("Active data is not a JSON object".to_string());
+    };
+    let state = serde_json::to_value(&advance.state)
+        .map_err(|e| format!("Failed to serialize cycle state: {}", e))?;
+    let history = serde_json::to_value(&advance.history)
+        .map_err(|e| format!("Failed to serialize cycle history: {}", e))?;
+    obj.insert("cycleState".to_string(), state);
+    obj.insert("cycleHistory".to_string(), history);
+
+    let Some(tasks) = obj.get_mut("tasks").and_then(Value::as_array_mut) else {
+        return Ok(());
+    };
+    for task in tasks {
+        let Some(task) = task.as_object_mut() else {
+            continue;
+        };
+        let rolled = task
+            .get("id")
+            .and_then(Value::as_str)
+            .is_some_and(|id| advance.rolled_task_ids.iter().any(|r| r == id));
+        if !rolled {
+            continue;
+        }
+        task.insert(
+            "unitStart".to_string(),
+            Value::String(advance.state.anchor_start.clone()),
+        );
+        let unit_override = task
+            .entry("unitOverride")
+            .or_insert_with(|| Value::Object(Default::default()));
+        if !unit_override.is_object() {
+            *unit_override = Value::Object(Default::default());
+        }
+        if let Some(unit_override) = unit_override.as_object_mut() {
+            unit_override.insert(
+                "extendedUntil".to_string(),
+                Value::String(advance.state.window_end.clone()),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Advance the cycle in `active.json` to the unit containing `today`.
+/// Returns `None` if there was nothing to do.
+pub fn run(data_dir: &Path, today: NaiveDate) -> Result<Option<Advance>, String> {
+    let path = data_dir.join(DataFileType::Active.filename());
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let _lock = lock::acquire(data_dir)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let data: ActiveData = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let Some(advance) = evaluate(&data, today) else {
+        return Ok(None);
+    };
+
+    let mut active: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+    apply(&mut active, &advance)?;
+    let content = serde_json::to_string_pretty(&active)
+        .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+    commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+
+    Ok(Some(advance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    fn task(id: &str, priority: &str, original: Option<&str>, unit_start: &str) -> Value {
+        json!({
+            "id": id,
+            "content": id,
+            "priority": priority,
+            "originalPriority": original,
+            "completed": priority == "G",
+            "createdAt": "2026-03-01T08:00:00+00:00",
+            "unitStart": unit_start
+        })
+    }
+
+    fn active(tasks: Vec<Value>, prompt: bool, state: Option<Value>) -> Value {
+        json!({
+            "version": "2.0",
+            "tasks": tasks,
+            "settings": { "lowCompletionPrompt": prompt },
+            "cycleState": state
+        })
+    }
+
+    fn data(active: &Value) -> ActiveData {
+        serde_json::from_value(active.clone()).unwrap()
+    }
+
+    // Sunday 2026-03-01 starts unit 1, Tuesday 2026-03-03 unit 2
+    fn state_at_unit_1() -> Value {
+        json!({
+            "anchorStart": "2026-03-01",
+            "windowEnd": "2026-03-02",
+            "merged": false,
+            "lastEvaluatedStart": "2026-02-26"
+        })
+    }
+
+    #[test]
+    fn completion_is_weighted_by_priority() {
+        let tasks = data(&active(
+            vec![
+                task("a", "G", Some("A"), "2026-03-01"),
+                task("c", "C", None, "2026-03-02"),
+                task("h", "H", None, "2026-03-01"),
+                task("f", "F", None, "2026-03-01"),
+                task("later", "B", None, "2026-03-03"),
+            ],
+            true,
+            None,
+        ))
+        .tasks;
+
+        assert_eq!(
+            weighted_completion(&tasks, &UnitLayout::default(), date("2026-03-01")),
+            Some(5.0 / 8.0)
+        );
+        assert_eq!(
+            weighted_completion(&tasks, &UnitLayout::default(), date("2026-02-26")),
+            None
+        );
+    }
+
+    #[test]
+    fn seeds_state_and_waits_for_the_next_unit() {
+        let empty = active(vec![], true, None);
+        let seeded = evaluate(&data(&empty), date("2026-03-02")).unwrap();
+        assert_eq!(seeded.state.anchor_start, "2026-03-01");
+        assert_eq!(seeded.state.window_end, "2026-03-02");
+        assert!(seeded.scored.is_none());
+
+        let current = active(vec![], true, Some(state_at_unit_1()));
+        assert!(evaluate(&data(&current), date("2026-03-02")).is_none());
+        // Saturday is the review day
+        assert!(evaluate(&data(&current), date("2026-03-07")).is_none());
+    }
+
+    #[test]
+    fn low_completion_rolls_unfinished_tasks_forward() {
+        let mut file = active(
+            vec![
+                task("a", "A", None, "2026-03-01"),
+                task("e", "G", Some("E"), "2026-03-01"),
+                task("f", "F", None, "2026-03-01"),
+            ],
+            false,
+            Some(state_at_unit_1()),
+        );
+
+        let advance = evaluate(&data(&file), date("2026-03-03")).unwrap();
+        assert_eq!(advance.state.anchor_start, "2026-03-03");
+        assert_eq!(advance.state.last_evaluated_start, "2026-03-01");
+        assert!(advance.state.merged);
+        assert_eq!(advance.scored.as_ref().unwrap().completion, Some(1.0 / 6.0));
+        assert_eq!(advance.rolled_task_ids, ["a"]);
+
+        apply(&mut file, &advance).unwrap();
+        assert_eq!(file["tasks"][0]["unitStart"], "2026-03-03");
+        assert_eq!(
+            file["tasks"][0]["unitOverride"]["extendedUntil"],
+            "2026-03-04"
+        );
+        assert_eq!(file["tasks"][2]["unitStart"], "2026-03-01");
+        assert_eq!(file["cycleHistory"][0]["periodStart"], "2026-03-01");
+
+        // Running again in the same window changes nothing
+        assert!(evaluate(&data(&file), date("2026-03-04")).is_none());
+    }
+
+    #[test]
+    fn prompt_mode_leaves_a_pending_review() {
+        let file = active(
+            vec![task("a", "A", None, "2026-03-01")],
+            true,
+            Some(state_at_unit_1()),
+        );
+
+        let advance = evaluate(&data(&file), date("2026-03-05")).unwrap();
+        assert!(!advance.state.merged);
+        assert!(advance.rolled_task_ids.is_empty());
+        let review = advance.state.pending_review.unwrap();
+        assert_eq!(review.period_start, "2026-03-01");
+        assert_eq!(review.completion, 0.0);
+    }
+}
@@ -0,0 +1,249 @@
+//! Per-file trailing-edge debounce for data file events.
+//!
+//! Every event for a file restarts its timer; the change is delivered once the
+//! file has been quiet for the settle delay, so a burst of writes (temp file,
+//! rename, metadata) becomes a single change read after the last write. A file
+//! written continuously is still delivered after the max delay.
+
+use notify::{Event, EventKind};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::inbox;
+
+/// Files whose changes are reported
+pub const WATCHED_FILES: &[&str] = &[
+    "active.json",
+    "archive.json",
+    "pomodoro_history.json",
+    // Legacy file for backwards compatibility
+    "focusflow_data.json",
+];
+
+/// Quiet period a file must go without events before its change is delivered
+pub const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+/// Longest a change is held back while the file keeps being written
+pub const MAX_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy)]
+struct Burst {
+    first: Instant,
+    last: Instant,
+}
+
+#[derive(Debug)]
+pub struct Debouncer {
+    settle: Duration,
+    max_delay: Duration,
+    pending: BTreeMap<String, Burst>,
+}
+
+/// Names of watched files written by `event`
+fn changed_files(event: &Event) -> Vec<String> {
+    if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+        return Vec::new();
+    }
+
+    event
+        .paths
+        .iter()
+        // A file named like a data file dropped in the inbox is not one
+        .filter(|path| !inbox::is_in_inbox(path))
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| WATCHED_FILES.contains(&name.as_str()))
+        .collect()
+}
+
+impl Debouncer {
+    pub fn new(settle: Duration, max_delay: Duration) -> Self {
+        Debouncer {
+            settle,
+            max_delay,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Record the watched files touched by `event`
+    pub fn push(&mut self, event: &Event, now: Instant) {
+        for name in changed_files(event) {
+            self.touch(name, now);
+        }
+    }
+
+    /// Record a change to `name`, restarting its settle timer
+    pub fn touch(&mut self, name: String, now: Instant) {
+        self.pending
+            .entry(name)
+            .and_modify(|burst| burst.last = now)
+            .or_insert(Burst {
+                first: now,
+                last: now,
+            });
+    }
+
+    fn deadline(&self, burst: &Burst) -> Instant {
+        (burst.last + self.settle).min(burst.first + self.max_delay)
+    }
+
+    /// When the next pending change becomes due, if any
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|b| self.deadline(b)).min()
+    }
+
+    /// Take the files whose changes are due, in name order
+    pub fn take_due(&mut self, now: Instant) -> Vec<String> {
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, burst)| self.deadline(burst) <= now)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in &due {
+            self.pending.remove(name);
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode};
+    use std::path::PathBuf;
+
+    const SETTLE: Duration = Duration::from_millis(300);
+    const MAX: Duration = Duration::from_secs(3);
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn modify(name: &str) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content)))
+            .add_path(PathBuf::from("/data").join(name))
+    }
+
+    fn rename_into(name: &str) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::To)))
+            .add_path(PathBuf::from("/data").join(name))
+    }
+
+    #[test]
+    fn burst_is_coalesced_into_one_change() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        d.push(
+            &Event::new(EventKind::Create(CreateKind::File)).add_path("/data/active.json".into()),
+            t0,
+        );
+        d.push(&modify("active.json"), t0 + ms(10));
+        d.push(&rename_into("active.json"), t0 + ms(20));
+
+        assert!(d.take_due(t0 + ms(100)).is_empty());
+        assert_eq!(d.take_due(t0 + ms(320)), ["active.json"]);
+        assert_eq!(d.next_deadline(), None);
+    }
+
+    #[test]
+    fn every_event_restarts_the_settle_timer() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        // Writes straddling what used to be a 2 s bucket boundary
+        d.push(&modify("active.json"), t0 + ms(1990));
+        d.push(&modify("active.json"), t0 + ms(2010));
+
+        assert_eq!(d.next_deadline(), Some(t0 + ms(2310)));
+        assert!(d.take_due(t0 + ms(2300)).is_empty());
+        assert_eq!(d.take_due(t0 + ms(2310)), ["active.json"]);
+    }
+
+    #[test]
+    fn change_after_delivery_is_delivered_again() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        d.push(&modify("active.json"), t0);
+        assert_eq!(d.take_due(t0 + SETTLE), ["active.json"]);
+
+        // A later real change in the same couple of seconds is not swallowed
+        d.push(&modify("active.json"), t0 + ms(500));
+        assert_eq!(d.take_due(t0 + ms(800)), ["active.json"]);
+    }
+
+    #[test]
+    fn files_are_debounced_independently() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        d.push(&modify("active.json"), t0);
+        d.push(&modify("pomodoro_history.json"), t0 + ms(200));
+
+        assert_eq!(d.take_due(t0 + ms(300)), ["active.json"]);
+        assert_eq!(d.next_deadline(), Some(t0 + ms(500)));
+        assert_eq!(d.take_due(t0 + ms(500)), ["pomodoro_history.json"]);
+    }
+
+    #[test]
+    fn continuous_writes_are_delivered_after_max_delay() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        for i in 0..30 {
+            let t = t0 + ms(100 * i);
+            d.push(&modify("active.json"), t);
+            assert!(d.take_due(t).is_empty());
+        }
+
+        assert_eq!(d.take_due(t0 + MAX), ["active.json"]);
+    }
+
+    #[test]
+    fn ignores_temp_files_unknown_files_and_other_event_kinds() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        d.push(
+            &modify("active.json.0123456789abcdef0123456789abcdef.tmp"),
+            t0,
+        );
+        d.push(&modify("focusflow.lock"), t0);
+        d.push(&modify("notes.txt"), t0);
+        d.push(
+            &Event::new(EventKind::Remove(RemoveKind::File)).add_path("/data/active.json".into()),
+            t0,
+        );
+        d.push(
+            &Event::new(EventKind::Access(notify::event::AccessKind::Any))
+                .add_path("/data/active.json".into()),
+            t0,
+        );
+
+        assert_eq!(d.next_deadline(), None);
+    }
+
+    #[test]
+    fn ignores_data_file_names_in_the_inbox() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        d.push(&modify("inbox/active.json"), t0);
+
+        assert_eq!(d.next_deadline(), None);
+    }
+
+    #[test]
+    fn one_event_can_touch_several_files() {
+        let mut d = Debouncer::new(SETTLE, MAX);
+        let t0 = Instant::now();
+
+        let event = modify("archive.json").add_path(PathBuf::from("/data/active.json"));
+        d.push(&event, t0);
+
+        assert_eq!(d.take_due(t0 + SETTLE), ["active.json", "archive.json"]);
+    }
+}
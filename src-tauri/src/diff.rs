@@ -0,0 +1,186 @@
+//! Structured description of how a data file changed, sent with
+//! `data-file-changed` so views can patch in place.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::commands::DataFileType;
+
+/// Top-level keys whose changes aren't worth reporting
+const IGNORED_KEYS: [&str; 1] = ["lastModified"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDiff {
+    /// IDs of tasks (sessions for pomodoro history) that are new
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Settings keys whose value changed
+    pub settings_changed: Vec<String>,
+    /// Other top-level keys that changed (reviews, cycleState, ...)
+    pub other_changed: Vec<String>,
+}
+
+impl DataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.settings_changed.is_empty()
+            && self.other_changed.is_empty()
+    }
+}
+
+/// Payload of the `data-file-changed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    pub file_type: String,
+    /// `None` when there was no previous version to compare against; the
+    /// frontend should treat the whole file as changed
+    pub diff: Option<DataDiff>,
+}
+
+impl FileChange {
+    /// A change with no diff, forcing a full reload
+    pub fn reload(file_type: impl Into<String>) -> Self {
+        FileChange {
+            file_type: file_type.into(),
+            diff: None,
+        }
+    }
+}
+
+fn items_by_id(doc: &Value, key: &str) -> HashMap<String, Value> {
+    doc.get(key)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let id = item.get("id")?.as_str()?;
+                    Some((id.to_string(), item.clone()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keys of two objects whose values differ, sorted
+fn changed_keys(
+    before: Option<&Map<String, Value>>,
+    after: Option<&Map<String, Value>>,
+) -> Vec<String> {
+    let empty = Map::new();
+    let (before, after) = (before.unwrap_or(&empty), after.unwrap_or(&empty));
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|k| before.get(*k) != after.get(*k))
+        .cloned()
+        .collect()
+}
+
+/// Diff two versions of a data file
+pub fn diff_documents(file_type: DataFileType, before: &Value, after: &Value) -> DataDiff {
+    let list = file_type.list_key();
+    let old_items = items_by_id(before, list);
+    let new_items = items_by_id(after, list);
+
+    let mut diff = DataDiff::default();
+
+    for (id, item) in &new_items {
+        match old_items.get(id) {
+            None => diff.added.push(id.clone()),
+            Some(old) if old != item => diff.modified.push(id.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old_items
+        .keys()
+        .filter(|id| !new_items.contains_key(*id))
+        .cloned()
+        .collect();
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.modified.sort();
+
+    diff.settings_changed = changed_keys(
+        before.get("settings").and_then(Value::as_object),
+        after.get("settings").and_then(Value::as_object),
+    );
+
+    diff.other_changed = changed_keys(before.as_object(), after.as_object())
+        .into_iter()
+        .filter(|k| k != list && k != "settings" && !IGNORED_KEYS.contains(&k.as_str()))
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn tasks_are_reported_as_added_removed_or_modified() {
+        let before = json!({
+            "lastModified": "2026-03-04T10:00:00Z",
+            "tasks": [
+                { "id": "kept", "content": "same" },
+                { "id": "edited", "content": "old" },
+                { "id": "gone", "content": "bye" }
+            ]
+        });
+        let after = json!({
+            "lastModified": "2026-03-04T11:00:00Z",
+            "tasks": [
+                { "id": "new", "content": "hi" },
+                { "id": "edited", "content": "new" },
+                { "id": "kept", "content": "same" }
+            ]
+        });
+
+        let diff = diff_documents(DataFileType::Active, &before, &after);
+        assert_eq!(diff.added, ["new"]);
+        assert_eq!(diff.removed, ["gone"]);
+        assert_eq!(diff.modified, ["edited"]);
+        // Reordering and lastModified alone are not changes
+        assert!(diff.settings_changed.is_empty());
+        assert!(diff.other_changed.is_empty());
+    }
+
+    #[test]
+    fn settings_and_other_keys_are_reported_by_name() {
+        let before = json!({
+            "tasks": [],
+            "settings": { "theme": "dark", "language": "en-US" },
+            "reviews": []
+        });
+        let after = json!({
+            "tasks": [],
+            "settings": { "theme": "light", "language": "en-US", "dueReminders": false },
+            "reviews": [{ "id": "r1" }],
+            "cycleState": { "anchorStart": "2026-03-01" }
+        });
+
+        let diff = diff_documents(DataFileType::Active, &before, &after);
+        assert_eq!(diff.settings_changed, ["dueReminders", "theme"]);
+        assert_eq!(diff.other_changed, ["cycleState", "reviews"]);
+        assert!(diff.added.is_empty() && diff.modified.is_empty());
+        assert!(!diff.is_empty());
+        assert!(diff_documents(DataFileType::Active, &after, &after).is_empty());
+    }
+
+    #[test]
+    fn pomodoro_history_is_diffed_by_session() {
+        let before = json!({ "sessions": [{ "id": "p1" }] });
+        let after = json!({ "sessions": [{ "id": "p1" }, { "id": "p2" }] });
+        let diff = diff_documents(DataFileType::PomodoroHistory, &before, &after);
+        assert_eq!(diff.added, ["p2"]);
+        assert!(diff.other_changed.is_empty());
+    }
+}
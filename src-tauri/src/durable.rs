@@ -0,0 +1,259 @@
+//! Durable file replacement shared by every writer of the data directory.
+//!
+//! Content goes to a uniquely named temp file next to the target
+//! (`<name>.<uuid>.tmp`), is fsynced, then renamed over the target. On Linux
+//! the parent directory is fsynced as well so the rename itself survives a
+//! crash. Temp files left behind by a crash are swept at startup.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Temp files younger than this may belong to a writer that is still running
+pub const STALE_TEMP_AGE: Duration = Duration::from_secs(5 * 60);
+
+const TEMP_EXTENSION: &str = ".tmp";
+
+fn temp_path_for(path: &Path) -> Result<PathBuf, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid write target: {}", path.display()))?;
+    let temp_name = format!(
+        "{}.{}{}",
+        name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple(),
+        TEMP_EXTENSION
+    );
+    Ok(path.with_file_name(temp_name))
+}
+
+/// The file a temp file was meant to replace, e.g. `active.json` for
+/// `active.json.<uuid>.tmp`. Also recognises the fixed `active.json.tmp`
+/// names used before temp names were made unique. Any other `.tmp` file is
+/// not ours and is `None`.
+pub fn temp_target(temp_name: &str) -> Option<&str> {
+    let stem = temp_name.strip_suffix(TEMP_EXTENSION)?;
+    match stem.rsplit_once('.') {
+        Some((target, id)) if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Some(target)
+        }
+        _ if stem.ends_with(".json") => Some(stem),
+        _ => None,
+    }
+}
+
+/// Fsync a directory so renames within it are persisted
+#[cfg(target_os = "linux")]
+fn sync_dir(dir: &Path) -> Result<(), String> {
+    fs::File::open(dir)
+        .and_then(|d| d.sync_all())
+        .map_err(|e| format!("Failed to sync directory: {}", e))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sync_dir(_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn write_and_sync(temp_path: &Path, content: &[u8]) -> Result<(), String> {
+    let mut temp_file =
+        fs::File::create(temp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    temp_file
+        .write_all(content)
+        .map_err(|e| format!("Failed to write to temp file: {}", e))?;
+
+    // Ensure data is flushed to disk
+    temp_file
+        .sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {}", e))
+}
+
+/// Replace `path` with `content` via temp-file-then-rename
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temp_path = temp_path_for(path)?;
+
+    // Atomic rename (replaces existing file)
+    let result = write_and_sync(&temp_path, content).and_then(|()| {
+        fs::rename(&temp_path, path).map_err(|e| format!("Failed to rename temp file: {}", e))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+        _ => Ok(()),
+    }
+}
+
+/// Revision identifier of a file's content: its SHA-256 in hex. Content based
+/// so that writers which know nothing about revisions (e.g. the CLI) still
+/// change it.
+pub fn revision(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Revision of the file at `path`, or `None` if it doesn't exist
+pub fn current_revision(path: &Path) -> Result<Option<String>, String> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(revision(&content))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read file: {}", e)),
+    }
+}
+
+/// Temp files in `dir` meant to replace `target`, newest first
+pub fn temp_files_for(dir: &Path, target: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut temps: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| temp_target(&e.file_name().to_string_lossy()) == Some(target))
+        .map(|e| {
+            let modified = e
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, e.path())
+        })
+        .collect();
+
+    temps.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    temps.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Delete temp files in `dir` older than `max_age` whose target `removable`
+/// accepts. Returns the names removed.
+pub fn remove_stale_temp_files(
+    dir: &Path,
+    max_age: Duration,
+    removable: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !temp_target(&name).is_some_and(&removable) {
+            continue;
+        }
+
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if !metadata.is_file() || age < max_age {
+            continue;
+        }
+
+        match fs::remove_file(entry.path()) {
+            Ok(()) => removed.push(name),
+            Err(e) => eprintln!("Failed to remove stale temp file {}: {}", name, e),
+        }
+    }
+
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backdate a file's modification time
+    fn age(path: &Path, by: Duration) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - by).unwrap();
+    }
+
+    #[test]
+    fn writes_replace_the_target_without_leaving_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("active.json");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn revisions_depend_only_on_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("active.json");
+        assert_eq!(current_revision(&path).unwrap(), None);
+
+        write_atomic(&path, b"{\"tasks\":[]}").unwrap();
+        let first = current_revision(&path).unwrap().unwrap();
+        assert_eq!(first, revision(b"{\"tasks\":[]}"));
+        assert_eq!(first.len(), 64);
+
+        // Rewriting the same bytes keeps the revision; any change moves it
+        write_atomic(&path, b"{\"tasks\":[]}").unwrap();
+        assert_eq!(current_revision(&path).unwrap().unwrap(), first);
+        write_atomic(&path, b"{\"tasks\":[] }").unwrap();
+        assert_ne!(current_revision(&path).unwrap().unwrap(), first);
+    }
+
+    #[test]
+    fn temp_names_map_back_to_their_target() {
+        assert_eq!(
+            temp_target("active.json.0123456789abcdef0123456789abcdef.tmp"),
+            Some("active.json")
+        );
+        assert_eq!(temp_target("active.json.tmp"), Some("active.json"));
+        assert_eq!(temp_target("active.json"), None);
+        assert_eq!(temp_target("notes.tmp"), None);
+        assert_eq!(temp_target("notes.0123456789.tmp"), None);
+    }
+
+    #[test]
+    fn unrelated_tmp_files_survive_the_sweep() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.tmp");
+        fs::write(&path, b"x").unwrap();
+        age(&path, STALE_TEMP_AGE * 2);
+
+        assert!(remove_stale_temp_files(dir.path(), STALE_TEMP_AGE, |_| true).is_empty());
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn only_old_temp_files_of_removable_targets_are_swept() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = "active.json.0123456789abcdef0123456789abcdef.tmp";
+        let fresh = "active.json.fedcba9876543210fedcba9876543210.tmp";
+        let kept = "archive.json.tmp";
+        let unrelated = "notes.txt";
+        for name in [old, fresh, kept, unrelated] {
+            fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        for name in [old, kept, unrelated] {
+            age(&dir.path().join(name), STALE_TEMP_AGE * 2);
+        }
+
+        assert_eq!(
+            temp_files_for(dir.path(), "active.json"),
+            [dir.path().join(fresh), dir.path().join(old)]
+        );
+
+        let removed = remove_stale_temp_files(dir.path(), STALE_TEMP_AGE, |target| {
+            target != "archive.json"
+        });
+        assert_eq!(removed, [old]);
+        for name in [fresh, kept, unrelated] {
+            assert!(dir.path().join(name).exists(), "{}", name);
+        }
+    }
+}
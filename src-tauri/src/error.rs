@@ -0,0 +1,95 @@
+use serde::Serialize;
+use std::fmt;
+
+use crate::lock::LockError;
+use crate::model::ValidationIssue;
+use crate::quota::QuotaViolation;
+
+/// Structured error returned by commands that persist data files.
+/// Serialized with a `kind` tag so the frontend can branch on it.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum WriteError {
+    /// Filesystem or path resolution failure
+    #[serde(rename_all = "camelCase")]
+    Io { message: String },
+    /// Payload is not valid JSON or does not match the data model
+    #[serde(rename_all = "camelCase")]
+    Parse {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// Payload deserialized but violates data invariants
+    #[serde(rename_all = "camelCase")]
+    Invalid { issues: Vec<ValidationIssue> },
+    /// File on disk is no longer at the revision the caller read
+    #[serde(rename_all = "camelCase")]
+    Conflict {
+        expected: String,
+        /// `None` if the file has since been deleted
+        actual: Option<String>,
+    },
+    /// Another process held the data directory lock for the whole timeout
+    #[serde(rename_all = "camelCase")]
+    LockTimeout {
+        message: String,
+        holder: Option<String>,
+    },
+    /// Payload adds to a priority quota overflow and `quotaMode` is reject
+    #[serde(rename_all = "camelCase")]
+    QuotaExceeded { violations: Vec<QuotaViolation> },
+}
+
+impl From<String> for WriteError {
+    fn from(message: String) -> Self {
+        WriteError::Io { message }
+    }
+}
+
+impl From<LockError> for WriteError {
+    fn from(e: LockError) -> Self {
+        match e {
+            LockError::Timeout { ref holder, .. } => WriteError::LockTimeout {
+                message: e.to_string(),
+                holder: holder.clone(),
+            },
+            LockError::Io(message) => WriteError::Io { message },
+        }
+    }
+}
+
+impl From<serde_json::Error> for WriteError {
+    fn from(e: serde_json::Error) -> Self {
+        WriteError::Parse {
+            message: e.to_string(),
+            line: e.line(),
+            column: e.column(),
+        }
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Io { message } => write!(f, "{}", message),
+            WriteError::Parse { message, .. } => write!(f, "Invalid payload: {}", message),
+            WriteError::Invalid { issues } => {
+                write!(f, "Payload failed validation ({} issue(s))", issues.len())
+            }
+            WriteError::Conflict { .. } => write!(f, "File changed on disk since it was read"),
+            WriteError::LockTimeout { message, .. } => write!(f, "{}", message),
+            WriteError::QuotaExceeded { violations } => {
+                let priorities: Vec<String> =
+                    violations.iter().map(|v| format!("{:?}", v.priority)).collect();
+                write!(f, "Priority quota exceeded for {}", priorities.join(", "))
+            }
+        }
+    }
+}
+
+impl From<WriteError> for String {
+    fn from(e: WriteError) -> Self {
+        e.to_string()
+    }
+}
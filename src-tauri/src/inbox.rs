@@ -0,0 +1,330 @@
+//! `inbox/` drop folder in the data directory.
+//!
+//! Scripts, shortcuts and other tools can capture tasks by dropping a `.txt`,
+//! `.md` or `.json` file there. Every line becomes a new task using the
+//! quick-add syntax (F priority unless the line says otherwise), appended to
+//! `active.json` under the data directory lock. The file is then moved to
+//! `inbox/processed/`. Files that can't be parsed go to `inbox/failed/` so
+//! they aren't picked up again.
+
+use chrono::Local;
+use notify::{Event, EventKind};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{self, DataFileType};
+use crate::lock;
+use crate::parser;
+use crate::quota::{self, Appended, QuotaReport};
+use crate::units;
+
+pub const INBOX_DIR: &str = "inbox";
+const PROCESSED_DIR: &str = "processed";
+const FAILED_DIR: &str = "failed";
+
+/// File extensions picked up from the inbox
+const EXTENSIONS: [&str; 3] = ["txt", "md", "json"];
+
+/// Payload of the `inbox-imported` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxImport {
+    pub file_name: String,
+    /// IDs of the tasks created, in file order
+    pub task_ids: Vec<String>,
+    /// Tasks left out because their priority is full (`quotaMode` reject)
+    pub rejected: usize,
+    /// Where the file was moved, relative to the inbox
+    pub moved_to: String,
+    /// Set when the new tasks went over a priority quota; emitted separately
+    /// as `quota-enforced`
+    #[serde(skip)]
+    pub quota: Option<QuotaReport>,
+}
+
+/// Payload of the `inbox-import-failed` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxFailure {
+    pub file_name: String,
+    pub error: String,
+}
+
+pub fn inbox_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(INBOX_DIR)
+}
+
+/// Whether `name` is a file the inbox imports (hidden files are skipped)
+fn is_inbox_file(name: &str) -> bool {
+    if name.starts_with('.') {
+        return false;
+    }
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Whether `path` is directly inside an inbox directory
+pub fn is_in_inbox(path: &Path) -> bool {
+    path.parent()
+        .and_then(|parent| parent.file_name())
+        .is_some_and(|name| name == INBOX_DIR)
+}
+
+/// Names of inbox files written by `event`
+pub fn changed_files(event: &Event) -> Vec<String> {
+    if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+        return Vec::new();
+    }
+
+    event
+        .paths
+        .iter()
+        .filter(|path| is_in_inbox(path))
+        .filter_map(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| is_inbox_file(name))
+        .collect()
+}
+
+/// Inbox files waiting to be imported, e.g. dropped while the app was closed
+pub fn pending_files(inbox: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(inbox) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| is_inbox_file(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Strip a Markdown list or checkbox marker. Returns `None` for lines that
+/// aren't tasks (headings, rules, checked items).
+fn markdown_line(line: &str) -> Option<&str> {
+    if line.starts_with('#') && (line.len() == 1 || line[1..].starts_with(['#', ' '])) {
+        return None;
+    }
+    if line.chars().all(|c| matches!(c, '-' | '*' | '_')) && line.len() >= 3 {
+        return None;
+    }
+
+    let mut rest = line;
+    if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|m| rest.strip_prefix(m)) {
+        rest = item.trim_start();
+    } else if let Some(dot) = rest.find(['.', ')']) {
+        if dot > 0 && rest[..dot].chars().all(|c| c.is_ascii_digit()) {
+            if let Some(item) = rest[dot + 1..].strip_prefix(' ') {
+                rest = item.trim_start();
+            }
+        }
+    }
+
+    if rest.starts_with("[x] ") || rest.starts_with("[X] ") {
+        return None;
+    }
+    Some(rest.strip_prefix("[ ] ").unwrap_or(rest))
+}
+
+/// The task lines of an inbox file
+fn task_lines(file_name: &str, content: &str) -> Result<Vec<String>, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let entries: Vec<String> = if extension == "json" {
+        // Either an array of strings or an object with a "tasks" array
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| format!("Failed to parse {}: {}", file_name, e))?;
+        let items = match &value {
+            Value::Array(items) => items,
+            Value::Object(obj) => obj
+                .get("tasks")
+                .and_then(Value::as_array)
+                .ok_or_else(|| format!("{}: expected a \"tasks\" array", file_name))?,
+            _ => return Err(format!("{}: expected an array of strings", file_name)),
+        };
+        items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(String::from)
+                    .ok_or_else(|| format!("{}: expected an array of strings", file_name))
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        vec![content.to_string()]
+    };
+
+    let is_markdown = extension == "md";
+    Ok(entries
+        .iter()
+        .flat_map(|entry| entry.lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            if is_markdown {
+                markdown_line(line)
+            } else {
+                Some(line)
+            }
+        })
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Move an inbox file into `subdir`, keeping the name unless it is taken.
+/// Returns the new path relative to the inbox.
+fn move_to(inbox: &Path, file_name: &str, subdir: &str) -> Result<String, String> {
+    let dir = inbox.join(subdir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+    let path = Path::new(file_name);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+
+    let mut target = file_name.to_string();
+    let mut n = 0;
+    while dir.join(&target).exists() {
+        target = if n == 0 {
+            format!("{}-{}.{}", stem, timestamp, extension)
+        } else {
+            format!("{}-{}-{}.{}", stem, timestamp, n, extension)
+        };
+        n += 1;
+    }
+
+    fs::rename(inbox.join(file_name), dir.join(&target))
+        .map_err(|e| format!("Failed to move {} to {}: {}", file_name, subdir, e))?;
+    Ok(format!("{}/{}", subdir, target))
+}
+
+/// Append tasks to `active.json` under the data directory lock. In quota
+/// reject mode only the tasks that fit are added. A missing file is started
+/// from an empty document.
+fn append_tasks(data_dir: &Path, tasks: Vec<Value>) -> Result<Appended, String> {
+    let _lock = lock::acquire(data_dir)?;
+
+    let path = data_dir.join(DataFileType::Active.filename());
+    let mut active: Value = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse active data: {}", e))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            serde_json::json!({ "version": "2.0", "tasks": [] })
+        }
+        Err(e) => return Err(format!("Failed to read active data: {}", e)),
+    };
+
+    let obj = active
+        .as_object_mut()
+        .ok_or("Active data is not a JSON object")?;
+    obj.insert(
+        "lastModified".to_string(),
+        serde_json::json!(Local::now().to_rfc3339()),
+    );
+    if !obj.get("tasks").is_some_and(Value::is_array) {
+        obj.insert("tasks".to_string(), Value::Array(Vec::new()));
+    }
+
+    let appended = quota::append_within_quota(active, tasks)?;
+    commands::write_data_file(data_dir, DataFileType::Active, &appended.content)?;
+    Ok(appended)
+}
+
+/// Import one inbox file. Returns `None` when the file is already gone (moved
+/// by an earlier import). On a read or write error the file stays in the
+/// inbox; a file that can't be parsed is moved to `inbox/failed/`.
+pub fn import_file(data_dir: &Path, file_name: &str) -> Result<Option<InboxImport>, String> {
+    let inbox = inbox_dir(data_dir);
+
+    let bytes = match fs::read(inbox.join(file_name)) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", file_name, e)),
+    };
+
+    let lines = String::from_utf8(bytes)
+        .map_err(|_| format!("{} is not valid UTF-8", file_name))
+        .and_then(|content| task_lines(file_name, &content));
+    let lines = match lines {
+        Ok(lines) => lines,
+        Err(e) => {
+            let moved_to = move_to(&inbox, file_name, FAILED_DIR)?;
+            return Err(format!("{} (moved to {})", e, moved_to));
+        }
+    };
+
+    let now = Local::now();
+    let layout = units::load_layout(data_dir);
+    let tasks: Vec<_> = lines
+        .iter()
+        .map(|line| parser::parse_task_input(line).into_task(now, &layout))
+        .filter(|task| !task.content.is_empty())
+        .collect();
+
+    let mut appended = None;
+    if !tasks.is_empty() {
+        let tasks = tasks
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
+        appended = Some(append_tasks(data_dir, tasks)?);
+    }
+
+    let moved_to = move_to(&inbox, file_name, PROCESSED_DIR)?;
+    let (task_ids, rejected, quota) = match appended {
+        Some(appended) => (appended.task_ids, appended.rejected, appended.report),
+        None => (Vec::new(), 0, None),
+    };
+    Ok(Some(InboxImport {
+        file_name: file_name.to_string(),
+        task_ids,
+        rejected,
+        moved_to,
+        quota,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_into_a_missing_active_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let inbox = inbox_dir(dir.path());
+        fs::create_dir_all(&inbox).unwrap();
+        fs::write(inbox.join("capture.txt"), "Buy milk\nCall Bob\n").unwrap();
+
+        let import = import_file(dir.path(), "capture.txt").unwrap().unwrap();
+        assert_eq!(import.task_ids.len(), 2);
+        assert_eq!(import.moved_to, "processed/capture.txt");
+
+        let content = fs::read_to_string(dir.path().join("active.json")).unwrap();
+        DataFileType::Active.validate_content(&content).unwrap();
+        let active: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(active["version"], "2.0");
+        assert!(active["lastModified"].is_string());
+        let ids: Vec<_> = active["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids, import.task_ids);
+    }
+}
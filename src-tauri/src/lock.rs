@@ -0,0 +1,308 @@
+//! Cross-process advisory lock on the data directory.
+//!
+//! Lock protocol, for every tool that modifies files in the data directory
+//! (the desktop app, `cli/focusflow.ts`, scripts):
+//!
+//! 1. Create `focusflow.lock` in the data directory exclusively
+//!    (`O_CREAT | O_EXCL`; in Node, `openSync(path, 'wx')`) and write
+//!    `{"pid": <pid>, "owner": "<tool>", "token": "<random>",
+//!    "acquiredAt": "<RFC 3339>"}` to it.
+//! 2. If it already exists, retry every 50 ms. A lockfile last modified more
+//!    than 30 s ago whose `pid` is no longer running was left by a crashed
+//!    holder: delete it (if it is still the file you judged) and retry. One
+//!    older than 10 min is abandoned whatever its pid, which may have been
+//!    reused since. Give up after 5 s and report that the data directory is
+//!    busy.
+//! 3. Do the whole read-modify-write, replacing files by temp-file-then-rename.
+//! 4. Delete the lockfile if it still holds your token. If it doesn't, another
+//!    tool took it over as stale and it is theirs now.
+//!
+//! Hold the lock only around the read-modify-write itself. Plain readers don't
+//! need it, since files are only ever replaced by rename.
+
+use chrono::Local;
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+pub const LOCK_FILENAME: &str = "focusflow.lock";
+
+/// How long to wait for another holder before giving up
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A lockfile older than this belongs to a holder that died, if its pid is
+/// no longer running
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// A lockfile older than this is abandoned even if its pid is running: no
+/// read-modify-write takes this long, so the pid has been reused
+const ABANDONED_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+const OWNER: &str = "focusflow-desktop";
+
+#[derive(Debug)]
+pub enum LockError {
+    /// Another holder kept the lock for the whole timeout
+    Timeout {
+        waited: Duration,
+        /// Contents of the lockfile, identifying the holder
+        holder: Option<String>,
+    },
+    Io(String),
+}
+
+impl std::fmt::Display for LockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockError::Timeout { waited, holder } => {
+                write!(
+                    f,
+                    "Data directory is locked by another process (waited {} ms)",
+                    waited.as_millis()
+                )?;
+                if let Some(holder) = holder {
+                    write!(f, ": {}", holder)?;
+                }
+                Ok(())
+            }
+            LockError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<LockError> for String {
+    fn from(e: LockError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Held lock on the data directory; released on drop
+#[derive(Debug)]
+pub struct DataDirLock {
+    path: PathBuf,
+    /// Written into the lockfile, to tell it apart from a later holder's
+    token: String,
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        // If this lock was taken over as stale, the file belongs to its new holder
+        match fs::read_to_string(&self.path) {
+            Ok(content) if Holder::parse(&content).token.as_deref() == Some(self.token.as_str()) => {
+                if let Err(e) = fs::remove_file(&self.path) {
+                    eprintln!("Failed to release data directory lock: {}", e);
+                }
+            }
+            Ok(_) => eprintln!("Data directory lock was taken over by another holder"),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                eprintln!("Data directory lock was removed while held")
+            }
+            Err(e) => eprintln!("Failed to release data directory lock: {}", e),
+        }
+    }
+}
+
+/// The parts of a lockfile that identify its holder
+#[derive(Debug, Default, Deserialize)]
+struct Holder {
+    pid: Option<u32>,
+    token: Option<String>,
+}
+
+impl Holder {
+    fn parse(content: &str) -> Self {
+        serde_json::from_str(content).unwrap_or_default()
+    }
+}
+
+/// Whether process `pid` is running; `None` where that can't be told
+#[cfg(unix)]
+fn is_running(pid: u32) -> Option<bool> {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return Some(false);
+    };
+    // Signal 0 only checks that the process exists and may be signalled
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    Some(std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Whether a lockfile with `content`, last modified `age` ago, was left by a
+/// holder that is gone
+fn is_abandoned(content: &str, age: Duration) -> bool {
+    if age > ABANDONED_LOCK_AGE {
+        return true;
+    }
+    if age <= STALE_LOCK_AGE {
+        return false;
+    }
+    // Without a pid to check, or a way to check it, age alone decides
+    match Holder::parse(content).pid.and_then(is_running) {
+        Some(running) => !running,
+        None => true,
+    }
+}
+
+/// The lockfile's content if it was left by a holder that is gone
+fn abandoned(path: &Path) -> Option<String> {
+    let age = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+    let content = fs::read_to_string(path).ok()?;
+    is_abandoned(&content, age).then_some(content)
+}
+
+fn try_create(path: &Path, token: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let info = serde_json::json!({
+        "pid": std::process::id(),
+        "owner": OWNER,
+        "token": token,
+        "acquiredAt": Local::now().to_rfc3339(),
+    });
+    file.write_all(info.to_string().as_bytes())
+        .inspect_err(|_| {
+            // Don't leave a lockfile nobody holds
+            let _ = fs::remove_file(path);
+        })
+}
+
+/// Take the data directory lock, waiting up to `LOCK_TIMEOUT`
+pub fn acquire(data_dir: &Path) -> Result<DataDirLock, LockError> {
+    acquire_with_timeout(data_dir, LOCK_TIMEOUT)
+}
+
+pub fn acquire_with_timeout(data_dir: &Path, timeout: Duration) -> Result<DataDirLock, LockError> {
+    let path = data_dir.join(LOCK_FILENAME);
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let started = Instant::now();
+
+    loop {
+        match try_create(&path, &token) {
+            Ok(()) => return Ok(DataDirLock { path, token }),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(LockError::Io(format!("Failed to create lockfile: {}", e))),
+        }
+
+        if let Some(content) = abandoned(&path) {
+            // Unless another waiter already replaced it with a lock of its own
+            if fs::read_to_string(&path).is_ok_and(|current| current == content) {
+                eprintln!("Removing stale data directory lock: {}", content);
+                let _ = fs::remove_file(&path);
+            }
+            continue;
+        }
+
+        if started.elapsed() >= timeout {
+            return Err(LockError::Timeout {
+                waited: started.elapsed(),
+                holder: fs::read_to_string(&path).ok(),
+            });
+        }
+
+        thread::sleep(RETRY_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// No process ever has this pid
+    const DEAD_PID: u32 = 999_999_999;
+
+    fn lockfile(data_dir: &Path, pid: u32, age: Duration) {
+        let path = data_dir.join(LOCK_FILENAME);
+        fs::write(&path, serde_json::json!({ "pid": pid, "owner": "test" }).to_string()).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn waiters_get_the_lock_once_it_is_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().to_path_buf();
+        let held = acquire(&data_dir).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let waiter = thread::spawn(move || {
+            let lock = acquire(&data_dir);
+            tx.send(()).unwrap();
+            lock.map(|_| ())
+        });
+        thread::sleep(Duration::from_millis(150));
+        assert!(rx.try_recv().is_err(), "waiter got a held lock");
+
+        drop(held);
+        waiter.join().unwrap().unwrap();
+        assert!(!dir.path().join(LOCK_FILENAME).exists());
+    }
+
+    #[test]
+    fn times_out_naming_the_holder() {
+        let dir = tempfile::tempdir().unwrap();
+        let _held = acquire(dir.path()).unwrap();
+
+        let Err(LockError::Timeout { waited, holder }) =
+            acquire_with_timeout(dir.path(), Duration::from_millis(100))
+        else {
+            panic!("expected a timeout");
+        };
+        assert!(waited >= Duration::from_millis(100));
+        assert!(holder.unwrap().contains(&std::process::id().to_string()));
+    }
+
+    #[test]
+    fn a_dead_holders_stale_lock_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        lockfile(dir.path(), DEAD_PID, STALE_LOCK_AGE * 2);
+        let lock = acquire_with_timeout(dir.path(), Duration::from_millis(100)).unwrap();
+        let content = fs::read_to_string(dir.path().join(LOCK_FILENAME)).unwrap();
+        assert_eq!(Holder::parse(&content).token.as_deref(), Some(lock.token.as_str()));
+
+        // A recently taken lock is left alone whoever holds it
+        drop(lock);
+        lockfile(dir.path(), DEAD_PID, Duration::ZERO);
+        assert!(acquire_with_timeout(dir.path(), Duration::from_millis(100)).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_running_holders_lock_is_not_stolen_until_abandoned() {
+        let dir = tempfile::tempdir().unwrap();
+        lockfile(dir.path(), std::process::id(), STALE_LOCK_AGE * 2);
+        assert!(acquire_with_timeout(dir.path(), Duration::from_millis(100)).is_err());
+
+        lockfile(dir.path(), std::process::id(), ABANDONED_LOCK_AGE * 2);
+        assert!(acquire_with_timeout(dir.path(), Duration::from_millis(100)).is_ok());
+    }
+
+    #[test]
+    fn dropping_a_taken_over_lock_leaves_the_new_holders_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILENAME);
+        let old = acquire(dir.path()).unwrap();
+
+        // Another process judged it stale and took the lock
+        fs::remove_file(&path).unwrap();
+        let new = acquire(dir.path()).unwrap();
+        drop(old);
+        assert!(path.exists());
+
+        drop(new);
+        assert!(!path.exists());
+    }
+}
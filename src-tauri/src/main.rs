@@ -1,42 +1,203 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod backup;
 mod commands;
+mod cycle;
+mod debounce;
+mod diff;
+mod durable;
+mod error;
+mod inbox;
+mod lock;
+mod merge;
+mod model;
+mod parser;
+mod pomodoro;
+mod quota;
+mod recovery;
+mod recurrence;
+mod reminders;
+mod scheduler;
+mod units;
 mod watcher;
 
-use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// Lifetime of a suspension lease when the caller doesn't give one
+pub const DEFAULT_LEASE: Duration = Duration::from_secs(30);
+
+/// Longest suspension lease a caller can take
+pub const MAX_LEASE: Duration = Duration::from_secs(5 * 60);
 
 /// Thread-safe state for controlling the file watcher
 pub struct WatcherState {
-    pub paused: AtomicBool,
+    /// Suspension leases by ID, with their expiry. The watcher is suspended
+    /// while any unexpired lease exists.
+    leases: Mutex<HashMap<String, Instant>>,
+    /// Data files that changed while suspended, replayed on resume
+    deferred: Mutex<BTreeSet<String>>,
+    /// Revision of the last content the app itself wrote, per file name
+    self_writes: Mutex<HashMap<String, String>>,
+    /// Health of the watcher thread, kept up to date by its supervisor
+    status: Mutex<watcher::WatcherStatus>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
 }
 
 impl WatcherState {
     pub fn new() -> Self {
         Self {
-            paused: AtomicBool::new(false),
+            leases: Mutex::new(HashMap::new()),
+            deferred: Mutex::new(BTreeSet::new()),
+            self_writes: Mutex::new(HashMap::new()),
+            status: Mutex::new(watcher::WatcherStatus::default()),
+        }
+    }
+
+    /// Take a suspension lease that expires after `timeout` (capped at
+    /// `MAX_LEASE`). Leases nest: each caller holds and releases its own.
+    pub fn suspend(&self, timeout: Duration) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let expiry = Instant::now() + timeout.min(MAX_LEASE);
+        lock(&self.leases).insert(id.clone(), expiry);
+        id
+    }
+
+    /// Release a lease. Returns false if it had already expired or never existed.
+    pub fn resume(&self, lease_id: &str) -> bool {
+        lock(&self.leases).remove(lease_id).is_some()
+    }
+
+    /// Whether any unexpired lease is held. Expired leases are dropped.
+    pub fn is_suspended(&self) -> bool {
+        let now = Instant::now();
+        let mut leases = lock(&self.leases);
+        leases.retain(|_, expiry| *expiry > now);
+        !leases.is_empty()
+    }
+
+    /// Hold back a change seen while suspended
+    pub fn defer(&self, file_name: &str) {
+        lock(&self.deferred).insert(file_name.to_string());
+    }
+
+    pub fn has_deferred(&self) -> bool {
+        !lock(&self.deferred).is_empty()
+    }
+
+    /// Take the held-back changes once no lease remains
+    pub fn take_deferred(&self) -> Vec<String> {
+        if self.is_suspended() {
+            return Vec::new();
         }
+        std::mem::take(&mut *lock(&self.deferred))
+            .into_iter()
+            .collect()
+    }
+
+    /// Remember content the app is about to write, so the watcher can tell
+    /// the resulting event apart from an external change
+    pub fn record_self_write(&self, file_name: &str, revision: String) {
+        lock(&self.self_writes).insert(file_name.to_string(), revision);
+    }
+
+    /// Whether `revision` is what the app last wrote to `file_name`
+    pub fn is_self_write(&self, file_name: &str, revision: &str) -> bool {
+        lock(&self.self_writes)
+            .get(file_name)
+            .is_some_and(|r| r == revision)
+    }
+
+    pub fn status(&self) -> watcher::WatcherStatus {
+        lock(&self.status).clone()
+    }
+
+    /// Apply `update` to the watcher status. Returns whether it was degraded
+    /// before, and the new status.
+    pub fn update_status(
+        &self,
+        update: impl FnOnce(&mut watcher::WatcherStatus),
+    ) -> (bool, watcher::WatcherStatus) {
+        let mut status = lock(&self.status);
+        let was_degraded = status.is_degraded();
+        update(&mut status);
+        (was_degraded, status.clone())
     }
 }
 
 fn main() {
     // Create shared watcher state
     let watcher_state = Arc::new(WatcherState::new());
+    let snapshots = Arc::new(merge::Snapshots::default());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_notification::init())
         .manage(watcher_state.clone())
+        .manage(snapshots.clone())
         .setup(move |app| {
-            // Start file watcher for auto-reload
+            // Start file watcher for auto-reload; it restarts itself on failure
             let app_handle = app.handle().clone();
             let watcher_state_clone = watcher_state.clone();
+            let snapshots_clone = snapshots.clone();
             std::thread::spawn(move || {
-                if let Err(e) = watcher::start_watcher(app_handle, watcher_state_clone) {
-                    eprintln!("Failed to start file watcher: {}", e);
+                watcher::supervise(app_handle, watcher_state_clone, snapshots_clone);
+            });
+
+            // Clear out temp files from writes interrupted by a crash
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                let removed = recovery::sweep_stale_temp_files(&data_dir);
+                if !removed.is_empty() {
+                    println!("Removed stale temp files: {:?}", removed);
                 }
+
+                // Give recurring tasks an RRULE migrated from their short pattern
+                match recurrence::migrate_active(&data_dir) {
+                    Ok(0) => {}
+                    Ok(n) => println!("Migrated {} recurring task(s) to RRULEs", n),
+                    Err(e) => eprintln!("Failed to migrate recurrence rules: {}", e),
+                }
+
+                // Re-label unitStart if the unit layout was changed outside the app
+                match units::migrate_active(&data_dir) {
+                    Ok(0) => {}
+                    Ok(n) => println!("Re-labelled {} task(s) for the unit layout", n),
+                    Err(e) => eprintln!("Failed to re-label unit starts: {}", e),
+                }
+            }
+
+            // Resume the pomodoro timer where the last run left it
+            let timer = Arc::new(match app.path().app_data_dir() {
+                Ok(data_dir) => pomodoro::Pomodoro::load(&data_dir),
+                Err(_) => pomodoro::Pomodoro::default(),
             });
+            app.manage(timer.clone());
+            tauri::async_runtime::spawn(pomodoro::run(app.handle().clone(), timer));
+
+            // Advance the cycle and create recurring task instances now and after
+            // each local midnight
+            tauri::async_runtime::spawn(scheduler::run(app.handle().clone()));
+
+            // Send due-date reminders, the morning digest and the overdue nudge
+            tauri::async_runtime::spawn(reminders::run(app.handle().clone()));
+
+            // Periodically verify backups so disk problems surface before a restore is needed
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                let app_handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    backup::verification_loop(data_dir, |failed| {
+                        if let Err(e) = app_handle.emit("backup-verification-failed", failed) {
+                            eprintln!("Failed to emit event: {}", e);
+                        }
+                    });
+                });
+            }
 
             Ok(())
         })
@@ -45,13 +206,37 @@ fn main() {
             commands::get_data_file_paths,
             commands::atomic_write_file,
             commands::read_data_file,
+            commands::merge_data_file,
             commands::backup_data,
+            commands::list_backups,
+            commands::prune_backups,
+            commands::verify_backup,
+            commands::verify_all_backups,
+            commands::restore_backup,
             commands::migrate_legacy_data,
             commands::get_system_info,
             commands::trigger_reload,
             commands::append_archive_tasks,
             commands::suspend_watcher,
-            commands::resume_watcher
+            commands::resume_watcher,
+            commands::watcher_status,
+            commands::parse_task_input,
+            commands::quota_status,
+            commands::next_occurrences,
+            commands::get_unit,
+            commands::get_week_units,
+            commands::set_unit_layout,
+            commands::period_completion,
+            commands::evaluate_cycle,
+            commands::pomodoro_status,
+            commands::start_pomodoro,
+            commands::pause_pomodoro,
+            commands::resume_pomodoro,
+            commands::toggle_pomodoro,
+            commands::stop_pomodoro,
+            commands::skip_pomodoro,
+            commands::record_interruption,
+            commands::snooze_reminder
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
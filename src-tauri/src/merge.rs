@@ -0,0 +1,476 @@
+//! Three-way merge of a data file changed outside the app.
+//!
+//! `base` is the last version this app instance saw on disk, `theirs` is what
+//! is on disk now and `ours` is the frontend's in-memory state. Items in the
+//! file's list (tasks, sessions) are matched by ID and merged field by field;
+//! other top-level keys are merged as whole values. A change on one side only
+//! is taken as is. When both sides changed the same field differently, ours is
+//! kept and a conflict is reported so the UI can offer the other value.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::commands::DataFileType;
+use crate::recovery;
+
+/// Top-level keys every writer touches; taken from theirs without conflict
+const VOLATILE_KEYS: [&str; 1] = ["lastModified"];
+
+/// Last version of each data file seen on disk by this app instance
+#[derive(Default)]
+pub struct Snapshots {
+    files: Mutex<HashMap<&'static str, String>>,
+}
+
+impl Snapshots {
+    pub fn get(&self, file_type: DataFileType) -> Option<String> {
+        self.lock().get(file_type.name()).cloned()
+    }
+
+    pub fn set(&self, file_type: DataFileType, content: String) {
+        self.lock().insert(file_type.name(), content);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<&'static str, String>> {
+        self.files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A field (or whole item) changed differently on both sides
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    /// ID of the task or session; `None` for a top-level key
+    pub item_id: Option<String>,
+    /// Field or top-level key; `None` when one side deleted the item
+    pub field: Option<String>,
+    pub base: Option<Value>,
+    /// Value kept in the merged result
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub merged: Value,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+fn item_id(item: &Value) -> Option<&str> {
+    item.get("id").and_then(Value::as_str)
+}
+
+/// Resolve one value three ways. `None` means absent.
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+) -> Result<Option<Value>, ()> {
+    if ours == theirs || theirs == base {
+        Ok(ours.cloned())
+    } else if ours == base {
+        Ok(theirs.cloned())
+    } else {
+        Err(())
+    }
+}
+
+/// Merge the fields of an item present on all three sides
+fn merge_item(
+    id: &str,
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Value {
+    let mut merged = Map::new();
+    let mut keys: Vec<&String> = ours.keys().collect();
+    keys.extend(theirs.keys().filter(|k| !ours.contains_key(*k)));
+    keys.extend(
+        base.keys()
+            .filter(|k| !ours.contains_key(*k) && !theirs.contains_key(*k)),
+    );
+
+    for key in keys {
+        let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+        let value = merge_value(b, o, t).unwrap_or_else(|()| {
+            conflicts.push(MergeConflict {
+                item_id: Some(id.to_string()),
+                field: Some(key.clone()),
+                base: b.cloned(),
+                ours: o.cloned(),
+                theirs: t.cloned(),
+            });
+            o.cloned()
+        });
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+
+    Value::Object(merged)
+}
+
+fn index_by_id(items: &[Value]) -> HashMap<&str, &Value> {
+    items
+        .iter()
+        .filter_map(|item| item_id(item).map(|id| (id, item)))
+        .collect()
+}
+
+/// Merge the ID-addressed lists. Order follows ours, with items new in
+/// theirs appended in their order. Items without an ID are kept from both
+/// sides.
+fn merge_list(
+    base: &[Value],
+    ours: &[Value],
+    theirs: &[Value],
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<Value> {
+    let base_by_id = index_by_id(base);
+    let theirs_by_id = index_by_id(theirs);
+    let ours_ids: HashSet<&str> = ours.iter().filter_map(item_id).collect();
+    let mut merged = Vec::new();
+
+    for item in ours {
+        let Some(id) = item_id(item) else {
+            merged.push(item.clone());
+            continue;
+        };
+        match (base_by_id.get(id), theirs_by_id.get(id)) {
+            // Added on our side, or on both
+            (None, None) => merged.push(item.clone()),
+            (None, Some(theirs)) => {
+                let empty = Map::new();
+                match (item.as_object(), theirs.as_object()) {
+                    (Some(o), Some(t)) => merged.push(merge_item(id, &empty, o, t, conflicts)),
+                    _ => merged.push(item.clone()),
+                }
+            }
+            // Deleted on their side
+            (Some(base), None) => {
+                if item != *base {
+                    conflicts.push(MergeConflict {
+                        item_id: Some(id.to_string()),
+                        field: None,
+                        base: Some((*base).clone()),
+                        ours: Some(item.clone()),
+                        theirs: None,
+                    });
+                    merged.push(item.clone());
+                }
+            }
+            (Some(base), Some(theirs)) => {
+                match (base.as_object(), item.as_object(), theirs.as_object()) {
+                    (Some(b), Some(o), Some(t)) => merged.push(merge_item(id, b, o, t, conflicts)),
+                    _ => merged.push(item.clone()),
+                }
+            }
+        }
+    }
+
+    for item in theirs {
+        let Some(id) = item_id(item) else {
+            // Can't be matched by ID: keep it unless ours already has it
+            if !ours.contains(item) {
+                merged.push(item.clone());
+            }
+            continue;
+        };
+        if ours_ids.contains(id) {
+            continue;
+        }
+        match base_by_id.get(id) {
+            // Added on their side
+            None => merged.push(item.clone()),
+            // Deleted on our side
+            Some(base) => {
+                if item != *base {
+                    conflicts.push(MergeConflict {
+                        item_id: Some(id.to_string()),
+                        field: None,
+                        base: Some((*base).clone()),
+                        ours: None,
+                        theirs: Some(item.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+fn as_list(value: Option<&Value>) -> &[Value] {
+    value
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// Three-way merge of a data file's JSON documents
+pub fn merge_documents(
+    file_type: DataFileType,
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+) -> MergeOutcome {
+    let (Some(base_obj), Some(ours_obj), Some(theirs_obj)) =
+        (base.as_object(), ours.as_object(), theirs.as_object())
+    else {
+        // Not something we can merge structurally: the file on disk wins
+        return MergeOutcome {
+            merged: theirs.clone(),
+            conflicts: Vec::new(),
+        };
+    };
+
+    let list = file_type.list_key();
+    let mut conflicts = Vec::new();
+    let mut merged = Map::new();
+
+    let mut keys: Vec<&String> = ours_obj.keys().collect();
+    keys.extend(theirs_obj.keys().filter(|k| !ours_obj.contains_key(*k)));
+
+    for key in keys {
+        let (b, o, t) = (base_obj.get(key), ours_obj.get(key), theirs_obj.get(key));
+        let value = if key == list {
+            Some(Value::Array(merge_list(
+                as_list(b),
+                as_list(o),
+                as_list(t),
+                &mut conflicts,
+            )))
+        } else if VOLATILE_KEYS.contains(&key.as_str()) {
+            t.or(o).cloned()
+        } else {
+            merge_value(b, o, t).unwrap_or_else(|()| {
+                conflicts.push(MergeConflict {
+                    item_id: None,
+                    field: Some(key.clone()),
+                    base: b.cloned(),
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                });
+                o.cloned()
+            })
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+
+    MergeOutcome {
+        merged: Value::Object(merged),
+        conflicts,
+    }
+}
+
+/// Result of merging the file on disk into the frontend's state
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub content: String,
+    /// Revision of the file on disk, to write the merged content against
+    pub revision: Option<String>,
+    /// The merged content differs from the file on disk and should be saved
+    pub needs_save: bool,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Merge the data file on disk into `local_content`, using the last snapshot
+/// as the base. Without a snapshot the file on disk wins outright.
+pub fn merge_with_disk(
+    data_dir: &Path,
+    file_type: DataFileType,
+    snapshots: &Snapshots,
+    local_content: &str,
+) -> Result<MergeResult, String> {
+    let disk = recovery::read_data_file(data_dir, file_type)?;
+
+    let Some(theirs_content) = disk.content else {
+        // Deleted externally: keep what we have
+        return Ok(MergeResult {
+            content: local_content.to_string(),
+            revision: None,
+            needs_save: true,
+            conflicts: Vec::new(),
+        });
+    };
+
+    let ours: Value = serde_json::from_str(local_content)
+        .map_err(|e| format!("Failed to parse local data: {}", e))?;
+    let theirs: Value = serde_json::from_str(&theirs_content)
+        .map_err(|e| format!("Failed to parse {}: {}", file_type.filename(), e))?;
+    let base = snapshots
+        .get(file_type)
+        .and_then(|base| serde_json::from_str(&base).ok())
+        .unwrap_or_else(|| ours.clone());
+
+    let outcome = merge_documents(file_type, &base, &ours, &theirs);
+    snapshots.set(file_type, theirs_content.clone());
+
+    let needs_save = outcome.merged != theirs;
+    let content = if needs_save {
+        serde_json::to_string_pretty(&outcome.merged)
+            .map_err(|e| format!("Failed to serialize merged data: {}", e))?
+    } else {
+        theirs_content
+    };
+
+    Ok(MergeResult {
+        content,
+        revision: disk.revision,
+        needs_save,
+        conflicts: outcome.conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn list(base: Value, ours: Value, theirs: Value) -> (Vec<Value>, Vec<MergeConflict>) {
+        let mut conflicts = Vec::new();
+        let merged = merge_list(
+            as_list(Some(&base)),
+            as_list(Some(&ours)),
+            as_list(Some(&theirs)),
+            &mut conflicts,
+        );
+        (merged, conflicts)
+    }
+
+    #[test]
+    fn keeps_items_added_on_either_side() {
+        let a = json!({"id": "a", "title": "A"});
+        let b = json!({"id": "b", "title": "B"});
+        let c = json!({"id": "c", "title": "C"});
+        let (merged, conflicts) = list(json!([a]), json!([a, b]), json!([a, c]));
+
+        assert_eq!(merged, vec![a, b, c]);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn merges_an_item_added_on_both_sides() {
+        let (merged, conflicts) = list(
+            json!([]),
+            json!([{"id": "a", "title": "A", "done": false}]),
+            json!([{"id": "a", "title": "A", "note": "n"}]),
+        );
+
+        assert_eq!(
+            merged,
+            vec![json!({"id": "a", "title": "A", "done": false, "note": "n"})]
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn follows_an_unconflicted_delete_on_either_side() {
+        let a = json!({"id": "a", "title": "A"});
+        let b = json!({"id": "b", "title": "B"});
+        let (merged, conflicts) = list(json!([a, b]), json!([a]), json!([b]));
+
+        assert!(merged.is_empty());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn reports_a_delete_against_an_edit() {
+        let base = json!([{"id": "a", "title": "A"}, {"id": "b", "title": "B"}]);
+        let ours = json!([{"id": "a", "title": "A2"}]);
+        let theirs = json!([{"id": "b", "title": "B2"}]);
+        let (merged, conflicts) = list(base, ours, theirs);
+
+        // Our edit survives their delete; their edit loses to our delete
+        assert_eq!(merged, vec![json!({"id": "a", "title": "A2"})]);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].item_id.as_deref(), Some("a"));
+        assert!(conflicts[0].field.is_none() && conflicts[0].theirs.is_none());
+        assert_eq!(conflicts[1].item_id.as_deref(), Some("b"));
+        assert!(conflicts[1].field.is_none() && conflicts[1].ours.is_none());
+    }
+
+    #[test]
+    fn merges_fields_changed_on_different_sides() {
+        let (merged, conflicts) = list(
+            json!([{"id": "a", "title": "A", "priority": "B", "tag": "x"}]),
+            json!([{"id": "a", "title": "A2", "priority": "B", "tag": "x"}]),
+            json!([{"id": "a", "title": "A", "priority": "C"}]),
+        );
+
+        assert_eq!(
+            merged,
+            vec![json!({"id": "a", "title": "A2", "priority": "C"})]
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn keeps_ours_and_reports_a_field_conflict() {
+        let (merged, conflicts) = list(
+            json!([{"id": "a", "title": "A"}]),
+            json!([{"id": "a", "title": "Ours"}]),
+            json!([{"id": "a", "title": "Theirs"}]),
+        );
+
+        assert_eq!(merged, vec![json!({"id": "a", "title": "Ours"})]);
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.item_id.as_deref(), Some("a"));
+        assert_eq!(conflict.field.as_deref(), Some("title"));
+        assert_eq!(conflict.base, Some(json!("A")));
+        assert_eq!(conflict.ours, Some(json!("Ours")));
+        assert_eq!(conflict.theirs, Some(json!("Theirs")));
+    }
+
+    #[test]
+    fn reports_a_field_removed_against_an_edit() {
+        let (merged, conflicts) = list(
+            json!([{"id": "a", "note": "n"}]),
+            json!([{"id": "a"}]),
+            json!([{"id": "a", "note": "n2"}]),
+        );
+
+        assert_eq!(merged, vec![json!({"id": "a"})]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field.as_deref(), Some("note"));
+        assert!(conflicts[0].ours.is_none());
+    }
+
+    #[test]
+    fn keeps_items_without_an_id_from_both_sides() {
+        let shared = json!({"title": "shared"});
+        let (merged, conflicts) = list(
+            json!([shared]),
+            json!([shared, {"title": "ours"}]),
+            json!([shared, {"title": "theirs"}]),
+        );
+
+        assert_eq!(
+            merged,
+            vec![shared, json!({"title": "ours"}), json!({"title": "theirs"})]
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn takes_volatile_keys_from_theirs_and_reports_top_level_conflicts() {
+        let base = json!({"tasks": [], "lastModified": "1", "settings": {"a": 1}});
+        let ours = json!({"tasks": [], "lastModified": "2", "settings": {"a": 2}});
+        let theirs = json!({"tasks": [], "lastModified": "3", "settings": {"a": 3}});
+        let outcome = merge_documents(DataFileType::Active, &base, &ours, &theirs);
+
+        assert_eq!(outcome.merged["lastModified"], json!("3"));
+        assert_eq!(outcome.merged["settings"], json!({"a": 2}));
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert!(outcome.conflicts[0].item_id.is_none());
+        assert_eq!(outcome.conflicts[0].field.as_deref(), Some("settings"));
+    }
+}
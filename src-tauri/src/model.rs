@@ -0,0 +1,744 @@
+//! Typed mirror of the frontend data model (`src/lib/types/index.ts`).
+//!
+//! These structs are used to validate payloads before they are persisted and
+//! to let backend code read and modify the data files without going through
+//! untyped `serde_json::Value`s. Field names follow the camelCase JSON that the
+//! webview writes. Every struct keeps the fields it doesn't know about in an
+//! `extra` map, so that a round-trip through Rust never drops data written by
+//! a newer frontend.
+
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::quota::QuotaMode;
+use crate::units::UnitLayout;
+
+/// Task priority letter (A-E work, F idea pool, G done, H cancelled, N future, S sustained)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    N,
+    S,
+}
+
+/// Fixed recurrence patterns supported by the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurrencePattern {
+    #[serde(rename = "1d")]
+    OneDay,
+    #[serde(rename = "2d")]
+    TwoDays,
+    #[serde(rename = "3d")]
+    ThreeDays,
+    #[serde(rename = "1w")]
+    OneWeek,
+    #[serde(rename = "2w")]
+    TwoWeeks,
+    #[serde(rename = "1m")]
+    OneMonth,
+    #[serde(rename = "3m")]
+    ThreeMonths,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recurrence {
+    pub pattern: Option<RecurrencePattern>,
+    /// Patterns like `mon,wed,fri` or `1m@15`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_pattern: Option<String>,
+    /// RFC 5545 rule (see `recurrence.rs`); set for every recurring task
+    /// once its short pattern has been migrated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rrule: Option<String>,
+    /// Shared by every instance of a recurring task, so the scheduler can
+    /// tell whether an occurrence already exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<String>,
+    #[serde(default)]
+    pub next_due: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subtask {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroCount {
+    #[serde(default)]
+    pub estimated: u32,
+    #[serde(default)]
+    pub completed: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended_until: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_early: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    pub content: String,
+    pub priority: Priority,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub unit_start: String,
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub contexts: Vec<String>,
+    #[serde(default)]
+    pub custom_tags: Vec<String>,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// Local `HH:MM` the task is due on `due_date`; without it, the end of the day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_time: Option<String>,
+    /// When to remind of the task; set by hand or by snoozing a reminder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remind_at: Option<String>,
+    #[serde(default)]
+    pub threshold_date: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub pomodoros: PomodoroCount,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtasks: Option<Vec<Subtask>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_override: Option<UnitOverride>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_priority_change_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evolved_from: Option<String>,
+    /// Fields this version of the backend does not know about
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Per-priority counters for A-F (`ActivePriorityCounts` in the frontend)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivePriorityCounts {
+    #[serde(rename = "A", default)]
+    pub a: u32,
+    #[serde(rename = "B", default)]
+    pub b: u32,
+    #[serde(rename = "C", default)]
+    pub c: u32,
+    #[serde(rename = "D", default)]
+    pub d: u32,
+    #[serde(rename = "E", default)]
+    pub e: u32,
+    #[serde(rename = "F", default)]
+    pub f: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewStats {
+    #[serde(default)]
+    pub planned: ActivePriorityCounts,
+    #[serde(default)]
+    pub completed: ActivePriorityCounts,
+    #[serde(default)]
+    pub pomodoros_total: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitReview {
+    pub id: String,
+    pub unit_start: String,
+    pub unit_end: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub stats: ReviewStats,
+    #[serde(default)]
+    pub reflection: String,
+    #[serde(default)]
+    pub next_unit_focus: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroSession {
+    pub id: String,
+    #[serde(default)]
+    pub task_id: String,
+    pub started_at: String,
+    #[serde(default)]
+    pub duration: u32,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interruptions: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interruption_reasons: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// App settings. Missing keys fall back to `createDefaultSettings()` values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub theme: String,
+    pub language: String,
+    pub pomodoro_work: u32,
+    pub pomodoro_short_break: u32,
+    pub pomodoro_long_break: u32,
+    pub auto_backup: bool,
+    pub sidebar_collapsed: bool,
+    pub auto_archive_days: u32,
+    pub e_zone_aging_days: u32,
+    pub show_future_tasks: bool,
+    pub unit_boundary_flex_hours: f64,
+    pub density: String,
+    pub due_reminders: bool,
+    /// Minutes before a task is due to remind of it, one reminder per entry
+    pub reminder_offsets: Vec<u32>,
+    /// Local `HH:MM` of the morning digest; `None` turns it off
+    pub digest_time: Option<String>,
+    /// Local `HH:MM` of the overdue nudge; `None` turns it off
+    pub overdue_nudge_time: Option<String>,
+    pub low_completion_prompt: bool,
+    pub backup_retention: RetentionPolicy,
+    pub compress_backups: bool,
+    pub quota_mode: QuotaMode,
+    pub unit_layout: UnitLayout,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            language: "zh-CN".to_string(),
+            pomodoro_work: 25,
+            pomodoro_short_break: 5,
+            pomodoro_long_break: 20,
+            auto_backup: true,
+            sidebar_collapsed: false,
+            auto_archive_days: 7,
+            e_zone_aging_days: 3,
+            show_future_tasks: false,
+            unit_boundary_flex_hours: 12.0,
+            density: "comfortable".to_string(),
+            due_reminders: true,
+            reminder_offsets: vec![60, 1440],
+            digest_time: Some("08:30".to_string()),
+            overdue_nudge_time: Some("18:30".to_string()),
+            low_completion_prompt: true,
+            backup_retention: RetentionPolicy::default(),
+            compress_backups: false,
+            quota_mode: QuotaMode::default(),
+            unit_layout: UnitLayout::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// How many backups to keep. A backup survives pruning if any tier selects it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Most recent backups, regardless of age
+    pub keep_last: usize,
+    /// Newest backup of each of the last N days that have one
+    pub daily: usize,
+    /// Newest backup of each of the last N ISO weeks that have one
+    pub weekly: usize,
+    /// Newest backup of each of the last N months that have one
+    pub monthly: usize,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            daily: 7,
+            weekly: 4,
+            monthly: 6,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingReview {
+    pub period_start: String,
+    pub completion: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleState {
+    pub anchor_start: String,
+    pub window_end: String,
+    #[serde(default)]
+    pub merged: bool,
+    #[serde(default)]
+    pub last_evaluated_start: String,
+    /// Set while a low-completion micro-review is awaiting the user
+    #[serde(default)]
+    pub pending_review: Option<PendingReview>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleHistoryEntry {
+    pub period_start: String,
+    pub completion: Option<f64>,
+    #[serde(default)]
+    pub merged: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Hot data file (`active.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveData {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub last_modified: String,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub reviews: Vec<UnitReview>,
+    #[serde(default)]
+    pub custom_tag_groups: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub settings: Settings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gamification: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_state: Option<CycleState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_history: Option<Vec<CycleHistoryEntry>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Cold data file (`archive.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveData {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub last_modified: String,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Pomodoro history file (`pomodoro_history.json`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroHistoryData {
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub last_modified: String,
+    #[serde(default)]
+    pub sessions: Vec<PomodoroSession>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A single invariant violation found while validating a payload
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ValidationIssue {
+    /// Two tasks (or subtasks of one task) share the same ID
+    #[serde(rename_all = "camelCase")]
+    DuplicateId { id: String },
+    /// A task, subtask or session has an empty ID
+    #[serde(rename_all = "camelCase")]
+    EmptyId { path: String },
+    /// A date, time or timestamp field could not be parsed
+    #[serde(rename_all = "camelCase")]
+    MalformedDate {
+        path: String,
+        value: String,
+        expected: &'static str,
+    },
+    /// `settings.unitLayout` doesn't split the week into units
+    #[serde(rename_all = "camelCase")]
+    InvalidUnitLayout { message: String },
+}
+
+/// Format expected for date-only fields
+const DATE_FORMAT: &str = "YYYY-MM-DD";
+/// Format expected for timestamp fields
+const TIMESTAMP_FORMAT: &str = "RFC 3339 timestamp";
+/// Format expected for time-of-day fields
+const TIME_FORMAT: &str = "HH:MM";
+
+/// Collects issues while walking a payload
+#[derive(Default)]
+struct Validator {
+    issues: Vec<ValidationIssue>,
+}
+
+impl Validator {
+    fn date(&mut self, path: String, value: &str) {
+        if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_err() {
+            self.issues.push(ValidationIssue::MalformedDate {
+                path,
+                value: value.to_string(),
+                expected: DATE_FORMAT,
+            });
+        }
+    }
+
+    fn opt_date(&mut self, path: String, value: Option<&String>) {
+        if let Some(value) = value {
+            self.date(path, value);
+        }
+    }
+
+    /// Date-only field that older frontends may have stored as a full timestamp
+    fn date_or_timestamp(&mut self, path: String, value: &str) {
+        let ok = NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+            || DateTime::parse_from_rfc3339(value).is_ok();
+        if !ok {
+            self.issues.push(ValidationIssue::MalformedDate {
+                path,
+                value: value.to_string(),
+                expected: DATE_FORMAT,
+            });
+        }
+    }
+
+    fn timestamp(&mut self, path: String, value: &str) {
+        if DateTime::parse_from_rfc3339(value).is_err() {
+            self.issues.push(ValidationIssue::MalformedDate {
+                path,
+                value: value.to_string(),
+                expected: TIMESTAMP_FORMAT,
+            });
+        }
+    }
+
+    fn opt_timestamp(&mut self, path: String, value: Option<&String>) {
+        if let Some(value) = value {
+            self.timestamp(path, value);
+        }
+    }
+
+    fn opt_time(&mut self, path: String, value: Option<&String>) {
+        if let Some(value) = value {
+            if NaiveTime::parse_from_str(value, "%H:%M").is_err() {
+                self.issues.push(ValidationIssue::MalformedDate {
+                    path,
+                    value: value.to_string(),
+                    expected: TIME_FORMAT,
+                });
+            }
+        }
+    }
+
+    fn id(&mut self, path: String, id: &str, seen: &mut HashSet<String>) {
+        if id.is_empty() {
+            self.issues.push(ValidationIssue::EmptyId { path });
+        } else if !seen.insert(id.to_string()) {
+            self.issues.push(ValidationIssue::DuplicateId { id: id.to_string() });
+        }
+    }
+
+    fn tasks(&mut self, prefix: &str, tasks: &[Task]) {
+        let mut seen = HashSet::new();
+        for (i, task) in tasks.iter().enumerate() {
+            let path = format!("{}[{}]", prefix, i);
+            self.id(format!("{}.id", path), &task.id, &mut seen);
+
+            if !task.created_at.is_empty() {
+                self.timestamp(format!("{}.createdAt", path), &task.created_at);
+            }
+            self.opt_timestamp(format!("{}.completedAt", path), task.completed_at.as_ref());
+            self.opt_timestamp(
+                format!("{}.lastPriorityChangeAt", path),
+                task.last_priority_change_at.as_ref(),
+            );
+            if !task.unit_start.is_empty() {
+                self.date(format!("{}.unitStart", path), &task.unit_start);
+            }
+            self.opt_date(format!("{}.dueDate", path), task.due_date.as_ref());
+            self.opt_time(format!("{}.dueTime", path), task.due_time.as_ref());
+            self.opt_timestamp(format!("{}.remindAt", path), task.remind_at.as_ref());
+            self.opt_date(format!("{}.thresholdDate", path), task.threshold_date.as_ref());
+            if let Some(recurrence) = &task.recurrence {
+                self.opt_date(
+                    format!("{}.recurrence.nextDue", path),
+                    recurrence.next_due.as_ref(),
+                );
+            }
+            if let Some(extended) = task.unit_override.as_ref().and_then(|o| o.extended_until.as_ref()) {
+                self.date_or_timestamp(format!("{}.unitOverride.extendedUntil", path), extended);
+            }
+
+            if let Some(subtasks) = &task.subtasks {
+                let mut seen_subtasks = HashSet::new();
+                for (j, subtask) in subtasks.iter().enumerate() {
+                    let sub_path = format!("{}.subtasks[{}]", path, j);
+                    self.id(format!("{}.id", sub_path), &subtask.id, &mut seen_subtasks);
+                    self.opt_timestamp(
+                        format!("{}.completedAt", sub_path),
+                        subtask.completed_at.as_ref(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ActiveData {
+    /// Check invariants serde cannot express (unique IDs, date formats)
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut v = Validator::default();
+        if !self.last_modified.is_empty() {
+            v.timestamp("lastModified".to_string(), &self.last_modified);
+        }
+        v.tasks("tasks", &self.tasks);
+        if let Err(message) = self.settings.unit_layout.check() {
+            v.issues.push(ValidationIssue::InvalidUnitLayout { message });
+        }
+
+        let mut seen = HashSet::new();
+        for (i, review) in self.reviews.iter().enumerate() {
+            let path = format!("reviews[{}]", i);
+            v.id(format!("{}.id", path), &review.id, &mut seen);
+            v.date_or_timestamp(format!("{}.unitStart", path), &review.unit_start);
+            v.date_or_timestamp(format!("{}.unitEnd", path), &review.unit_end);
+        }
+
+        if let Some(state) = &self.cycle_state {
+            v.date("cycleState.anchorStart".to_string(), &state.anchor_start);
+            v.date("cycleState.windowEnd".to_string(), &state.window_end);
+            // Empty until the first period has been scored
+            if !state.last_evaluated_start.is_empty() {
+                v.date(
+                    "cycleState.lastEvaluatedStart".to_string(),
+                    &state.last_evaluated_start,
+                );
+            }
+        }
+        if let Some(history) = &self.cycle_history {
+            for (i, entry) in history.iter().enumerate() {
+                v.date(format!("cycleHistory[{}].periodStart", i), &entry.period_start);
+            }
+        }
+
+        v.issues
+    }
+}
+
+impl ArchiveData {
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut v = Validator::default();
+        v.tasks("tasks", &self.tasks);
+        v.issues
+    }
+}
+
+impl PomodoroHistoryData {
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut v = Validator::default();
+        let mut seen = HashSet::new();
+        for (i, session) in self.sessions.iter().enumerate() {
+            let path = format!("sessions[{}]", i);
+            v.id(format!("{}.id", path), &session.id, &mut seen);
+            v.timestamp(format!("{}.startedAt", path), &session.started_at);
+        }
+        v.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn active(tasks: Value) -> ActiveData {
+        serde_json::from_value(json!({
+            "version": "2.0",
+            "lastModified": "2026-03-04T10:00:00Z",
+            "tasks": tasks
+        }))
+        .unwrap()
+    }
+
+    fn task(id: &str) -> Value {
+        json!({ "id": id, "content": id, "priority": "C" })
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_round_trip() {
+        let mut t = task("t1");
+        t["recurrence"] = json!({ "pattern": "1w", "nextDue": null, "anchor": "mon" });
+        t["subtasks"] = json!([{ "id": "s1", "content": "s", "estimate": 2 }]);
+        t["pomodoros"] = json!({ "estimated": 1, "completed": 0, "focus": "deep" });
+        let mut data = json!({
+            "tasks": [t],
+            "reviews": [{
+                "id": "r1",
+                "unitStart": "2026-03-01",
+                "unitEnd": "2026-03-02",
+                "mood": "good"
+            }],
+            "cycleState": { "anchorStart": "2026-03-01", "windowEnd": "2026-03-02", "streak": 3 },
+            "futureKey": true
+        });
+
+        let typed: ActiveData = serde_json::from_value(data.clone()).unwrap();
+        let back = serde_json::to_value(&typed).unwrap();
+        for pointer in [
+            "/tasks/0/recurrence/anchor",
+            "/tasks/0/subtasks/0/estimate",
+            "/tasks/0/pomodoros/focus",
+            "/reviews/0/mood",
+            "/cycleState/streak",
+            "/futureKey",
+        ] {
+            assert_eq!(back.pointer(pointer), data.pointer(pointer), "{}", pointer);
+        }
+
+        data = json!({
+            "sessions": [{ "id": "p1", "startedAt": "2026-03-04T10:00:00Z", "tag": "x" }],
+            "device": "a"
+        });
+        let typed: PomodoroHistoryData = serde_json::from_value(data.clone()).unwrap();
+        let back = serde_json::to_value(&typed).unwrap();
+        assert_eq!(back["sessions"][0]["tag"], "x");
+        assert_eq!(back["device"], "a");
+    }
+
+    #[test]
+    fn valid_data_has_no_issues() {
+        let mut t = task("t1");
+        t["createdAt"] = json!("2026-03-04T10:00:00+08:00");
+        t["dueDate"] = json!("2026-03-05");
+        t["dueTime"] = json!("09:30");
+        t["remindAt"] = json!("2026-03-05T01:00:00.000Z");
+        t["unitOverride"] = json!({ "extendedUntil": "2026-03-06T00:00:00Z" });
+        assert_eq!(active(json!([t, task("t2")])).validate(), []);
+    }
+
+    #[test]
+    fn duplicate_and_empty_ids_are_reported() {
+        let mut t = task("t1");
+        t["subtasks"] = json!([
+            { "id": "s1", "content": "a" },
+            { "id": "s1", "content": "b" }
+        ]);
+        let issues = active(json!([t, task("t1"), task("")])).validate();
+        assert_eq!(
+            issues,
+            [
+                ValidationIssue::DuplicateId { id: "s1".to_string() },
+                ValidationIssue::DuplicateId { id: "t1".to_string() },
+                ValidationIssue::EmptyId {
+                    path: "tasks[2].id".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_dates_and_times_name_their_field() {
+        let mut t = task("t1");
+        t["dueDate"] = json!("2026-13-01");
+        t["dueTime"] = json!("9am");
+        t["remindAt"] = json!("2026-03-05 09:00");
+        let mut data = active(json!([t]));
+        data.last_modified = "yesterday".to_string();
+
+        let paths: Vec<(String, &str)> = data
+            .validate()
+            .into_iter()
+            .filter_map(|issue| match issue {
+                ValidationIssue::MalformedDate { path, expected, .. } => Some((path, expected)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                ("lastModified".to_string(), TIMESTAMP_FORMAT),
+                ("tasks[0].dueDate".to_string(), DATE_FORMAT),
+                ("tasks[0].dueTime".to_string(), TIME_FORMAT),
+                ("tasks[0].remindAt".to_string(), TIMESTAMP_FORMAT),
+            ]
+        );
+    }
+
+    #[test]
+    fn history_sessions_need_ids_and_start_times() {
+        let history: PomodoroHistoryData = serde_json::from_value(json!({
+            "sessions": [
+                { "id": "p1", "startedAt": "2026-03-04T10:00:00Z" },
+                { "id": "p1", "startedAt": "not a time" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(history.validate().len(), 2);
+    }
+}
@@ -0,0 +1,397 @@
+//! Quick-add inline syntax, ported from `parseTaskInput` in
+//! `src/lib/utils/parser.ts` so the backend can create tasks from text.
+//!
+//! Recognised tokens are removed from the content:
+//! - `!A` … `!S` for priority, or `【A】` with full-width brackets (F when absent)
+//! - `+project`, `@context`, `#tag` and emoji tags like `💻编码`
+//! - `~2024-12-01`, `~tomorrow`, `~+3d`, `~12-25` for the due date
+//! - `thr:2024-12-01` or `thr:+3d` for the threshold date
+//! - `rec:1d`, `rec:weekly`, `rec:mon,wed,fri`, `rec:1m@15`,
+//!   `rec:FREQ=MONTHLY;BYDAY=2TU` for recurrence
+//! - `🍅3` or `p3` for estimated pomodoros
+//!
+//! Both implementations are checked against `src/lib/utils/parser.vectors.json`;
+//! keep them in step when changing the syntax.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Map;
+use std::sync::LazyLock;
+
+use crate::model::{PomodoroCount, Priority, Recurrence, RecurrencePattern, Task};
+use crate::units::UnitLayout;
+
+// JavaScript's `\d` and `\w` are ASCII-only, so the patterns spell those out
+static BRACKET_PRIORITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|\s)【\s*([ABCDEFNSabcdefns])\s*】(\s|$)").unwrap());
+static PROJECT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\+(\S+)").unwrap());
+static CONTEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"@(\S+)").unwrap());
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#(\S+)").unwrap());
+static POMODOROS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:🍅|p)([0-9]+)").unwrap());
+static EMOJI_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([\x{1F300}-\x{1F9FF}][\x{4e00}-\x{9fa5}A-Za-z0-9_]+)").unwrap());
+static THRESHOLD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[tT][hH][rR]:(\S+)").unwrap());
+static RECURRENCE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[rR][eE][cC]:(\S+)").unwrap());
+static DUE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~(\S+)").unwrap());
+static WHITESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+static RELATIVE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\+?([0-9]+)([dwm])$").unwrap());
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$").unwrap());
+static SHORT_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([0-9]{1,2})[-/]([0-9]{1,2})$").unwrap());
+static WEEKDAYS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(mon|tue|wed|thu|fri|sat|sun)(,(mon|tue|wed|thu|fri|sat|sun))*$").unwrap()
+});
+static MONTHLY_DAY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([0-9]+)m@([0-9]+|last)$").unwrap());
+
+/// Result of parsing quick-add input (`ParsedTask` in the frontend)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedTask {
+    pub content: String,
+    pub priority: Priority,
+    pub projects: Vec<String>,
+    pub contexts: Vec<String>,
+    pub custom_tags: Vec<String>,
+    pub due_date: Option<String>,
+    pub threshold_date: Option<String>,
+    pub estimated_pomodoros: u32,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl ParsedTask {
+    /// Build a new task from the parsed input (`createTaskFromInput`)
+    pub fn into_task(self, now: DateTime<Local>, layout: &UnitLayout) -> Task {
+        Task {
+            id: uuid::Uuid::new_v4().to_string(),
+            content: self.content,
+            priority: self.priority,
+            completed: false,
+            completed_at: None,
+            created_at: now.to_rfc3339(),
+            unit_start: format_date(layout.unit_start(now.date_naive())),
+            projects: self.projects,
+            contexts: self.contexts,
+            custom_tags: self.custom_tags,
+            due_date: self.due_date,
+            due_time: None,
+            remind_at: None,
+            threshold_date: self.threshold_date,
+            recurrence: self.recurrence,
+            pomodoros: PomodoroCount {
+                estimated: self.estimated_pomodoros,
+                completed: 0,
+                extra: Map::new(),
+            },
+            notes: String::new(),
+            subtasks: None,
+            unit_override: None,
+            original_priority: None,
+            last_priority_change_at: None,
+            evolved_from: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+fn priority_from_letter(letter: char) -> Option<Priority> {
+    match letter.to_ascii_uppercase() {
+        'A' => Some(Priority::A),
+        'B' => Some(Priority::B),
+        'C' => Some(Priority::C),
+        'D' => Some(Priority::D),
+        'E' => Some(Priority::E),
+        'F' => Some(Priority::F),
+        'N' => Some(Priority::N),
+        'S' => Some(Priority::S),
+        _ => None,
+    }
+}
+
+/// `!X` priority marks not followed by another ASCII letter, as byte offsets
+/// of the `!` with their priority
+fn priority_marks(s: &str) -> Vec<(usize, Priority)> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    chars
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, c))| *c == '!')
+        .filter_map(|(i, &(pos, _))| {
+            let priority = priority_from_letter(chars.get(i + 1)?.1)?;
+            let followed_by_letter = chars
+                .get(i + 2)
+                .is_some_and(|(_, next)| next.is_ascii_alphabetic());
+            (!followed_by_letter).then_some((pos, priority))
+        })
+        .collect()
+}
+
+/// Collect the first capture of every match of `re`, then remove the matches
+fn extract(content: &mut String, re: &Regex) -> Vec<String> {
+    let found = re
+        .captures_iter(content)
+        .map(|caps| caps[1].to_string())
+        .collect();
+    *content = re.replace_all(content, "").trim().to_string();
+    found
+}
+
+/// Parse quick-add input relative to today's date
+pub fn parse_task_input(input: &str) -> ParsedTask {
+    parse_task_input_on(input, Local::now().date_naive())
+}
+
+/// Parse quick-add input, resolving relative dates against `today`
+pub fn parse_task_input_on(input: &str, today: NaiveDate) -> ParsedTask {
+    let mut content = input.trim().to_string();
+    let mut priority = Priority::F;
+
+    // Extract priority. `!X` wins; the bracket form needs whitespace or the
+    // ends of the input around it, so "见【A】部分" keeps its brackets.
+    let marks = priority_marks(&content);
+    if let Some(&(_, first)) = marks.first() {
+        priority = first;
+        for &(pos, _) in marks.iter().rev() {
+            content.replace_range(pos..pos + 2, "");
+        }
+        content = WHITESPACE.replace_all(&content, " ").trim().to_string();
+    } else if let Some(caps) = BRACKET_PRIORITY.captures(&content) {
+        priority = caps[2]
+            .chars()
+            .next()
+            .and_then(priority_from_letter)
+            .unwrap_or(Priority::F);
+        content = BRACKET_PRIORITY.replace_all(&content, " ").to_string();
+        content = WHITESPACE.replace_all(&content, " ").trim().to_string();
+    }
+
+    let projects = extract(&mut content, &PROJECT);
+    let contexts = extract(&mut content, &CONTEXT);
+    let mut custom_tags = extract(&mut content, &TAG);
+
+    // Pomodoros before emoji tags, so 🍅3 isn't taken for a tag
+    let estimated_pomodoros = extract(&mut content, &POMODOROS)
+        .first()
+        .map_or(0, |n| n.parse().unwrap_or(u32::MAX));
+
+    custom_tags.extend(extract(&mut content, &EMOJI_TAG));
+
+    let threshold_date = extract(&mut content, &THRESHOLD)
+        .first()
+        .and_then(|date| parse_date_string(date, today));
+
+    let recurrence = extract(&mut content, &RECURRENCE)
+        .first()
+        .and_then(|pattern| parse_recurrence(pattern));
+
+    let due_date = extract(&mut content, &DUE)
+        .first()
+        .and_then(|date| parse_date_string(date, today));
+
+    // Clean up extra spaces
+    let content = WHITESPACE.replace_all(&content, " ").trim().to_string();
+
+    ParsedTask {
+        content,
+        priority,
+        projects,
+        contexts,
+        custom_tags,
+        due_date,
+        threshold_date,
+        estimated_pomodoros,
+        recurrence,
+    }
+}
+
+/// `new Date(year, month, day)`: out-of-range months and days roll over
+fn rolled_date(year: i64, month0: i64, day: i64) -> Option<NaiveDate> {
+    let year = i32::try_from(year.checked_add(month0.div_euclid(12))?).ok()?;
+    let month = u32::try_from(month0.rem_euclid(12)).ok()? + 1;
+    NaiveDate::from_ymd_opt(year, month, 1)?.checked_add_signed(Duration::try_days(day - 1)?)
+}
+
+/// Add months the way `Date.setMonth` does (Jan 31 + 1 month is Mar 3 or 2)
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    rolled_date(
+        i64::from(date.year()),
+        i64::from(date.month0()).checked_add(months)?,
+        i64::from(date.day()),
+    )
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Parse a relative or absolute date (`parseDateString`)
+pub fn parse_date_string(date_str: &str, today: NaiveDate) -> Option<String> {
+    let lower = date_str.to_lowercase();
+
+    match lower.as_str() {
+        "today" | "今天" => return Some(format_date(today)),
+        "tomorrow" | "明天" => return Some(format_date(today + Duration::days(1))),
+        "后天" => return Some(format_date(today + Duration::days(2))),
+        _ => {}
+    }
+
+    // Relative days, weeks or months (+3d, 2w, +1m)
+    if let Some(caps) = RELATIVE.captures(&lower) {
+        let n: i64 = caps[1].parse().ok()?;
+        let date = match &caps[2] {
+            "d" => today.checked_add_signed(Duration::try_days(n)?)?,
+            "w" => today.checked_add_signed(Duration::try_days(n.checked_mul(7)?)?)?,
+            _ => add_months(today, n)?,
+        };
+        return Some(format_date(date));
+    }
+
+    if ISO_DATE.is_match(date_str) {
+        return Some(date_str.to_string());
+    }
+
+    // MM-DD or MM/DD; a date already past this year means next year
+    if let Some(caps) = SHORT_DATE.captures(date_str) {
+        let month0 = caps[1].parse::<i64>().ok()? - 1;
+        let day = caps[2].parse::<i64>().ok()?;
+        let year = i64::from(today.year());
+        let mut date = rolled_date(year, month0, day)?;
+        if date < today {
+            date = rolled_date(
+                i64::from(date.year()) + 1,
+                i64::from(date.month0()),
+                i64::from(date.day()),
+            )?;
+        }
+        return Some(format_date(date));
+    }
+
+    None
+}
+
+/// Parse a recurrence pattern (`parseRecurrence`)
+pub fn parse_recurrence(pattern_str: &str) -> Option<Recurrence> {
+    use RecurrencePattern::*;
+
+    let lower = pattern_str.to_lowercase();
+
+    let standard = match lower.as_str() {
+        "1d" | "daily" | "每天" => Some(OneDay),
+        "2d" | "隔天" => Some(TwoDays),
+        "3d" => Some(ThreeDays),
+        "1w" | "weekly" | "每周" => Some(OneWeek),
+        "2w" | "biweekly" | "隔周" => Some(TwoWeeks),
+        "1m" | "monthly" | "每月" => Some(OneMonth),
+        "3m" | "quarterly" | "每季" => Some(ThreeMonths),
+        _ => None,
+    };
+    if let Some(pattern) = standard {
+        return Some(Recurrence {
+            pattern: Some(pattern),
+            custom_pattern: None,
+            rrule: None,
+            series_id: None,
+            next_due: None,
+            extra: Map::new(),
+        });
+    }
+
+    // Weekdays like mon,wed,fri
+    if WEEKDAYS.is_match(&lower) {
+        return Some(Recurrence {
+            pattern: None,
+            custom_pattern: Some(lower),
+            rrule: None,
+            series_id: None,
+            next_due: None,
+            extra: Map::new(),
+        });
+    }
+
+    // A day of the month: 1m@15 (15th of each month), 3m@last
+    if let Some(caps) = MONTHLY_DAY.captures(&lower) {
+        let pattern = if &caps[1] == "1" {
+            OneMonth
+        } else {
+            ThreeMonths
+        };
+        return Some(Recurrence {
+            pattern: Some(pattern),
+            custom_pattern: Some(lower.clone()),
+            rrule: None,
+            series_id: None,
+            next_due: None,
+            extra: Map::new(),
+        });
+    }
+
+    // An RFC 5545 rule: FREQ=MONTHLY;BYDAY=2TU
+    if lower.starts_with("freq=") {
+        return Some(Recurrence {
+            pattern: None,
+            custom_pattern: None,
+            rrule: Some(pattern_str.to_uppercase()),
+            series_id: None,
+            next_due: None,
+            extra: Map::new(),
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const VECTORS: &str = include_str!("../../src/lib/utils/parser.vectors.json");
+
+    #[test]
+    fn matches_shared_vectors() {
+        let vectors: Value = serde_json::from_str(VECTORS).unwrap();
+        let today =
+            NaiveDate::parse_from_str(vectors["today"].as_str().unwrap(), "%Y-%m-%d").unwrap();
+
+        let failures: Vec<String> = vectors["cases"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|case| {
+                let input = case["input"].as_str().unwrap();
+                let actual = serde_json::to_value(parse_task_input_on(input, today)).unwrap();
+                (actual != case["expected"]).then(|| {
+                    format!(
+                        "{:?}\n  expected {}\n  actual   {}",
+                        input, case["expected"], actual
+                    )
+                })
+            })
+            .collect();
+
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn builds_a_task_for_the_current_unit() {
+        let now = Local::now();
+        let layout = UnitLayout {
+            week_start: chrono::Weekday::Mon,
+            unit_lengths: vec![3, 3],
+            review_day: Some(chrono::Weekday::Sun),
+        };
+        let task =
+            parse_task_input_on("Write report !B 🍅2", now.date_naive()).into_task(now, &layout);
+
+        assert_eq!(task.content, "Write report");
+        assert_eq!(task.priority, Priority::B);
+        assert_eq!(task.pomodoros.estimated, 2);
+        assert_eq!(
+            task.unit_start,
+            format_date(layout.unit_start(now.date_naive()))
+        );
+    }
+}
@@ -0,0 +1,685 @@
+//! Pomodoro timer, owned by the backend so a webview reload, sleep or
+//! throttled tab can't lose or skew a session.
+//!
+//! A running phase ends at a wall-clock deadline rather than after a count of
+//! ticks, so time spent suspended or with the app closed still counts. The
+//! timer is saved to `pomodoro_state.json` after every change and loaded at
+//! startup; phases whose deadline passed while the app was down are completed
+//! then. Finished work sessions are appended to `pomodoro_history.json` and
+//! counted on their task in `active.json`.
+//!
+//! The frontend drives the timer through commands and follows it through the
+//! `pomodoro-tick` (once a second while running) and `pomodoro-phase` events.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{self, DataFileType};
+use crate::durable;
+use crate::lock;
+use crate::model::{PomodoroSession, Settings};
+
+/// File holding the in-progress timer, next to the data files
+pub const STATE_FILENAME: &str = "pomodoro_state.json";
+
+/// Every fourth work session is followed by a long break
+const LONG_BREAK_EVERY: u32 = 4;
+
+const TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    #[default]
+    Idle,
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Phase lengths in minutes, taken from the settings when a session starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Durations {
+    pub work: u32,
+    pub short_break: u32,
+    pub long_break: u32,
+}
+
+impl Default for Durations {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+impl Durations {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            work: settings.pomodoro_work,
+            short_break: settings.pomodoro_short_break,
+            long_break: settings.pomodoro_long_break,
+        }
+    }
+
+    fn of(&self, phase: Phase) -> Duration {
+        let minutes = match phase {
+            Phase::Idle | Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        };
+        Duration::minutes(i64::from(minutes))
+    }
+}
+
+/// Timer state, as saved to `pomodoro_state.json`
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Timer {
+    pub phase: Phase,
+    pub task_id: Option<String>,
+    pub durations: Durations,
+    /// When the current phase ends; `None` while paused or idle
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Time left in the current phase while paused, in seconds
+    pub paused_remaining: i64,
+    /// When the current work session started
+    pub work_started_at: Option<DateTime<Utc>>,
+    /// Work sessions finished so far, for spacing the long breaks
+    pub session_count: u32,
+    pub interruptions: u32,
+    pub interruption_reasons: Vec<String>,
+    /// Finished sessions not yet written to the history, e.g. because the
+    /// data directory was locked. Retried on every tick.
+    pub unrecorded: Vec<PomodoroSession>,
+    /// Tasks of sessions in the history whose pomodoro is not yet counted in
+    /// `active.json`. Retried on every tick.
+    pub uncounted: Vec<String>,
+}
+
+/// A move from one phase to another
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseChange {
+    pub from: Phase,
+    pub to: Phase,
+    /// Whether `from` ran out or was skipped, rather than being stopped
+    pub finished: bool,
+    /// The session recorded when a work phase finished
+    pub session: Option<PomodoroSession>,
+}
+
+/// What the frontend shows
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub phase: Phase,
+    pub task_id: Option<String>,
+    pub is_running: bool,
+    pub remaining_secs: i64,
+    pub total_secs: i64,
+    pub session_count: u32,
+    pub interruptions: u32,
+    pub interruption_reasons: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhaseChanged<'a> {
+    #[serde(flatten)]
+    change: &'a PhaseChange,
+    timer: &'a Snapshot,
+}
+
+impl Timer {
+    pub fn is_running(&self) -> bool {
+        self.ends_at.is_some()
+    }
+
+    /// Seconds left in the current phase at `now`
+    pub fn remaining(&self, now: DateTime<Utc>) -> i64 {
+        match self.ends_at {
+            Some(ends_at) => (ends_at - now).num_seconds().max(0),
+            None => self.paused_remaining,
+        }
+    }
+
+    pub fn snapshot(&self, now: DateTime<Utc>) -> Snapshot {
+        Snapshot {
+            phase: self.phase,
+            task_id: self.task_id.clone(),
+            is_running: self.is_running(),
+            remaining_secs: self.remaining(now),
+            total_secs: self.durations.of(self.phase).num_seconds(),
+            session_count: self.session_count,
+            interruptions: self.interruptions,
+            interruption_reasons: self.interruption_reasons.clone(),
+        }
+    }
+
+    /// Start a work session on a task, abandoning any session in progress
+    pub fn start(
+        &mut self,
+        task_id: String,
+        durations: Durations,
+        now: DateTime<Utc>,
+    ) -> PhaseChange {
+        let from = self.phase;
+        self.phase = Phase::Work;
+        self.task_id = Some(task_id);
+        self.durations = durations;
+        self.ends_at = Some(now + durations.of(Phase::Work));
+        self.work_started_at = Some(now);
+        self.interruptions = 0;
+        self.interruption_reasons.clear();
+        PhaseChange {
+            from,
+            to: Phase::Work,
+            finished: false,
+            session: None,
+        }
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) {
+        if self.is_running() {
+            self.paused_remaining = self.remaining(now);
+            self.ends_at = None;
+        }
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) {
+        if self.phase != Phase::Idle && !self.is_running() {
+            self.ends_at = Some(now + Duration::seconds(self.paused_remaining));
+        }
+    }
+
+    pub fn toggle(&mut self, now: DateTime<Utc>) {
+        if self.is_running() {
+            self.pause(now);
+        } else {
+            self.resume(now);
+        }
+    }
+
+    /// Count an interruption of the current work session
+    pub fn interrupt(&mut self, reason: Option<String>) {
+        if self.phase == Phase::Work {
+            self.interruptions += 1;
+            self.interruption_reasons.extend(reason);
+        }
+    }
+
+    /// Drop the current phase without recording it. `None` if already idle.
+    pub fn stop(&mut self) -> Option<PhaseChange> {
+        if self.phase == Phase::Idle {
+            return None;
+        }
+        let from = self.phase;
+        self.go_idle();
+        Some(PhaseChange {
+            from,
+            to: Phase::Idle,
+            finished: false,
+            session: None,
+        })
+    }
+
+    /// Finish the current phase now. `None` if idle.
+    pub fn skip(&mut self, now: DateTime<Utc>) -> Option<PhaseChange> {
+        (self.phase != Phase::Idle).then(|| self.finish(now))
+    }
+
+    /// Finish every phase whose deadline has passed by `now`. Each phase
+    /// starts when the previous one was due to end, so a break that also
+    /// ran out while the app was closed is finished too.
+    pub fn catch_up(&mut self, now: DateTime<Utc>) -> Vec<PhaseChange> {
+        let mut changes = Vec::new();
+        while let Some(ends_at) = self.ends_at.filter(|ends_at| *ends_at <= now) {
+            changes.push(self.finish(ends_at));
+        }
+        changes
+    }
+
+    fn finish(&mut self, at: DateTime<Utc>) -> PhaseChange {
+        let from = self.phase;
+        if from != Phase::Work {
+            self.go_idle();
+            return PhaseChange {
+                from,
+                to: Phase::Idle,
+                finished: true,
+                session: None,
+            };
+        }
+
+        let session = self.task_id.clone().map(|task_id| PomodoroSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            task_id,
+            started_at: self.work_started_at.unwrap_or(at).to_rfc3339(),
+            duration: self.durations.work,
+            completed: true,
+            interruptions: Some(self.interruptions),
+            interruption_reasons: Some(std::mem::take(&mut self.interruption_reasons)),
+            extra: Map::new(),
+        });
+        self.unrecorded.extend(session.clone());
+        self.session_count += 1;
+        self.interruptions = 0;
+
+        self.phase = if self.session_count.is_multiple_of(LONG_BREAK_EVERY) {
+            Phase::LongBreak
+        } else {
+            Phase::ShortBreak
+        };
+        self.ends_at = Some(at + self.durations.of(self.phase));
+        PhaseChange {
+            from,
+            to: self.phase,
+            finished: true,
+            session,
+        }
+    }
+
+    fn go_idle(&mut self) {
+        self.phase = Phase::Idle;
+        self.task_id = None;
+        self.ends_at = None;
+        self.paused_remaining = self.durations.of(Phase::Work).num_seconds();
+        self.work_started_at = None;
+        self.interruptions = 0;
+        self.interruption_reasons.clear();
+    }
+}
+
+/// Append sessions to pomodoro history data, skipping any already in it (a
+/// crash between writing the history and saving the timer records a session
+/// twice). Returns the task IDs of the sessions added.
+fn append_sessions(
+    history: &mut Value,
+    sessions: &[PomodoroSession],
+) -> Result<Vec<String>, String> {
+    let Some(obj) = history.as_object_mut() else {
+        return Err("Pomodoro history is not an object".to_string());
+    };
+    let list = obj.entry("sessions").or_insert_with(|| json!([]));
+    let Some(list) = list.as_array_mut() else {
+        return Err("Pomodoro history sessions is not a list".to_string());
+    };
+
+    let seen: HashSet<String> = list
+        .iter()
+        .filter_map(|s| s.get("id").and_then(Value::as_str).map(String::from))
+        .collect();
+    let mut added = Vec::new();
+    for session in sessions.iter().filter(|s| !seen.contains(&s.id)) {
+        list.push(
+            serde_json::to_value(session)
+                .map_err(|e| format!("Failed to serialize pomodoro session: {}", e))?,
+        );
+        added.push(session.task_id.clone());
+    }
+    Ok(added)
+}
+
+/// Add one to the completed pomodoros of each task in `task_ids` (once per
+/// occurrence). Returns whether any task was found.
+fn count_pomodoros(active: &mut Value, task_ids: &[String]) -> bool {
+    let Some(tasks) = active.get_mut("tasks").and_then(Value::as_array_mut) else {
+        return false;
+    };
+    let mut counted = false;
+    for task_id in task_ids {
+        let Some(task) = tasks
+            .iter_mut()
+            .find(|t| t.get("id").and_then(Value::as_str) == Some(task_id))
+        else {
+            continue;
+        };
+        let completed = task
+            .pointer("/pomodoros/completed")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        // Only the count changes; other fields on the object are kept
+        if !task.get("pomodoros").is_some_and(Value::is_object) {
+            task["pomodoros"] = json!({ "estimated": 0, "completed": 0 });
+        }
+        task["pomodoros"]["completed"] = json!(completed + 1);
+        counted = true;
+    }
+    counted
+}
+
+/// Write finished sessions to `pomodoro_history.json` and count them on their
+/// tasks in `active.json`. Each step drains its own list, so a count that
+/// failed is retried without depending on the history write.
+fn record(data_dir: &Path, timer: &mut Timer) -> Result<(), String> {
+    let _lock = lock::acquire(data_dir)?;
+    let now = Utc::now().to_rfc3339();
+
+    if !timer.unrecorded.is_empty() {
+        let path = data_dir.join(DataFileType::PomodoroHistory.filename());
+        let mut history: Value = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse pomodoro history: {}", e))?,
+            Err(_) => json!({ "version": "2.0", "sessions": [] }),
+        };
+        if !append_sessions(&mut history, &timer.unrecorded)?.is_empty() {
+            history["lastModified"] = json!(now);
+            let content = serde_json::to_string_pretty(&history)
+                .map_err(|e| format!("Failed to serialize pomodoro history: {}", e))?;
+            commands::write_data_file(data_dir, DataFileType::PomodoroHistory, &content)?;
+        }
+        // Counted even when the history already had them: an earlier run may
+        // have written the history and failed to count
+        let sessions = std::mem::take(&mut timer.unrecorded);
+        timer
+            .uncounted
+            .extend(sessions.into_iter().map(|s| s.task_id));
+    }
+
+    if timer.uncounted.is_empty() {
+        return Ok(());
+    }
+    let path = data_dir.join(DataFileType::Active.filename());
+    let Ok(content) = fs::read_to_string(&path) else {
+        timer.uncounted.clear();
+        return Ok(());
+    };
+    let mut active: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+    if count_pomodoros(&mut active, &timer.uncounted) {
+        active["lastModified"] = json!(now);
+        let content = serde_json::to_string_pretty(&active)
+            .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+        commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+    }
+    timer.uncounted.clear();
+    Ok(())
+}
+
+fn load(data_dir: &Path) -> Timer {
+    let Ok(content) = fs::read_to_string(data_dir.join(STATE_FILENAME)) else {
+        return Timer::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!("Failed to parse pomodoro state, starting idle: {}", e);
+        Timer::default()
+    })
+}
+
+fn save(data_dir: &Path, timer: &Timer) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(timer)
+        .map_err(|e| format!("Failed to serialize pomodoro state: {}", e))?;
+    durable::write_atomic(&data_dir.join(STATE_FILENAME), content.as_bytes())
+}
+
+/// The shared timer, managed by Tauri
+#[derive(Default)]
+pub struct Pomodoro {
+    timer: Mutex<Timer>,
+}
+
+impl Pomodoro {
+    /// Pick up the timer saved by the last run
+    pub fn load(data_dir: &Path) -> Self {
+        Self {
+            timer: Mutex::new(load(data_dir)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Timer> {
+        self.timer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Apply a command to the timer, after finishing any phase whose deadline
+    /// has passed, and tell the frontend about the phases it moved through
+    pub fn command(
+        &self,
+        app_handle: &AppHandle,
+        command: impl FnOnce(&mut Timer, DateTime<Utc>) -> Option<PhaseChange>,
+    ) -> Result<Snapshot, String> {
+        let data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?;
+
+        let mut timer = self.lock();
+        let now = Utc::now();
+        let mut changes = timer.catch_up(now);
+        changes.extend(command(&mut timer, now));
+        save(&data_dir, &timer)?;
+
+        let snapshot = timer.snapshot(now);
+        settle(app_handle, &data_dir, &mut timer, &changes, &snapshot);
+        Ok(snapshot)
+    }
+
+    fn tick(&self, app_handle: &AppHandle) -> Result<(), String> {
+        let mut timer = self.lock();
+        if timer.phase == Phase::Idle && timer.unrecorded.is_empty() && timer.uncounted.is_empty() {
+            return Ok(());
+        }
+        let data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?;
+
+        let now = Utc::now();
+        let changes = timer.catch_up(now);
+        if !changes.is_empty() {
+            save(&data_dir, &timer)?;
+        }
+
+        let snapshot = timer.snapshot(now);
+        settle(app_handle, &data_dir, &mut timer, &changes, &snapshot);
+        if timer.is_running() {
+            if let Err(e) = app_handle.emit("pomodoro-tick", &snapshot) {
+                eprintln!("Failed to emit event: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Record finished sessions and announce phase changes
+fn settle(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    timer: &mut Timer,
+    changes: &[PhaseChange],
+    snapshot: &Snapshot,
+) {
+    if !timer.unrecorded.is_empty() || !timer.uncounted.is_empty() {
+        // Saved either way, as the history may have been written
+        if let Err(e) = record(data_dir, timer) {
+            eprintln!("Failed to record pomodoro sessions: {}", e);
+        }
+        if let Err(e) = save(data_dir, timer) {
+            eprintln!("{}", e);
+        }
+    }
+
+    for change in changes {
+        let event = PhaseChanged {
+            change,
+            timer: snapshot,
+        };
+        if let Err(e) = app_handle.emit("pomodoro-phase", event) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
+}
+
+/// Tick the timer once a second for as long as the app runs
+pub async fn run(app_handle: AppHandle, pomodoro: Arc<Pomodoro>) {
+    loop {
+        if let Err(e) = pomodoro.tick(&app_handle) {
+            eprintln!("Failed to advance pomodoro timer: {}", e);
+        }
+        tokio::time::sleep(TICK).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2026-03-02T{}:00Z", time))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn durations() -> Durations {
+        Durations {
+            work: 25,
+            short_break: 5,
+            long_break: 20,
+        }
+    }
+
+    #[test]
+    fn pausing_freezes_the_remaining_time() {
+        let mut timer = Timer::default();
+        timer.start("t1".to_string(), durations(), at("09:00"));
+        timer.pause(at("09:10"));
+        assert_eq!(timer.remaining(at("11:00")), 15 * 60);
+        assert!(timer.catch_up(at("11:00")).is_empty());
+
+        timer.resume(at("11:00"));
+        assert_eq!(timer.ends_at, Some(at("11:15")));
+        assert_eq!(timer.snapshot(at("11:05")).remaining_secs, 10 * 60);
+    }
+
+    #[test]
+    fn catch_up_finishes_phases_that_ran_out_while_closed() {
+        let mut timer = Timer::default();
+        timer.start("t1".to_string(), durations(), at("09:00"));
+        timer.interrupt(Some("phone".to_string()));
+
+        // Work ended at 09:25 and its short break at 09:30
+        let changes = timer.catch_up(at("10:00"));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            (changes[0].from, changes[0].to),
+            (Phase::Work, Phase::ShortBreak)
+        );
+        assert_eq!(
+            (changes[1].from, changes[1].to),
+            (Phase::ShortBreak, Phase::Idle)
+        );
+
+        let session = changes[0].session.as_ref().unwrap();
+        assert_eq!(session.task_id, "t1");
+        assert_eq!(session.started_at, at("09:00").to_rfc3339());
+        assert_eq!(
+            session.interruption_reasons,
+            Some(vec!["phone".to_string()])
+        );
+        assert_eq!(timer.unrecorded, vec![session.clone()]);
+        assert_eq!(timer.phase, Phase::Idle);
+        assert_eq!(timer.session_count, 1);
+    }
+
+    #[test]
+    fn every_fourth_session_earns_a_long_break() {
+        let mut timer = Timer::default();
+        let mut breaks = Vec::new();
+        for hour in ["09", "10", "11", "12"] {
+            timer.start("t1".to_string(), durations(), at(&format!("{}:00", hour)));
+            breaks.push(timer.skip(at(&format!("{}:10", hour))).unwrap().to);
+            timer.stop();
+        }
+        assert_eq!(
+            breaks,
+            [
+                Phase::ShortBreak,
+                Phase::ShortBreak,
+                Phase::ShortBreak,
+                Phase::LongBreak
+            ]
+        );
+        assert_eq!(timer.skip(at("12:30")), None);
+    }
+
+    #[test]
+    fn recording_twice_adds_a_session_once() {
+        let mut timer = Timer::default();
+        timer.start("t1".to_string(), durations(), at("09:00"));
+        timer.skip(at("09:20"));
+
+        let mut history = json!({ "version": "2.0", "sessions": [] });
+        assert_eq!(
+            append_sessions(&mut history, &timer.unrecorded).unwrap(),
+            ["t1"]
+        );
+        assert!(append_sessions(&mut history, &timer.unrecorded)
+            .unwrap()
+            .is_empty());
+        assert_eq!(history["sessions"].as_array().unwrap().len(), 1);
+
+        let mut active = json!({ "tasks": [
+            { "id": "t1", "pomodoros": { "estimated": 3, "completed": 1, "goal": "deep" } },
+            { "id": "t2" }
+        ] });
+        assert!(count_pomodoros(
+            &mut active,
+            &["t1".to_string(), "t2".to_string()]
+        ));
+        assert_eq!(
+            active["tasks"][0]["pomodoros"],
+            json!({ "estimated": 3, "completed": 2, "goal": "deep" })
+        );
+        assert_eq!(
+            active["tasks"][1]["pomodoros"],
+            json!({ "estimated": 0, "completed": 1 })
+        );
+        assert!(!count_pomodoros(&mut active, &["gone".to_string()]));
+    }
+
+    #[test]
+    fn a_failed_count_is_retried_without_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let active_path = dir.path().join("active.json");
+        let mut timer = Timer::default();
+        timer.start("t1".to_string(), durations(), at("09:00"));
+        timer.skip(at("09:20"));
+
+        fs::write(&active_path, "{").unwrap();
+        assert!(record(dir.path(), &mut timer).is_err());
+        assert!(timer.unrecorded.is_empty());
+        assert_eq!(timer.uncounted, ["t1"]);
+
+        let task = json!({
+            "id": "t1",
+            "content": "t1",
+            "priority": "A",
+            "createdAt": "2026-03-02T08:00:00Z",
+            "pomodoros": { "estimated": 2, "completed": 0 }
+        });
+        fs::write(
+            &active_path,
+            json!({ "version": "2.0", "tasks": [task] }).to_string(),
+        )
+        .unwrap();
+        record(dir.path(), &mut timer).unwrap();
+        assert!(timer.uncounted.is_empty());
+
+        let read = |name: &str| -> Value {
+            serde_json::from_str(&fs::read_to_string(dir.path().join(name)).unwrap()).unwrap()
+        };
+        assert_eq!(read("active.json")["tasks"][0]["pomodoros"]["completed"], 1);
+        assert_eq!(
+            read("pomodoro_history.json")["sessions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
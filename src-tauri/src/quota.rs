@@ -0,0 +1,521 @@
+//! Priority quotas and the Highlander rule (mirror of `src/lib/utils/quotaCore.ts`).
+//!
+//! The frontend checks quotas before it changes a task, but the CLI or a
+//! hand-edited `active.json` can still put three A tasks in play. Every write
+//! of active data goes through [`enforce`], which handles the overflow
+//! according to `settings.quotaMode`.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::WriteError;
+use crate::model::{ActiveData, Priority, Task};
+
+/// Priorities with a quota, in the order demotions cascade through them
+const LIMITED: [Priority; 6] = [
+    Priority::A,
+    Priority::B,
+    Priority::C,
+    Priority::D,
+    Priority::E,
+    Priority::S,
+];
+
+/// Priorities reported by `quota_status`
+const REPORTED: [Priority; 7] = [
+    Priority::A,
+    Priority::B,
+    Priority::C,
+    Priority::D,
+    Priority::E,
+    Priority::F,
+    Priority::S,
+];
+
+/// How many uncompleted tasks a priority may hold (`None` = unlimited)
+pub fn quota(priority: Priority) -> Option<usize> {
+    match priority {
+        Priority::A | Priority::S => Some(1),
+        Priority::B => Some(2),
+        Priority::C => Some(3),
+        Priority::D => Some(4),
+        Priority::E => Some(5),
+        _ => None,
+    }
+}
+
+/// Where demote mode moves an over-quota task: one level down, and into the
+/// idea pool after E. S has no level below it, so it goes to the idea pool too.
+fn demoted(priority: Priority) -> Priority {
+    match priority {
+        Priority::A => Priority::B,
+        Priority::B => Priority::C,
+        Priority::C => Priority::D,
+        Priority::D => Priority::E,
+        _ => Priority::F,
+    }
+}
+
+/// What to do with a write that puts more tasks in play than the quotas allow
+/// (`settings.quotaMode`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaMode {
+    /// Refuse writes that add to an overflow
+    Reject,
+    /// Keep the most recently prioritised tasks and demote the rest one
+    /// level, like `applyHighlanderRule` does for A
+    #[default]
+    Demote,
+    /// Write as is and report the overflow
+    Warn,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaViolation {
+    pub priority: Priority,
+    pub quota: usize,
+    /// Uncompleted tasks with this priority
+    pub count: usize,
+    /// Tasks that don't fit, in file order. The most recently prioritised
+    /// tasks are the ones that fit.
+    pub task_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Demotion {
+    pub task_id: String,
+    pub from: Priority,
+    pub to: Priority,
+}
+
+/// Payload of the `quota-enforced` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaReport {
+    pub mode: QuotaMode,
+    pub violations: Vec<QuotaViolation>,
+    pub demotions: Vec<Demotion>,
+}
+
+/// Result of [`enforce`]
+#[derive(Debug)]
+pub struct Enforced {
+    /// Content to write: the payload itself unless tasks were demoted
+    pub content: String,
+    /// `None` when the write is within quota (or adds nothing to an overflow
+    /// that was already on disk, in warn mode)
+    pub report: Option<QuotaReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityQuota {
+    pub priority: Priority,
+    pub used: usize,
+    /// `None` for unlimited priorities
+    pub quota: Option<usize>,
+    pub remaining: Option<usize>,
+}
+
+/// Returned by the `quota_status` command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaStatus {
+    pub mode: QuotaMode,
+    pub priorities: Vec<PriorityQuota>,
+}
+
+/// When the task got its current priority: its last priority change, or its
+/// creation. Unparseable timestamps count as oldest.
+fn prioritised_at(task: &Task) -> Option<DateTime<FixedOffset>> {
+    task.last_priority_change_at
+        .as_deref()
+        .unwrap_or(&task.created_at)
+        .parse()
+        .ok()
+}
+
+/// Indices of the uncompleted tasks that `priorities` puts at `priority`
+/// beyond its quota, in file order
+fn overflow(tasks: &[Task], priorities: &[Priority], priority: Priority) -> Vec<usize> {
+    let Some(quota) = quota(priority) else {
+        return Vec::new();
+    };
+
+    let mut in_play: Vec<usize> = (0..tasks.len())
+        .filter(|&i| !tasks[i].completed && priorities[i] == priority)
+        .collect();
+    if in_play.len() <= quota {
+        return Vec::new();
+    }
+
+    // Newest first; among equal timestamps the task later in the file wins
+    in_play.sort_by_key(|&i| std::cmp::Reverse((prioritised_at(&tasks[i]), i)));
+    let mut over = in_play.split_off(quota);
+    over.sort_unstable();
+    over
+}
+
+/// Priorities holding more uncompleted tasks than their quota
+pub fn violations(tasks: &[Task]) -> Vec<QuotaViolation> {
+    let priorities: Vec<Priority> = tasks.iter().map(|t| t.priority).collect();
+
+    LIMITED
+        .into_iter()
+        .filter_map(|priority| {
+            let over = overflow(tasks, &priorities, priority);
+            if over.is_empty() {
+                return None;
+            }
+            let quota = quota(priority).unwrap_or_default();
+            Some(QuotaViolation {
+                priority,
+                quota,
+                count: quota + over.len(),
+                task_ids: over.iter().map(|&i| tasks[i].id.clone()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Usage and remaining capacity per priority
+pub fn status(data: &ActiveData) -> QuotaStatus {
+    let priorities = REPORTED
+        .into_iter()
+        .map(|priority| {
+            let used = data
+                .tasks
+                .iter()
+                .filter(|t| !t.completed && t.priority == priority)
+                .count();
+            let quota = quota(priority);
+            PriorityQuota {
+                priority,
+                used,
+                quota,
+                remaining: quota.map(|q| q.saturating_sub(used)),
+            }
+        })
+        .collect();
+
+    QuotaStatus {
+        mode: data.settings.quota_mode,
+        priorities,
+    }
+}
+
+/// Demote over-quota tasks until every priority fits. A's overflow lands in
+/// B before B is checked, so it can cascade further down.
+fn demote(tasks: &[Task]) -> Vec<Demotion> {
+    let mut priorities: Vec<Priority> = tasks.iter().map(|t| t.priority).collect();
+    for priority in LIMITED {
+        for i in overflow(tasks, &priorities, priority) {
+            priorities[i] = demoted(priority);
+        }
+    }
+
+    tasks
+        .iter()
+        .zip(priorities)
+        .filter(|(task, to)| task.priority != *to)
+        .map(|(task, to)| Demotion {
+            task_id: task.id.clone(),
+            from: task.priority,
+            to,
+        })
+        .collect()
+}
+
+/// Check the quotas of an `active.json` payload (already validated) against
+/// the mode in its own settings. `previous` is the content on disk, if any:
+/// reject and warn mode only act on violations the write makes worse, so an
+/// overflow that is already on disk doesn't block unrelated edits.
+pub fn enforce(content: &str, previous: Option<&str>) -> Result<Enforced, WriteError> {
+    let data: ActiveData = serde_json::from_str(content)?;
+    let violations = violations(&data.tasks);
+    let mode = data.settings.quota_mode;
+
+    let unchanged = |report| {
+        Ok(Enforced {
+            content: content.to_string(),
+            report,
+        })
+    };
+
+    if violations.is_empty() {
+        return unchanged(None);
+    }
+
+    if mode == QuotaMode::Demote {
+        let demotions = demote(&data.tasks);
+        let mut doc: Value = serde_json::from_str(content)?;
+        if let Some(tasks) = doc.get_mut("tasks").and_then(Value::as_array_mut) {
+            for task in tasks.iter_mut() {
+                let id = task.get("id").and_then(Value::as_str);
+                if let Some(d) = demotions.iter().find(|d| Some(d.task_id.as_str()) == id) {
+                    task["priority"] = serde_json::to_value(d.to)?;
+                }
+            }
+        }
+        return Ok(Enforced {
+            content: serde_json::to_string_pretty(&doc)?,
+            report: Some(QuotaReport {
+                mode,
+                violations,
+                demotions,
+            }),
+        });
+    }
+
+    let before: Vec<QuotaViolation> = previous
+        .and_then(|p| serde_json::from_str::<ActiveData>(p).ok())
+        .map(|p| self::violations(&p.tasks))
+        .unwrap_or_default();
+    let worse: Vec<QuotaViolation> = violations
+        .into_iter()
+        .filter(|v| {
+            before
+                .iter()
+                .find(|b| b.priority == v.priority)
+                .is_none_or(|b| v.count > b.count)
+        })
+        .collect();
+
+    if worse.is_empty() {
+        return unchanged(None);
+    }
+    if mode == QuotaMode::Reject {
+        return Err(WriteError::QuotaExceeded { violations: worse });
+    }
+    unchanged(Some(QuotaReport {
+        mode,
+        violations: worse,
+        demotions: Vec::new(),
+    }))
+}
+
+/// Result of [`append_within_quota`]
+#[derive(Debug)]
+pub struct Appended {
+    /// Active data with the tasks that fit
+    pub content: String,
+    /// IDs of the tasks added, in order
+    pub task_ids: Vec<String>,
+    /// Tasks left out because their priority is full (reject mode)
+    pub rejected: usize,
+    pub report: Option<QuotaReport>,
+}
+
+/// Append tasks to an active document one at a time, so that in reject mode
+/// only those that don't fit are left out
+pub fn append_within_quota(mut active: Value, tasks: Vec<Value>) -> Result<Appended, String> {
+    let serialize = |active: &Value| {
+        serde_json::to_string_pretty(active)
+            .map_err(|e| format!("Failed to serialize active data: {}", e))
+    };
+
+    let mut current = serialize(&active)?;
+    let mut task_ids = Vec::new();
+    let mut rejected = 0;
+    let mut report: Option<QuotaReport> = None;
+    for task in tasks {
+        let id = task
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let mut candidate = active.clone();
+        if let Some(tasks) = candidate.get_mut("tasks").and_then(Value::as_array_mut) {
+            tasks.push(task);
+        }
+
+        match enforce(&serialize(&candidate)?, Some(&current)) {
+            Ok(enforced) => {
+                active = serde_json::from_str(&enforced.content)
+                    .map_err(|e| format!("Failed to parse active data: {}", e))?;
+                current = enforced.content;
+                task_ids.push(id);
+                if let Some(r) = enforced.report {
+                    match &mut report {
+                        Some(report) => {
+                            report.violations.extend(r.violations);
+                            report.demotions.extend(r.demotions);
+                        }
+                        None => report = Some(r),
+                    }
+                }
+            }
+            Err(WriteError::QuotaExceeded { .. }) => rejected += 1,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    Ok(Appended {
+        content: current,
+        task_ids,
+        rejected,
+        report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, priority: &str, created_at: &str) -> Value {
+        json!({ "id": id, "content": id, "priority": priority, "createdAt": created_at })
+    }
+
+    fn active(mode: &str, tasks: Vec<Value>) -> String {
+        json!({ "tasks": tasks, "settings": { "quotaMode": mode } }).to_string()
+    }
+
+    fn priorities(content: &str) -> Vec<(String, Priority)> {
+        let data: ActiveData = serde_json::from_str(content).unwrap();
+        data.tasks.into_iter().map(|t| (t.id, t.priority)).collect()
+    }
+
+    #[test]
+    fn demote_keeps_the_newest_a_and_cascades() {
+        let content = active(
+            "demote",
+            vec![
+                task("old-a", "A", "2026-01-01T09:00:00Z"),
+                task("b1", "B", "2026-01-02T09:00:00Z"),
+                task("b2", "B", "2026-01-03T09:00:00Z"),
+                task("new-a", "A", "2026-01-04T09:00:00+08:00"),
+            ],
+        );
+
+        let enforced = enforce(&content, None).unwrap();
+        assert_eq!(
+            priorities(&enforced.content),
+            [
+                ("old-a".to_string(), Priority::C),
+                ("b1".to_string(), Priority::B),
+                ("b2".to_string(), Priority::B),
+                ("new-a".to_string(), Priority::A),
+            ]
+        );
+        let report = enforced.report.unwrap();
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].task_ids, ["old-a"]);
+        assert_eq!(
+            report.demotions,
+            [Demotion {
+                task_id: "old-a".to_string(),
+                from: Priority::A,
+                to: Priority::C,
+            }]
+        );
+    }
+
+    #[test]
+    fn completed_and_unlimited_tasks_do_not_count() {
+        let mut done = task("done", "A", "2026-01-01T09:00:00Z");
+        done["completed"] = json!(true);
+        let content = active(
+            "reject",
+            vec![
+                done,
+                task("a", "A", "2026-01-02T09:00:00Z"),
+                task("f1", "F", "2026-01-02T09:00:00Z"),
+                task("f2", "F", "2026-01-02T09:00:00Z"),
+            ],
+        );
+
+        let enforced = enforce(&content, None).unwrap();
+        assert_eq!(enforced.content, content);
+        assert!(enforced.report.is_none());
+    }
+
+    #[test]
+    fn reject_and_warn_only_act_on_a_growing_overflow() {
+        let on_disk = |mode| {
+            active(
+                mode,
+                vec![
+                    task("s1", "S", "2026-01-01T09:00:00Z"),
+                    task("s2", "S", "2026-01-02T09:00:00Z"),
+                ],
+            )
+        };
+        let added = |mode| {
+            active(
+                mode,
+                vec![
+                    task("s1", "S", "2026-01-01T09:00:00Z"),
+                    task("s2", "S", "2026-01-02T09:00:00Z"),
+                    task("s3", "S", "2026-01-03T09:00:00Z"),
+                ],
+            )
+        };
+
+        // An edit that leaves the existing overflow alone goes through
+        assert!(enforce(&on_disk("reject"), Some(&on_disk("reject")))
+            .unwrap()
+            .report
+            .is_none());
+        assert!(enforce(&on_disk("warn"), Some(&on_disk("warn")))
+            .unwrap()
+            .report
+            .is_none());
+
+        match enforce(&added("reject"), Some(&on_disk("reject"))) {
+            Err(WriteError::QuotaExceeded { violations }) => {
+                assert_eq!(violations[0].priority, Priority::S);
+                assert_eq!(violations[0].count, 3);
+            }
+            other => panic!("expected a quota error, got {:?}", other),
+        }
+
+        let warned = enforce(&added("warn"), Some(&on_disk("warn"))).unwrap();
+        assert_eq!(warned.content, added("warn"));
+        assert_eq!(warned.report.unwrap().violations[0].task_ids, ["s1", "s2"]);
+    }
+
+    #[test]
+    fn status_reports_remaining_capacity() {
+        let content = active(
+            "warn",
+            vec![
+                task("b1", "B", "2026-01-01T09:00:00Z"),
+                task("f1", "F", "2026-01-01T09:00:00Z"),
+            ],
+        );
+        let status = status(&serde_json::from_str(&content).unwrap());
+
+        assert_eq!(status.mode, QuotaMode::Warn);
+        let b = &status.priorities[1];
+        assert_eq!((b.priority, b.used, b.remaining), (Priority::B, 1, Some(1)));
+        let f = &status.priorities[5];
+        assert_eq!((f.priority, f.used, f.remaining), (Priority::F, 1, None));
+    }
+
+    #[test]
+    fn append_leaves_out_only_the_tasks_that_do_not_fit() {
+        let content = active("reject", vec![task("a1", "A", "2026-01-01T09:00:00Z")]);
+        let doc: Value = serde_json::from_str(&content).unwrap();
+
+        let appended = append_within_quota(
+            doc,
+            vec![
+                task("f1", "F", "2026-01-02T09:00:00Z"),
+                task("a2", "A", "2026-01-02T09:00:00Z"),
+                task("b1", "B", "2026-01-02T09:00:00Z"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(appended.task_ids, ["f1", "b1"]);
+        assert_eq!(appended.rejected, 1);
+        assert!(appended.report.is_none());
+        let ids: Vec<_> = priorities(&appended.content).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, ["a1", "f1", "b1"]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Detection and recovery of corrupted data files.
+//!
+//! A data file that no longer parses (truncated write, bad hand edit) is moved
+//! aside under a timestamped `*.corrupt-*` name and replaced with the best
+//! surviving copy: a leftover temp file from an interrupted write, or else the
+//! newest backup whose copy of the file is intact.
+
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::backup;
+use crate::commands::{self, DataFileType};
+use crate::durable;
+use crate::error::WriteError;
+use crate::lock;
+
+/// Where a recovered file's content came from
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RecoverySource {
+    /// Temp file left behind by an interrupted write
+    #[serde(rename_all = "camelCase")]
+    TempFile { file_name: String },
+    #[serde(rename_all = "camelCase")]
+    Backup { backup_id: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryReport {
+    pub file_type: &'static str,
+    /// Why the file on disk was rejected
+    pub error: String,
+    /// Name the broken file was moved to
+    pub quarantined_as: String,
+    /// `None` when no intact copy was found and the file is now missing
+    pub recovered_from: Option<RecoverySource>,
+}
+
+/// Result of reading a data file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFileRead {
+    pub content: Option<String>,
+    /// Revision to pass back to `atomic_write_file`; `None` if there is no file
+    pub revision: Option<String>,
+    /// Set when the file was corrupt and had to be recovered
+    pub recovery: Option<RecoveryReport>,
+}
+
+/// Why `content` can't be loaded, or `None` if it is usable.
+/// Invariant violations (e.g. duplicate IDs) don't count as corruption: the
+/// frontend can still load such a file, so it is returned unchanged.
+fn corruption(file_type: DataFileType, content: &[u8]) -> Option<String> {
+    let Ok(content) = std::str::from_utf8(content) else {
+        return Some("File is not valid UTF-8".to_string());
+    };
+    match file_type.validate_content(content) {
+        Err(e @ WriteError::Parse { .. }) => Some(e.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether the data file on disk exists but can't be loaded
+fn is_corrupt(data_dir: &Path, file_type: DataFileType) -> bool {
+    fs::read(data_dir.join(file_type.filename()))
+        .map(|bytes| corruption(file_type, &bytes).is_some())
+        .unwrap_or(false)
+}
+
+/// Whether `content` is a fully valid copy to recover from
+fn is_intact(file_type: DataFileType, content: &str) -> bool {
+    file_type.validate_content(content).is_ok()
+}
+
+/// Move a broken file aside as `<stem>.corrupt-<timestamp>.json`
+fn quarantine(data_dir: &Path, file_type: DataFileType) -> Result<String, String> {
+    let filename = file_type.filename();
+    let stem = filename.strip_suffix(".json").unwrap_or(filename);
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+
+    let mut name = format!("{}.corrupt-{}.json", stem, timestamp);
+    let mut attempt = 1;
+    while data_dir.join(&name).exists() {
+        name = format!("{}.corrupt-{}-{}.json", stem, timestamp, attempt);
+        attempt += 1;
+    }
+
+    fs::rename(data_dir.join(filename), data_dir.join(&name))
+        .map_err(|e| format!("Failed to quarantine {}: {}", filename, e))?;
+    Ok(name)
+}
+
+/// Find the newest intact copy of a data file
+fn find_intact_copy(data_dir: &Path, file_type: DataFileType) -> Option<(String, RecoverySource)> {
+    for temp_path in durable::temp_files_for(data_dir, file_type.filename()) {
+        let Ok(content) = fs::read_to_string(&temp_path) else {
+            continue;
+        };
+        if is_intact(file_type, &content) {
+            let file_name = temp_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            return Some((content, RecoverySource::TempFile { file_name }));
+        }
+    }
+
+    for info in backup::list_backups(data_dir).ok()? {
+        let Ok(contents) = backup::read_backup(data_dir, &info.id) else {
+            continue;
+        };
+        if !contents.checksum_mismatches().is_empty() {
+            continue;
+        }
+        let Some(content) = contents
+            .files
+            .get(file_type.filename())
+            .and_then(|bytes| String::from_utf8(bytes.clone()).ok())
+        else {
+            continue;
+        };
+        if is_intact(file_type, &content) {
+            return Some((content, RecoverySource::Backup { backup_id: info.id }));
+        }
+    }
+
+    None
+}
+
+/// Read a data file as it is on disk. The inner `Err` holds the reason the
+/// file is corrupt.
+fn read_if_loadable(
+    data_dir: &Path,
+    file_type: DataFileType,
+) -> Result<Result<DataFileRead, String>, String> {
+    let file_path = data_dir.join(file_type.filename());
+
+    if !file_path.exists() {
+        return Ok(Ok(DataFileRead {
+            content: None,
+            revision: None,
+            recovery: None,
+        }));
+    }
+
+    let bytes = fs::read(&file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    if let Some(error) = corruption(file_type, &bytes) {
+        return Ok(Err(error));
+    }
+
+    Ok(Ok(DataFileRead {
+        revision: Some(durable::revision(&bytes)),
+        // Checked to be UTF-8 above
+        content: Some(String::from_utf8_lossy(&bytes).into_owned()),
+        recovery: None,
+    }))
+}
+
+/// Read a data file, recovering it first if it is corrupt
+pub fn read_data_file(data_dir: &Path, file_type: DataFileType) -> Result<DataFileRead, String> {
+    if !data_dir.exists() {
+        return Ok(DataFileRead {
+            content: None,
+            revision: None,
+            recovery: None,
+        });
+    }
+
+    // Held from classifying the file through recovery, so a write can't land
+    // between deciding the file is corrupt and replacing it
+    let _lock = lock::acquire(data_dir)?;
+    let error = match read_if_loadable(data_dir, file_type)? {
+        Ok(read) => return Ok(read),
+        Err(error) => error,
+    };
+
+    eprintln!("{} is corrupt: {}", file_type.filename(), error);
+    let quarantined_as = quarantine(data_dir, file_type)?;
+
+    let (content, recovered_from) = match find_intact_copy(data_dir, file_type) {
+        Some((content, source)) => {
+            // The copy was on disk before, so an overflow it already had
+            // doesn't block recovery; demote mode still applies
+            let written =
+                commands::write_data_file_over(data_dir, file_type, &content, Some(&content))?;
+            println!("Recovered {} from {:?}", file_type.filename(), source);
+            (Some(written.content), Some(source))
+        }
+        None => {
+            eprintln!("No intact copy of {} found", file_type.filename());
+            (None, None)
+        }
+    };
+
+    Ok(DataFileRead {
+        revision: content.as_deref().map(|c| durable::revision(c.as_bytes())),
+        content,
+        recovery: Some(RecoveryReport {
+            file_type: file_type.name(),
+            error,
+            quarantined_as,
+            recovered_from,
+        }),
+    })
+}
+
+/// Remove temp files left behind by interrupted writes. Those belonging to a
+/// corrupt data file are kept, as recovery may still need them.
+pub fn sweep_stale_temp_files(data_dir: &Path) -> Vec<String> {
+    durable::remove_stale_temp_files(data_dir, durable::STALE_TEMP_AGE, |target| {
+        !DataFileType::ALL
+            .iter()
+            .any(|t| t.filename() == target && is_corrupt(data_dir, *t))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupFormat;
+    use serde_json::json;
+
+    fn active(task_id: &str) -> String {
+        json!({ "tasks": [{ "id": task_id, "content": task_id, "priority": "C" }] }).to_string()
+    }
+
+    fn write(data_dir: &Path, name: &str, content: &str) {
+        fs::write(data_dir.join(name), content).unwrap();
+    }
+
+    const TEMP_NAME: &str = "active.json.0123456789abcdef0123456789abcdef.tmp";
+
+    #[test]
+    fn an_intact_file_is_read_as_is() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "active.json", &active("t1"));
+        let read = read_data_file(dir.path(), DataFileType::Active).unwrap();
+        assert_eq!(read.content, Some(active("t1")));
+        assert!(read.recovery.is_none());
+
+        let missing = read_data_file(dir.path(), DataFileType::Archive).unwrap();
+        assert!(missing.content.is_none());
+        assert!(!dir.path().join(lock::LOCK_FILENAME).exists());
+    }
+
+    #[test]
+    fn recovers_from_a_leftover_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "active.json", "{\"tasks\": [");
+        write(dir.path(), TEMP_NAME, &active("from-temp"));
+
+        let read = read_data_file(dir.path(), DataFileType::Active).unwrap();
+        assert_eq!(read.content, Some(active("from-temp")));
+        let report = read.recovery.unwrap();
+        assert!(matches!(
+            report.recovered_from,
+            Some(RecoverySource::TempFile { file_name }) if file_name == TEMP_NAME
+        ));
+        assert_eq!(
+            fs::read_to_string(dir.path().join(&report.quarantined_as)).unwrap(),
+            "{\"tasks\": ["
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("active.json")).unwrap(),
+            active("from-temp")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_newest_intact_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "active.json", &active("backed-up"));
+        let (backup_path, _) = backup::create_backup(dir.path(), BackupFormat::Directory).unwrap();
+        let backup_id = backup_path.file_name().unwrap().to_string_lossy().to_string();
+
+        write(dir.path(), "active.json", "not json");
+        // A temp file that is itself broken is passed over
+        write(dir.path(), TEMP_NAME, "{");
+
+        let read = read_data_file(dir.path(), DataFileType::Active).unwrap();
+        assert_eq!(read.content, Some(active("backed-up")));
+        assert!(matches!(
+            read.recovery.unwrap().recovered_from,
+            Some(RecoverySource::Backup { backup_id: id }) if id == backup_id
+        ));
+    }
+
+    #[test]
+    fn reports_when_nothing_is_left_to_recover_from() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "pomodoro_history.json", "\u{0}\u{0}");
+
+        let read = read_data_file(dir.path(), DataFileType::PomodoroHistory).unwrap();
+        assert!(read.content.is_none());
+        let report = read.recovery.unwrap();
+        assert!(report.recovered_from.is_none());
+        assert!(dir.path().join(report.quarantined_as).exists());
+        assert!(!dir.path().join("pomodoro_history.json").exists());
+    }
+}
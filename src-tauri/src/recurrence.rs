@@ -0,0 +1,731 @@
+//! RFC 5545 recurrence rules for recurring tasks.
+//!
+//! Tasks recur by date, so rules are evaluated on dates and any time of day
+//! in DTSTART, UNTIL or EXDATE is ignored. Supported rule parts are FREQ
+//! (DAILY, WEEKLY, MONTHLY, YEARLY), INTERVAL, BYDAY, BYMONTHDAY, BYSETPOS,
+//! UNTIL, COUNT and WKST, plus DTSTART and EXDATE lines:
+//!
+//! ```text
+//! DTSTART;VALUE=DATE:20260106
+//! RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1
+//! EXDATE;VALUE=DATE:20261231
+//! ```
+//!
+//! So that a rule fits in one line of `active.json` (and in quick-add
+//! `rec:` input), DTSTART and EXDATE may also be given as rule parts:
+//! `FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;EXDATE=20261225,20270101`.
+//!
+//! The short patterns the frontend has always used (`1w`, `mon,wed,fri`,
+//! `1m@15` ...) are migrated to rules by [`rule_of`]. Monthly
+//! rules follow RFC 5545 rather than `Date.setMonth`: "every month" from the
+//! 31st skips months without a 31st instead of rolling into the next one.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{self, DataFileType};
+use crate::lock;
+use crate::model::{Recurrence, RecurrencePattern};
+
+/// Stop looking after this many consecutive periods without an occurrence
+/// (e.g. `FREQ=MONTHLY;BYMONTHDAY=30;BYDAY=MO` only matches a few times a year)
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+/// Most occurrences [`next_occurrences`] returns
+const MAX_OCCURRENCES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A BYDAY entry: a weekday, optionally the nth (or nth from last when
+/// negative) of its month
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: Option<i32>,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_set_pos: Vec<i32>,
+    pub until: Option<NaiveDate>,
+    pub count: Option<u32>,
+    pub week_start: Weekday,
+    /// First occurrence; when missing, the date the rule is evaluated from
+    pub dtstart: Option<NaiveDate>,
+    pub exdates: Vec<NaiveDate>,
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    let date = value.split('T').next().unwrap_or_default();
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .map_err(|_| format!("Invalid date: {}", value))
+}
+
+fn parse_dates(value: &str) -> Result<Vec<NaiveDate>, String> {
+    value.split(',').map(|d| parse_date(d.trim())).collect()
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Invalid weekday: {}", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim_start_matches('+')
+        .parse()
+        .map_err(|_| format!("Invalid {}: {}", name, value))
+}
+
+/// Parse a comma-separated list of non-zero numbers within `-max..=max`
+fn parse_offsets(name: &str, value: &str, max: i32) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            let n: i32 = parse_number(name, v)?;
+            if n == 0 || n.abs() > max {
+                return Err(format!("Invalid {}: {}", name, v));
+            }
+            Ok(n)
+        })
+        .collect()
+}
+
+fn parse_by_day(value: &str) -> Result<ByDay, String> {
+    if !value.is_ascii() {
+        return Err(format!("Invalid BYDAY: {}", value));
+    }
+    let split = value.len().saturating_sub(2);
+    let (nth, weekday) = value.split_at(split);
+    let nth = if nth.is_empty() {
+        None
+    } else {
+        let n: i32 = parse_number("BYDAY", nth)?;
+        if n == 0 || n.abs() > 5 {
+            return Err(format!("Invalid BYDAY: {}", value));
+        }
+        Some(n)
+    };
+    Ok(ByDay {
+        nth,
+        weekday: parse_weekday(weekday)?,
+    })
+}
+
+impl std::str::FromStr for RRule {
+    type Err = String;
+
+    /// Parse a rule: either iCalendar lines (`DTSTART:`, `RRULE:`, `EXDATE:`)
+    /// or a bare `FREQ=...` rule, case-insensitively
+    fn from_str(input: &str) -> Result<Self, String> {
+        let mut parts: Vec<(String, String)> = Vec::new();
+        let mut dtstart = None;
+        let mut exdates = Vec::new();
+
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let line = line.to_ascii_uppercase();
+            // `NAME;PARAMS:VALUE` property lines; a bare rule has no colon
+            let (name, value) = match line.split_once(':') {
+                Some((name, value)) => (
+                    name.split(';').next().unwrap_or_default().to_string(),
+                    value,
+                ),
+                None => ("RRULE".to_string(), line.as_str()),
+            };
+            match name.as_str() {
+                "DTSTART" => dtstart = Some(parse_date(value)?),
+                "EXDATE" => exdates.extend(parse_dates(value)?),
+                "RRULE" => {
+                    for part in value.split(';').filter(|p| !p.is_empty()) {
+                        let (key, val) = part
+                            .split_once('=')
+                            .ok_or_else(|| format!("Invalid rule part: {}", part))?;
+                        parts.push((key.to_string(), val.to_string()));
+                    }
+                }
+                _ => return Err(format!("Unsupported property: {}", name)),
+            }
+        }
+
+        let mut freq = None;
+        let mut rule = RRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_set_pos: Vec::new(),
+            until: None,
+            count: None,
+            week_start: Weekday::Mon,
+            dtstart,
+            exdates,
+        };
+
+        for (key, value) in &parts {
+            match key.as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported FREQ: {}", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = parse_number("INTERVAL", value)?;
+                    if rule.interval == 0 {
+                        return Err("INTERVAL must be at least 1".to_string());
+                    }
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => rule.by_month_day = parse_offsets("BYMONTHDAY", value, 31)?,
+                "BYSETPOS" => rule.by_set_pos = parse_offsets("BYSETPOS", value, 366)?,
+                "UNTIL" => rule.until = Some(parse_date(value)?),
+                "COUNT" => rule.count = Some(parse_number("COUNT", value)?),
+                "WKST" => rule.week_start = parse_weekday(value)?,
+                "DTSTART" => rule.dtstart = Some(parse_date(value)?),
+                "EXDATE" => rule.exdates.extend(parse_dates(value)?),
+                _ => return Err(format!("Unsupported rule part: {}", key)),
+            }
+        }
+
+        rule.freq = freq.ok_or("Rule has no FREQ")?;
+        rule.check()?;
+        Ok(rule)
+    }
+}
+
+impl RRule {
+    /// Reject combinations RFC 5545 forbids or this engine doesn't evaluate
+    fn check(&self) -> Result<(), String> {
+        if self.count.is_some() && self.until.is_some() {
+            return Err("COUNT and UNTIL can't both be set".to_string());
+        }
+        if self.freq != Frequency::Monthly && self.by_day.iter().any(|d| d.nth.is_some()) {
+            return Err("Numbered BYDAY (e.g. 2TU) needs FREQ=MONTHLY".to_string());
+        }
+        if self.freq == Frequency::Weekly && !self.by_month_day.is_empty() {
+            return Err("BYMONTHDAY can't be used with FREQ=WEEKLY".to_string());
+        }
+        if self.freq == Frequency::Yearly
+            && !(self.by_day.is_empty() && self.by_month_day.is_empty())
+        {
+            return Err("BYDAY and BYMONTHDAY aren't supported with FREQ=YEARLY".to_string());
+        }
+        if !self.by_set_pos.is_empty() && self.by_day.is_empty() && self.by_month_day.is_empty() {
+            return Err("BYSETPOS needs BYDAY or BYMONTHDAY".to_string());
+        }
+        Ok(())
+    }
+
+    /// Occurrences in date order, starting at DTSTART or else `start`
+    pub fn occurrences(&self, start: NaiveDate) -> Occurrences<'_> {
+        let anchor = self.dtstart.unwrap_or(start);
+        Occurrences {
+            rule: self,
+            anchor,
+            period: Some(self.period_start(anchor)),
+            pending: VecDeque::new(),
+            generated: 0,
+            done: false,
+        }
+    }
+
+    fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self.freq {
+            Frequency::Daily => date,
+            Frequency::Weekly => {
+                let offset = (7 + date.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                date - Duration::days(offset as i64)
+            }
+            Frequency::Monthly => date.with_day(1).unwrap_or(date),
+            Frequency::Yearly => date.with_ordinal(1).unwrap_or(date),
+        }
+    }
+
+    fn next_period(&self, period: NaiveDate) -> Option<NaiveDate> {
+        let n = self.interval;
+        match self.freq {
+            Frequency::Daily => period.checked_add_signed(Duration::try_days(n as i64)?),
+            Frequency::Weekly => period.checked_add_signed(Duration::try_weeks(n as i64)?),
+            Frequency::Monthly => period.checked_add_months(Months::new(n)),
+            Frequency::Yearly => period.checked_add_months(Months::new(n.checked_mul(12)?)),
+        }
+    }
+
+    fn matches_by_day(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday())
+    }
+
+    fn matches_by_month_day(&self, date: NaiveDate) -> bool {
+        self.by_month_day.is_empty() || {
+            let len = days_in_month(date) as i32;
+            let day = date.day() as i32;
+            self.by_month_day
+                .iter()
+                .any(|&d| d == day || len + 1 + d == day)
+        }
+    }
+
+    /// Candidate dates in the period starting at `period`, in order and
+    /// before BYSETPOS
+    fn expand(&self, period: NaiveDate, anchor: NaiveDate) -> Vec<NaiveDate> {
+        match self.freq {
+            Frequency::Daily => {
+                if self.matches_by_day(period) && self.matches_by_month_day(period) {
+                    vec![period]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => period
+                .iter_days()
+                .take(7)
+                .filter(|d| {
+                    if self.by_day.is_empty() {
+                        d.weekday() == anchor.weekday()
+                    } else {
+                        self.matches_by_day(*d)
+                    }
+                })
+                .collect(),
+            Frequency::Monthly => {
+                let month: Vec<NaiveDate> = period
+                    .iter_days()
+                    .take(days_in_month(period) as usize)
+                    .collect();
+                if self.by_day.is_empty() && self.by_month_day.is_empty() {
+                    return month
+                        .into_iter()
+                        .filter(|d| d.day() == anchor.day())
+                        .collect();
+                }
+                month
+                    .iter()
+                    .copied()
+                    .filter(|&d| self.matches_by_month_day(d))
+                    .filter(|&d| {
+                        self.by_day.is_empty()
+                            || self.by_day.iter().any(|b| {
+                                b.weekday == d.weekday() && {
+                                    match b.nth {
+                                        None => true,
+                                        // nth weekday of the month, or nth from the end
+                                        Some(n) if n > 0 => (d.day() as i32 - 1) / 7 + 1 == n,
+                                        Some(n) => {
+                                            (days_in_month(d) as i32 - d.day() as i32) / 7 + 1 == -n
+                                        }
+                                    }
+                                }
+                            })
+                    })
+                    .collect()
+            }
+            Frequency::Yearly => {
+                NaiveDate::from_ymd_opt(period.year(), anchor.month(), anchor.day())
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+
+    fn apply_set_pos(&self, candidates: Vec<NaiveDate>) -> Vec<NaiveDate> {
+        if self.by_set_pos.is_empty() {
+            return candidates;
+        }
+        let len = candidates.len() as i32;
+        let mut picked: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|&pos| {
+                let index = if pos > 0 { pos - 1 } else { len + pos };
+                (0..len)
+                    .contains(&index)
+                    .then(|| candidates[index as usize])
+            })
+            .collect();
+        picked.sort_unstable();
+        picked.dedup();
+        picked
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap_or(date);
+    first
+        .checked_add_months(Months::new(1))
+        .map(|next| (next - first).num_days() as u32)
+        .unwrap_or(31)
+}
+
+/// Iterator over the dates of a rule, with EXDATEs left out
+pub struct Occurrences<'a> {
+    rule: &'a RRule,
+    anchor: NaiveDate,
+    period: Option<NaiveDate>,
+    pending: VecDeque<NaiveDate>,
+    /// Occurrences produced so far, including EXDATEs (which COUNT includes)
+    generated: u32,
+    done: bool,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDate;
+
+    fn next(&mut self) -> Option<NaiveDate> {
+        let mut empty_periods = 0;
+        while !self.done {
+            let Some(date) = self.pending.pop_front() else {
+                let Some(period) = self.period else {
+                    self.done = true;
+                    break;
+                };
+                let candidates = self.rule.expand(period, self.anchor);
+                self.pending = self
+                    .rule
+                    .apply_set_pos(candidates)
+                    .into_iter()
+                    .filter(|d| *d >= self.anchor)
+                    .collect();
+                self.period = self.rule.next_period(period);
+                if self.pending.is_empty() {
+                    empty_periods += 1;
+                    self.done = empty_periods > MAX_EMPTY_PERIODS;
+                }
+                continue;
+            };
+
+            if self.rule.until.is_some_and(|until| date > until)
+                || self.rule.count.is_some_and(|count| self.generated >= count)
+            {
+                self.done = true;
+                break;
+            }
+            self.generated += 1;
+            if !self.rule.exdates.contains(&date) {
+                return Some(date);
+            }
+        }
+        None
+    }
+}
+
+/// The first `n` occurrences of `rule` after `from` (at most
+/// `MAX_OCCURRENCES`). Without a DTSTART the rule is anchored at `from`
+/// (e.g. a task's current due date). `rule` may also be one of the short
+/// patterns (`1w`, `mon,wed,fri`, `1m@15`).
+pub fn next_occurrences(rule: &str, from: NaiveDate, n: usize) -> Result<Vec<NaiveDate>, String> {
+    let rule: RRule = match short_pattern_rule(rule) {
+        Some(migrated) => migrated.parse()?,
+        None => rule.parse()?,
+    };
+    Ok(rule
+        .occurrences(from)
+        .filter(|d| *d > from)
+        .take(n.min(MAX_OCCURRENCES))
+        .collect())
+}
+
+/// Rule for one of the fixed `pattern`s
+pub fn pattern_rule(pattern: RecurrencePattern) -> &'static str {
+    match pattern {
+        RecurrencePattern::OneDay => "FREQ=DAILY",
+        RecurrencePattern::TwoDays => "FREQ=DAILY;INTERVAL=2",
+        RecurrencePattern::ThreeDays => "FREQ=DAILY;INTERVAL=3",
+        RecurrencePattern::OneWeek => "FREQ=WEEKLY",
+        RecurrencePattern::TwoWeeks => "FREQ=WEEKLY;INTERVAL=2",
+        RecurrencePattern::OneMonth => "FREQ=MONTHLY",
+        RecurrencePattern::ThreeMonths => "FREQ=MONTHLY;INTERVAL=3",
+    }
+}
+
+/// Rule for a `customPattern` (`mon,wed,fri`, `1m@15`, `3m@last`) or a
+/// fixed pattern name (`2w`). `None` if `input` isn't a short pattern.
+fn short_pattern_rule(input: &str) -> Option<String> {
+    let input = input.trim().to_ascii_lowercase();
+
+    if let Ok(pattern) = serde_json::from_value(Value::String(input.clone())) {
+        return Some(pattern_rule(pattern).to_string());
+    }
+
+    if let Some((months, day)) = input.split_once("m@") {
+        let interval: u32 = months.parse().ok().filter(|&n| n > 0)?;
+        let day = match day {
+            "last" => -1,
+            day => day.parse().ok().filter(|d| (1..=31).contains(d))?,
+        };
+        let interval = if interval == 1 {
+            String::new()
+        } else {
+            format!(";INTERVAL={}", interval)
+        };
+        return Some(format!("FREQ=MONTHLY{};BYMONTHDAY={}", interval, day));
+    }
+
+    let days = input
+        .split(',')
+        .map(|day| match day {
+            "mon" => Some("MO"),
+            "tue" => Some("TU"),
+            "wed" => Some("WE"),
+            "thu" => Some("TH"),
+            "fri" => Some("FR"),
+            "sat" => Some("SA"),
+            "sun" => Some("SU"),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("FREQ=WEEKLY;BYDAY={}", days.join(",")))
+}
+
+/// The rule a task recurs by: its `rrule`, or its short pattern migrated to
+/// one. A `customPattern` wins over `pattern`, which the frontend sets to a
+/// rough approximation of it (`2m@15` has pattern `3m`).
+pub fn rule_of(recurrence: &Recurrence) -> Option<String> {
+    if let Some(rule) = &recurrence.rrule {
+        return Some(rule.clone());
+    }
+    if let Some(custom) = &recurrence.custom_pattern {
+        if custom.trim().to_ascii_uppercase().starts_with("FREQ=") {
+            return Some(custom.trim().to_ascii_uppercase());
+        }
+        if let Some(rule) = short_pattern_rule(custom) {
+            return Some(rule);
+        }
+    }
+    recurrence.pattern.map(|p| pattern_rule(p).to_string())
+}
+
+/// Give every recurring task in `active.json` an `rrule` migrated from its
+/// short pattern. The short pattern is kept for older frontends. Returns the
+/// number of tasks migrated.
+pub fn migrate_active(data_dir: &Path) -> Result<usize, String> {
+    let path = data_dir.join(DataFileType::Active.filename());
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let _lock = lock::acquire(data_dir)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let mut active: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let mut migrated = 0;
+    if let Some(tasks) = active.get_mut("tasks").and_then(Value::as_array_mut) {
+        for task in tasks {
+            let Some(obj) = task.get_mut("recurrence").and_then(Value::as_object_mut) else {
+                continue;
+            };
+            if obj.contains_key("rrule") {
+                continue;
+            }
+            let Ok(recurrence) = serde_json::from_value::<Recurrence>(Value::Object(obj.clone()))
+            else {
+                continue;
+            };
+            if let Some(rule) = rule_of(&recurrence) {
+                obj.insert("rrule".to_string(), Value::String(rule));
+                migrated += 1;
+            }
+        }
+    }
+
+    if migrated > 0 {
+        let content = serde_json::to_string_pretty(&active)
+            .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+        commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Map;
+
+    fn date(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    fn next(rule: &str, from: &str, n: usize) -> Vec<String> {
+        next_occurrences(rule, date(from), n)
+            .unwrap()
+            .iter()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn second_tuesday_of_the_month() {
+        assert_eq!(
+            next("FREQ=MONTHLY;BYDAY=2TU", "2026-01-01", 3),
+            ["2026-01-13", "2026-02-10", "2026-03-10"]
+        );
+    }
+
+    #[test]
+    fn last_weekday_of_the_month() {
+        assert_eq!(
+            next(
+                "RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+                "2026-01-01",
+                3
+            ),
+            ["2026-01-30", "2026-02-27", "2026-03-31"]
+        );
+    }
+
+    #[test]
+    fn weekdays_except_holidays() {
+        let rule = "DTSTART;VALUE=DATE:20261221\n\
+                    RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\n\
+                    EXDATE;VALUE=DATE:20261225,20270101";
+        assert_eq!(
+            next(rule, "2026-12-23", 5),
+            [
+                "2026-12-24",
+                "2026-12-28",
+                "2026-12-29",
+                "2026-12-30",
+                "2026-12-31"
+            ]
+        );
+        assert_eq!(
+            next(
+                "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;EXDATE=20261225",
+                "2026-12-23",
+                2
+            ),
+            ["2026-12-24", "2026-12-28"]
+        );
+    }
+
+    #[test]
+    fn interval_count_and_until() {
+        let rule = "DTSTART:20260105\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;COUNT=3";
+        assert_eq!(
+            next(rule, "2026-01-01", 5),
+            ["2026-01-05", "2026-01-19", "2026-02-02"]
+        );
+        // COUNT includes occurrences before `from`
+        assert_eq!(next(rule, "2026-01-10", 5), ["2026-01-19", "2026-02-02"]);
+        assert_eq!(
+            next("FREQ=DAILY;INTERVAL=3;UNTIL=20260110", "2026-01-01", 5),
+            ["2026-01-04", "2026-01-07", "2026-01-10"]
+        );
+    }
+
+    #[test]
+    fn month_days_count_from_the_end_and_skip_short_months() {
+        assert_eq!(
+            next("FREQ=MONTHLY;BYMONTHDAY=-1", "2026-01-31", 2),
+            ["2026-02-28", "2026-03-31"]
+        );
+        assert_eq!(
+            next("DTSTART:20260131\nRRULE:FREQ=MONTHLY", "2026-01-31", 2),
+            ["2026-03-31", "2026-05-31"]
+        );
+    }
+
+    #[test]
+    fn short_patterns_migrate_to_rules() {
+        assert_eq!(next("2w", "2026-01-01", 2), ["2026-01-15", "2026-01-29"]);
+        assert_eq!(
+            next("mon,fri", "2026-01-01", 2),
+            ["2026-01-02", "2026-01-05"]
+        );
+        assert_eq!(
+            next("3m@last", "2026-01-15", 2),
+            ["2026-01-31", "2026-04-30"]
+        );
+
+        let recurrence = |pattern: &str, custom: Option<&str>| Recurrence {
+            pattern: serde_json::from_value(Value::String(pattern.to_string())).ok(),
+            custom_pattern: custom.map(String::from),
+            rrule: None,
+            series_id: None,
+            next_due: None,
+            extra: Map::new(),
+        };
+        assert_eq!(
+            rule_of(&recurrence("1m", None)).as_deref(),
+            Some("FREQ=MONTHLY")
+        );
+        assert_eq!(
+            rule_of(&recurrence("3m", Some("2m@15"))).as_deref(),
+            Some("FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=15")
+        );
+        assert_eq!(
+            rule_of(&recurrence("", Some("mon,wed,fri"))).as_deref(),
+            Some("FREQ=WEEKLY;BYDAY=MO,WE,FR")
+        );
+    }
+
+    #[test]
+    fn completing_each_instance_follows_the_pinned_rule() {
+        // The frontend completes a task by asking for the next occurrence
+        // after its due date, with DTSTART pinned to the first instance
+        let unpinned = "FREQ=WEEKLY;COUNT=3";
+        // Unpinned, COUNT restarts at every due date: a fourth instance
+        assert_eq!(next(unpinned, "2026-03-16", 1), ["2026-03-23"]);
+
+        let pinned = format!("DTSTART:20260302\n{}", unpinned);
+        let mut due = "2026-03-02".to_string();
+        let mut dues = Vec::new();
+        while let Some(next_due) = next(&pinned, &due, 1).pop() {
+            dues.push(next_due.clone());
+            due = next_due;
+        }
+        assert_eq!(dues, ["2026-03-09", "2026-03-16"]);
+
+        // `1m@15` is stored with pattern `1m`; the custom pattern keeps the day
+        assert_eq!(next("1m@15", "2026-01-15", 1), ["2026-02-15"]);
+        assert_eq!(next("3m@last", "2026-01-31", 1), ["2026-04-30"]);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for rule in [
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=WEEKLY;BYDAY=2TU",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=DAILY;COUNT=2;UNTIL=20260101",
+            "FREQ=DAILY;BYSETPOS=1",
+            "FREQ=DAILY;BYMONTH=1",
+        ] {
+            assert!(rule.parse::<RRule>().is_err(), "{}", rule);
+        }
+    }
+}
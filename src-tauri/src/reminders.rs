@@ -0,0 +1,746 @@
+//! Due-date reminders, sent by the backend so they fire on time however long
+//! the app has been open.
+//!
+//! Four kinds, all off while `dueReminders` is:
+//! - per task, at each of `reminderOffsets` (minutes) before it is due;
+//! - per task, at its own `remindAt`;
+//! - a morning digest at `digestTime` of the tasks due today or overdue;
+//! - an overdue nudge at `overdueNudgeTime` if any task is past due.
+//!
+//! A reminder is only sent on the day it falls due: one missed while the app
+//! was closed goes out when it next runs that day, or not at all. Which
+//! reminders went out today is kept in `reminders_sent.json` so a restart
+//! doesn't repeat them.
+//!
+//! Task reminders can be snoozed or the task completed from the notification
+//! where the platform reports which button was pressed (Linux), and from the
+//! reminder card the app shows on every platform. Snoozing sets the task's
+//! `remindAt` and completing moves it to G, both in `active.json`, so they
+//! take effect even with no window open to hear about them.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::commands::{self, DataFileType};
+use crate::model::{ActiveData, Priority, Settings, Task};
+use crate::{durable, lock};
+
+/// File recording the reminders sent today, next to the data files
+pub const SENT_FILENAME: &str = "reminders_sent.json";
+
+/// A due date without a time is due at the end of the working day
+const END_OF_DAY_HOUR: u32 = 18;
+
+/// "Tomorrow" snoozes to the digest time, or this hour with the digest off
+const MORNING_HOUR: u32 = 9;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    /// Identifies the reminder for dedupe; changes when the task's due date does
+    pub key: String,
+    /// The task reminded of; `None` for the digest and the nudge
+    pub task_id: Option<String>,
+    pub title: String,
+    pub body: String,
+}
+
+/// How long to put a task reminder off for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Snooze {
+    TenMinutes,
+    OneHour,
+    Tomorrow,
+}
+
+impl Snooze {
+    /// When a reminder snoozed at `now` comes back
+    pub fn until(self, now: NaiveDateTime, settings: &Settings) -> NaiveDateTime {
+        match self {
+            Snooze::TenMinutes => now + Duration::minutes(10),
+            Snooze::OneHour => now + Duration::hours(1),
+            Snooze::Tomorrow => {
+                let morning = settings
+                    .digest_time
+                    .as_deref()
+                    .and_then(parse_time)
+                    .or_else(|| NaiveTime::from_hms_opt(MORNING_HOUR, 0, 0))
+                    .unwrap_or_default();
+                (now.date() + Duration::days(1)).and_time(morning)
+            }
+        }
+    }
+}
+
+/// A task reminder for the app's reminder card
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskReminder {
+    task_id: String,
+    title: String,
+    body: String,
+}
+
+/// Reminders already sent on `date`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Sent {
+    pub date: Option<NaiveDate>,
+    pub keys: BTreeSet<String>,
+}
+
+impl Sent {
+    /// The record for `today`, forgetting earlier days
+    fn on(self, today: NaiveDate) -> Self {
+        if self.date == Some(today) {
+            self
+        } else {
+            Self {
+                date: Some(today),
+                keys: BTreeSet::new(),
+            }
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+fn is_open(task: &Task) -> bool {
+    !task.completed
+        && matches!(
+            task.priority,
+            Priority::A | Priority::B | Priority::C | Priority::D | Priority::E | Priority::F
+        )
+}
+
+/// When a task is due: at its `dueTime`, or the end of the working day
+fn due_at(task: &Task) -> Option<NaiveDateTime> {
+    let date = task.due_date.as_deref()?.get(..10)?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    match task.due_time.as_deref().and_then(parse_time) {
+        Some(time) => Some(date.and_time(time)),
+        None => date.and_hms_opt(END_OF_DAY_HOUR, 0, 0),
+    }
+}
+
+/// A task's `remindAt` in local time
+fn remind_at(task: &Task) -> Option<NaiveDateTime> {
+    let at = DateTime::parse_from_rfc3339(task.remind_at.as_deref()?).ok()?;
+    Some(at.with_timezone(&Local).naive_local())
+}
+
+/// Notification text in the app's language
+struct Messages {
+    zh: bool,
+}
+
+impl Messages {
+    fn new(language: &str) -> Self {
+        Self {
+            zh: language.starts_with("zh"),
+        }
+    }
+
+    fn lead_time(&self, minutes: u32) -> String {
+        let (n, en, zh) = if minutes > 0 && minutes.is_multiple_of(1440) {
+            (minutes / 1440, "day", "天")
+        } else if minutes > 0 && minutes.is_multiple_of(60) {
+            (minutes / 60, "h", "小时")
+        } else {
+            (minutes, "min", "分钟")
+        };
+        match (self.zh, en) {
+            (true, _) => format!("{} {}", n, zh),
+            (false, "day") if n > 1 => format!("{} days", n),
+            (false, _) => format!("{} {}", n, en),
+        }
+    }
+
+    fn due_soon(&self, minutes: u32) -> String {
+        if self.zh {
+            format!("{}后到期", self.lead_time(minutes))
+        } else {
+            format!("Due in {}", self.lead_time(minutes))
+        }
+    }
+
+    fn reminder(&self) -> String {
+        if self.zh { "提醒" } else { "Reminder" }.to_string()
+    }
+
+    fn digest(&self, count: usize) -> (String, String) {
+        if self.zh {
+            (
+                "今天有任务到期".to_string(),
+                format!("你有 {} 个任务今天到期或已逾期", count),
+            )
+        } else {
+            (
+                "Tasks due today".to_string(),
+                format!("You have {} tasks due today or overdue", count),
+            )
+        }
+    }
+
+    fn overdue(&self, count: usize) -> (String, String) {
+        if self.zh {
+            (
+                "任务已逾期".to_string(),
+                format!("有 {} 个任务已过截止时间", count),
+            )
+        } else {
+            (
+                "Overdue tasks".to_string(),
+                format!("{} tasks are past due", count),
+            )
+        }
+    }
+}
+
+/// Reminders due at `now` that haven't been sent yet
+pub fn pending(
+    tasks: &[Task],
+    settings: &Settings,
+    now: NaiveDateTime,
+    sent: &Sent,
+) -> Vec<Reminder> {
+    if !settings.due_reminders {
+        return Vec::new();
+    }
+    let messages = Messages::new(&settings.language);
+    let today = now.date();
+    let open: Vec<(&Task, NaiveDateTime)> = tasks
+        .iter()
+        .filter(|task| is_open(task))
+        .filter_map(|task| due_at(task).map(|due| (task, due)))
+        .collect();
+
+    let mut reminders = Vec::new();
+    for (task, due) in &open {
+        for &offset in &settings.reminder_offsets {
+            let at = *due - Duration::minutes(i64::from(offset));
+            if at.date() == today && at <= now && now < *due {
+                reminders.push(Reminder {
+                    key: format!(
+                        "task:{}:{}:{}",
+                        task.id,
+                        offset,
+                        due.format("%Y-%m-%dT%H:%M")
+                    ),
+                    task_id: Some(task.id.clone()),
+                    title: messages.due_soon(offset),
+                    body: task.content.clone(),
+                });
+            }
+        }
+    }
+    for task in tasks.iter().filter(|task| is_open(task)) {
+        if let Some(at) = remind_at(task).filter(|at| at.date() == today && *at <= now) {
+            reminders.push(Reminder {
+                key: format!("remind:{}:{}", task.id, at.format("%Y-%m-%dT%H:%M")),
+                task_id: Some(task.id.clone()),
+                title: messages.reminder(),
+                body: task.content.clone(),
+            });
+        }
+    }
+
+    let at = |time: &Option<String>| {
+        time.as_deref()
+            .and_then(parse_time)
+            .is_some_and(|time| now.time() >= time)
+    };
+    if at(&settings.digest_time) {
+        let count = open.iter().filter(|(_, due)| due.date() <= today).count();
+        if count > 0 {
+            let (title, body) = messages.digest(count);
+            reminders.push(Reminder {
+                key: "digest".to_string(),
+                task_id: None,
+                title,
+                body,
+            });
+        }
+    }
+    if at(&settings.overdue_nudge_time) {
+        let count = open.iter().filter(|(_, due)| *due <= now).count();
+        if count > 0 {
+            let (title, body) = messages.overdue(count);
+            reminders.push(Reminder {
+                key: "overdue".to_string(),
+                task_id: None,
+                title,
+                body,
+            });
+        }
+    }
+
+    reminders.retain(|r| !sent.keys.contains(&r.key));
+    reminders
+}
+
+fn load_sent(data_dir: &Path) -> Sent {
+    fs::read_to_string(data_dir.join(SENT_FILENAME))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_sent(data_dir: &Path, sent: &Sent) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(sent)
+        .map_err(|e| format!("Failed to serialize sent reminders: {}", e))?;
+    durable::write_atomic(&data_dir.join(SENT_FILENAME), content.as_bytes())
+}
+
+/// Snooze a task's reminder by setting its `remindAt` in active.json
+///
+/// Returns the new `remindAt`.
+pub fn snooze(
+    data_dir: &Path,
+    task_id: &str,
+    snooze: Snooze,
+    now: DateTime<Local>,
+) -> Result<String, String> {
+    let _lock = lock::acquire(data_dir)?;
+
+    let path = data_dir.join(DataFileType::Active.filename());
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let mut data: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+    let settings: Settings = serde_json::from_value(data["settings"].clone()).unwrap_or_default();
+
+    let until = snooze.until(now.naive_local(), &settings);
+    let until = Local
+        .from_local_datetime(&until)
+        .earliest()
+        .ok_or_else(|| format!("Failed to snooze: {} doesn't exist locally", until))?
+        .with_timezone(&Utc)
+        .to_rfc3339();
+
+    let task = data["tasks"]
+        .as_array_mut()
+        .and_then(|tasks| tasks.iter_mut().find(|task| task["id"] == task_id))
+        .ok_or_else(|| format!("Failed to snooze: no task {}", task_id))?;
+    task["remindAt"] = Value::String(until.clone());
+    task["lastModified"] = Value::String(now.with_timezone(&Utc).to_rfc3339());
+
+    let content = serde_json::to_string_pretty(&data)
+        .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+    commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+    Ok(until)
+}
+
+/// Complete a task from its reminder as the frontend's `completeTask` does:
+/// it moves to G, keeping its priority in `originalPriority`.
+///
+/// Returns the task as it was, or `None` if it was already done or cancelled.
+pub fn complete(
+    data_dir: &Path,
+    task_id: &str,
+    now: DateTime<Local>,
+) -> Result<Option<Task>, String> {
+    let _lock = lock::acquire(data_dir)?;
+
+    let path = data_dir.join(DataFileType::Active.filename());
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let mut data: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let task = data["tasks"]
+        .as_array_mut()
+        .and_then(|tasks| tasks.iter_mut().find(|task| task["id"] == task_id))
+        .ok_or_else(|| format!("Failed to complete: no task {}", task_id))?;
+    let before: Task = serde_json::from_value(task.clone())
+        .map_err(|e| format!("Failed to parse task {}: {}", task_id, e))?;
+    if matches!(before.priority, Priority::G | Priority::H) {
+        return Ok(None);
+    }
+    let now = now.with_timezone(&Utc).to_rfc3339();
+    task["originalPriority"] = task["priority"].clone();
+    task["priority"] = Value::String("G".to_string());
+    task["completed"] = Value::Bool(true);
+    task["completedAt"] = Value::String(now.clone());
+    task["lastModified"] = Value::String(now);
+
+    let content = serde_json::to_string_pretty(&data)
+        .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+    commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+    Ok(Some(before))
+}
+
+#[cfg(target_os = "linux")]
+use actions::notify_task;
+
+/// Snooze and complete buttons on the notification itself. Only Linux
+/// notification servers report which button was pressed.
+#[cfg(target_os = "linux")]
+mod actions {
+    use super::*;
+
+    /// Action ids, the same as the frontend's names for them
+    const ACTIONS: [(&str, Option<Snooze>); 4] = [
+        ("tenMinutes", Some(Snooze::TenMinutes)),
+        ("oneHour", Some(Snooze::OneHour)),
+        ("tomorrow", Some(Snooze::Tomorrow)),
+        ("complete", None),
+    ];
+
+    /// How long a notification waits for a button before it is closed; the
+    /// reminder card in the app keeps the buttons after that
+    const ACTION_WAIT: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+    /// Sent to the frontend once a reminder's task is snoozed or completed, so
+    /// its reminder card goes away
+    #[derive(Clone, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ReminderAction {
+        task_id: String,
+        action: String,
+        /// The task as it was before completing it, for the frontend to
+        /// record the completion and add the next occurrence
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task: Option<Task>,
+    }
+
+    fn label(messages: &Messages, snooze: Option<Snooze>) -> &'static str {
+        let (en, zh) = match snooze {
+            Some(Snooze::TenMinutes) => ("10 min", "10 分钟后"),
+            Some(Snooze::OneHour) => ("1 hour", "1 小时后"),
+            Some(Snooze::Tomorrow) => ("Tomorrow", "明天"),
+            None => ("Complete", "完成"),
+        };
+        if messages.zh {
+            zh
+        } else {
+            en
+        }
+    }
+
+    fn act(app_handle: &AppHandle, task_id: &str, action: &str) -> Result<(), String> {
+        let Some(&(_, snooze)) = ACTIONS.iter().find(|(id, _)| *id == action) else {
+            return Ok(());
+        };
+        let data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve data directory: {}", e))?;
+        let task = match snooze {
+            Some(snooze) => {
+                super::snooze(&data_dir, task_id, snooze, Local::now())?;
+                None
+            }
+            None => super::complete(&data_dir, task_id, Local::now())?,
+        };
+        let payload = ReminderAction {
+            task_id: task_id.to_string(),
+            action: action.to_string(),
+            task,
+        };
+        if let Err(e) = app_handle.emit("reminder-action", payload) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Show a task reminder with its buttons, waiting for one on a thread of
+    /// its own for at most `ACTION_WAIT`
+    pub(super) fn notify_task(
+        app_handle: &AppHandle,
+        reminder: &Reminder,
+        task_id: &str,
+        messages: &Messages,
+    ) -> Result<(), String> {
+        let mut notification = notify_rust::Notification::new();
+        notification
+            .appname("FocusFlow")
+            .summary(&reminder.title)
+            .body(&reminder.body);
+        for (id, snooze) in ACTIONS {
+            notification.action(id, label(messages, snooze));
+        }
+        let handle = notification
+            .show()
+            .map_err(|e| format!("Failed to show reminder: {}", e))?;
+
+        // Wait on the notification's id rather than its handle, which is kept
+        // to close it: closing ends the wait on servers that never expire it
+        let id = handle.id();
+        let app_handle = app_handle.clone();
+        let task_id = task_id.to_string();
+        std::thread::spawn(move || {
+            let waited = notify_rust::handle_action(id, |response| {
+                if let notify_rust::ActionResponse::Custom(action) = response {
+                    if let Err(e) = act(&app_handle, &task_id, action) {
+                        eprintln!("Failed to act on reminder: {}", e);
+                    }
+                }
+            });
+            if let Err(e) = waited {
+                eprintln!("Failed to wait for a reminder action: {}", e);
+            }
+        });
+        std::thread::spawn(move || {
+            std::thread::sleep(ACTION_WAIT);
+            handle.close();
+        });
+        Ok(())
+    }
+}
+
+/// Elsewhere the buttons are only on the app's reminder card
+#[cfg(not(target_os = "linux"))]
+fn notify_task(
+    app_handle: &AppHandle,
+    reminder: &Reminder,
+    _task_id: &str,
+    _messages: &Messages,
+) -> Result<(), String> {
+    notify(app_handle, reminder)
+}
+
+fn notify(app_handle: &AppHandle, reminder: &Reminder) -> Result<(), String> {
+    app_handle
+        .notification()
+        .builder()
+        .title(&reminder.title)
+        .body(&reminder.body)
+        .show()
+        .map_err(|e| format!("Failed to show reminder: {}", e))
+}
+
+/// Send the reminders due now
+fn check(app_handle: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join(DataFileType::Active.filename());
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let data: ActiveData = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let now = Local::now().naive_local();
+    let mut sent = load_sent(data_dir).on(now.date());
+    let reminders = pending(&data.tasks, &data.settings, now, &sent);
+    if reminders.is_empty() {
+        return Ok(());
+    }
+
+    let messages = Messages::new(&data.settings.language);
+    for reminder in reminders {
+        let shown = match &reminder.task_id {
+            Some(task_id) => {
+                let card = TaskReminder {
+                    task_id: task_id.clone(),
+                    title: reminder.title.clone(),
+                    body: reminder.body.clone(),
+                };
+                if let Err(e) = app_handle.emit("task-reminder", card) {
+                    eprintln!("Failed to emit event: {}", e);
+                }
+                notify_task(app_handle, &reminder, task_id, &messages)
+            }
+            None => notify(app_handle, &reminder),
+        };
+        match shown {
+            Ok(()) => {
+                sent.keys.insert(reminder.key);
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    save_sent(data_dir, &sent)
+}
+
+/// Check for due reminders every `CHECK_INTERVAL` for as long as the app runs
+pub async fn run(app_handle: AppHandle) {
+    loop {
+        match app_handle.path().app_data_dir() {
+            Ok(data_dir) => {
+                if let Err(e) = check(&app_handle, &data_dir) {
+                    eprintln!("Failed to send reminders: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to resolve data directory: {}", e),
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, due: &str, completed: bool) -> Task {
+        serde_json::from_value(json!({
+            "id": id,
+            "content": format!("Task {}", id),
+            "priority": if completed { "G" } else { "C" },
+            "completed": completed,
+            "dueDate": due
+        }))
+        .unwrap()
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            language: "en-US".to_string(),
+            ..Settings::default()
+        }
+    }
+
+    fn keys(reminders: &[Reminder]) -> Vec<&str> {
+        reminders.iter().map(|r| r.key.as_str()).collect()
+    }
+
+    #[test]
+    fn task_reminders_fire_on_their_day_before_the_task_is_due() {
+        let tasks = [
+            task("t1", "2026-03-04", false),
+            task("t2", "2026-03-04", true),
+        ];
+        let mut settings = settings();
+        settings.digest_time = None;
+        let sent = Sent::default();
+
+        // 1 day before is 18:00 the day before; 1 h before is 17:00 on the day
+        assert!(pending(&tasks, &settings, at("2026-03-03 17:59"), &sent).is_empty());
+        let day_before = pending(&tasks, &settings, at("2026-03-03 18:00"), &sent);
+        assert_eq!(keys(&day_before), ["task:t1:1440:2026-03-04T18:00"]);
+        assert_eq!(day_before[0].title, "Due in 1 day");
+        assert_eq!(day_before[0].body, "Task t1");
+
+        // Missed yesterday's reminder isn't sent today
+        let on_the_day = pending(&tasks, &settings, at("2026-03-04 17:30"), &sent);
+        assert_eq!(keys(&on_the_day), ["task:t1:60:2026-03-04T18:00"]);
+        assert_eq!(on_the_day[0].title, "Due in 1 h");
+    }
+
+    #[test]
+    fn digest_and_nudge_go_out_once_a_day() {
+        let tasks = [
+            task("t1", "2026-03-04", false),
+            task("t2", "2026-03-02", false),
+        ];
+        let mut settings = settings();
+        settings.reminder_offsets.clear();
+
+        assert!(pending(&tasks, &settings, at("2026-03-04 08:00"), &Sent::default()).is_empty());
+        let morning = pending(&tasks, &settings, at("2026-03-04 09:00"), &Sent::default());
+        assert_eq!(keys(&morning), ["digest"]);
+        assert_eq!(morning[0].body, "You have 2 tasks due today or overdue");
+
+        let mut sent = Sent::default().on(at("2026-03-04 09:00").date());
+        sent.keys.insert("digest".to_string());
+        let evening = pending(&tasks, &settings, at("2026-03-04 19:00"), &sent);
+        assert_eq!(keys(&evening), ["overdue"]);
+        assert_eq!(evening[0].body, "2 tasks are past due");
+
+        // A new day starts with nothing sent
+        let tomorrow = sent.on(at("2026-03-05 09:00").date());
+        assert!(tomorrow.keys.is_empty());
+    }
+
+    #[test]
+    fn nothing_is_sent_with_reminders_off() {
+        let tasks = [task("t1", "2026-03-04", false)];
+        let mut settings = settings();
+        settings.due_reminders = false;
+        assert!(pending(&tasks, &settings, at("2026-03-04 20:00"), &Sent::default()).is_empty());
+    }
+
+    #[test]
+    fn due_times_and_remind_at_set_when_tasks_remind() {
+        let mut timed = task("t1", "2026-03-04", false);
+        timed.due_time = Some("10:00".to_string());
+        let mut reminded = task("t2", "2026-03-10", false);
+        let remind_at = Local.from_local_datetime(&at("2026-03-04 14:30")).unwrap();
+        reminded.remind_at = Some(remind_at.to_rfc3339());
+        let tasks = [timed, reminded];
+        let mut settings = settings();
+        settings.digest_time = None;
+        settings.overdue_nudge_time = None;
+
+        let morning = pending(&tasks, &settings, at("2026-03-04 09:00"), &Sent::default());
+        assert_eq!(keys(&morning), ["task:t1:60:2026-03-04T10:00"]);
+        assert_eq!(morning[0].task_id.as_deref(), Some("t1"));
+
+        assert!(pending(&tasks, &settings, at("2026-03-04 14:29"), &Sent::default()).is_empty());
+        let afternoon = pending(&tasks, &settings, at("2026-03-04 14:30"), &Sent::default());
+        assert_eq!(keys(&afternoon), ["remind:t2:2026-03-04T14:30"]);
+        assert_eq!(afternoon[0].title, "Reminder");
+
+        // Not carried over to the next day
+        assert!(pending(&tasks, &settings, at("2026-03-05 08:00"), &Sent::default()).is_empty());
+    }
+
+    #[test]
+    fn snoozing_until_tomorrow_waits_for_the_morning() {
+        let mut settings = settings();
+        let now = at("2026-03-04 17:45");
+        assert_eq!(
+            Snooze::TenMinutes.until(now, &settings),
+            at("2026-03-04 17:55")
+        );
+        assert_eq!(
+            Snooze::OneHour.until(now, &settings),
+            at("2026-03-04 18:45")
+        );
+        assert_eq!(
+            Snooze::Tomorrow.until(now, &settings),
+            at("2026-03-05 08:30")
+        );
+        settings.digest_time = None;
+        assert_eq!(
+            Snooze::Tomorrow.until(now, &settings),
+            at("2026-03-05 09:00")
+        );
+    }
+
+    #[test]
+    fn lead_times_read_naturally() {
+        let en = Messages::new("en-US");
+        let zh = Messages::new("zh-CN");
+        assert_eq!(en.due_soon(15), "Due in 15 min");
+        assert_eq!(en.due_soon(2880), "Due in 2 days");
+        assert_eq!(zh.due_soon(120), "2 小时后到期");
+    }
+
+    #[test]
+    fn completing_from_a_reminder_moves_the_task_to_g_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("active.json");
+        let active = json!({ "version": "2.0", "tasks": [task("t1", "2026-03-04", false)] });
+        fs::write(&path, active.to_string()).unwrap();
+        let now = Local.with_ymd_and_hms(2026, 3, 4, 9, 0, 0).unwrap();
+
+        let before = complete(dir.path(), "t1", now).unwrap().unwrap();
+        assert_eq!(before.priority, Priority::C);
+        assert!(!before.completed);
+
+        let data: ActiveData = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let done = &data.tasks[0];
+        assert_eq!(done.priority, Priority::G);
+        assert_eq!(done.original_priority, Some(Priority::C));
+        assert!(done.completed);
+        assert!(done.completed_at.is_some());
+
+        assert!(complete(dir.path(), "t1", now).unwrap().is_none());
+        assert!(complete(dir.path(), "gone", now).is_err());
+    }
+}
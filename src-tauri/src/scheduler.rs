@@ -0,0 +1,581 @@
+//! Daily backend jobs on `active.json`: advancing the 2-day cycle (see
+//! `cycle`) and materialising recurring task instances.
+//!
+//! Runs at startup and again after each local midnight. A recurring task
+//! gets its next instance once every instance of its series is completed and
+//! the next occurrence is due: on its due date, or on its threshold date when
+//! the series defers tasks (`thr:`). After days asleep only the latest due
+//! occurrence is created, not one per missed day. An occurrence that already
+//! has an instance is never created again, so runs are idempotent. New
+//! instances are subject to the priority quotas like any other write.
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{self, DataFileType};
+use crate::cycle;
+use crate::lock;
+use crate::model::{ActiveData, Priority, Task};
+use crate::quota::{self, QuotaReport};
+use crate::recurrence::{self, RRule};
+use crate::units::UnitLayout;
+
+/// How often the scheduler checks whether the date changed. Timers don't
+/// run while the machine sleeps, so it can't just sleep until midnight.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Payload of the `recurring-tasks-created` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Materialised {
+    pub task_ids: Vec<String>,
+    /// Instances left out because their priority is full (`quotaMode`
+    /// reject); they are retried on the next run
+    pub deferred: usize,
+    /// Set when adding the instances demoted tasks or went over a quota;
+    /// emitted separately as `quota-enforced`
+    #[serde(skip)]
+    pub quota: Option<QuotaReport>,
+}
+
+/// What a run changes in `active.json`
+#[derive(Debug, Default)]
+struct Plan {
+    /// Series IDs for recurring tasks created before series existed, by index
+    series_ids: Vec<(usize, String)>,
+    instances: Vec<Task>,
+}
+
+fn parse_date(date: Option<&str>) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date?, "%Y-%m-%d").ok()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Whether the task still needs doing (not completed or cancelled)
+fn is_open(task: &Task) -> bool {
+    !task.completed && !matches!(task.priority, Priority::G | Priority::H)
+}
+
+/// Pin DTSTART to `start` so COUNT keeps counting from the first instance
+/// instead of restarting at every instance's due date
+fn pin_start(rule: &str, start: NaiveDate) -> String {
+    format!("DTSTART:{}\n{}", start.format("%Y%m%d"), rule)
+}
+
+/// Series IDs for recurring tasks that don't have one. Such tasks are grouped
+/// by content and rule, and join a series with the same content and rule if
+/// there is one; otherwise the oldest task's ID names the series.
+fn assign_series(tasks: &[Task], rules: &[Option<String>]) -> Vec<(usize, String)> {
+    let key = |i: usize| (tasks[i].content.clone(), rules[i].clone());
+    let mut series: HashMap<(String, Option<String>), String> = HashMap::new();
+    let mut unassigned: HashMap<(String, Option<String>), Vec<usize>> = HashMap::new();
+
+    for (i, task) in tasks.iter().enumerate() {
+        let Some(recurrence) = &task.recurrence else {
+            continue;
+        };
+        if rules[i].is_none() {
+            continue;
+        }
+        match &recurrence.series_id {
+            Some(id) => {
+                series.insert(key(i), id.clone());
+            }
+            None => unassigned.entry(key(i)).or_default().push(i),
+        }
+    }
+
+    let mut assigned = Vec::new();
+    for (key, members) in unassigned {
+        let id = series.get(&key).cloned().unwrap_or_else(|| {
+            let oldest = members
+                .iter()
+                .min_by_key(|&&i| (&tasks[i].created_at, i))
+                .copied()
+                .unwrap_or(members[0]);
+            tasks[oldest].id.clone()
+        });
+        assigned.extend(members.into_iter().map(|i| (i, id.clone())));
+    }
+    assigned.sort();
+    assigned
+}
+
+/// The latest occurrence after `due` whose instance is due by `today`, where
+/// an instance is due `lead_days` before the occurrence (its threshold)
+fn due_occurrence(
+    rule: &RRule,
+    due: NaiveDate,
+    lead_days: i64,
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    rule.occurrences(due)
+        .filter(|d| *d > due)
+        .take_while(|d| (*d - today).num_days() <= lead_days)
+        .last()
+}
+
+/// Next instance of a series, built from its latest instance
+fn next_instance(
+    latest: &Task,
+    series_id: &str,
+    rule: String,
+    due: NaiveDate,
+    unit_start: NaiveDate,
+    now: DateTime<Local>,
+) -> Task {
+    let mut task = latest.clone();
+
+    // Keep the gap between the threshold and the due date
+    let lead = parse_date(latest.due_date.as_deref())
+        .zip(parse_date(latest.threshold_date.as_deref()))
+        .map(|(d, t)| d - t);
+    task.threshold_date = lead
+        .and_then(|lead| due.checked_sub_signed(lead))
+        .map(format_date);
+
+    task.id = uuid::Uuid::new_v4().to_string();
+    task.priority = match latest.original_priority.unwrap_or(latest.priority) {
+        Priority::G | Priority::H => Priority::F,
+        priority => priority,
+    };
+    task.completed = false;
+    task.completed_at = None;
+    task.created_at = now.to_rfc3339();
+    task.unit_start = format_date(unit_start);
+    // A reminder keeps its distance from the due date
+    let remind_at = latest.remind_at.as_deref();
+    task.remind_at = parse_date(latest.due_date.as_deref())
+        .zip(remind_at.and_then(|r| DateTime::parse_from_rfc3339(r).ok()))
+        .map(|(previous, remind_at)| (remind_at + (due - previous)).to_rfc3339());
+    task.due_date = Some(format_date(due));
+    task.pomodoros.completed = 0;
+    task.original_priority = None;
+    task.last_priority_change_at = None;
+    task.unit_override = None;
+    task.evolved_from = None;
+    if let Some(subtasks) = &mut task.subtasks {
+        for subtask in subtasks {
+            subtask.completed = false;
+            subtask.completed_at = None;
+        }
+    }
+    if let Some(recurrence) = &mut task.recurrence {
+        recurrence.rrule = Some(rule);
+        recurrence.series_id = Some(series_id.to_string());
+        recurrence.next_due = Some(format_date(due));
+    }
+    task
+}
+
+/// Work out the series IDs to assign and the instances due by `today`
+fn plan(tasks: &[Task], layout: &UnitLayout, today: NaiveDate, now: DateTime<Local>) -> Plan {
+    let rules: Vec<Option<String>> = tasks
+        .iter()
+        .map(|t| t.recurrence.as_ref().and_then(recurrence::rule_of))
+        .collect();
+    let series_ids = assign_series(tasks, &rules);
+
+    let mut series: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        let assigned = series_ids.iter().find(|(j, _)| *j == i).map(|(_, id)| id);
+        let id = assigned.or(task.recurrence.as_ref().and_then(|r| r.series_id.as_ref()));
+        if let (Some(id), Some(_)) = (id, &rules[i]) {
+            series.entry(id.clone()).or_default().push(i);
+        }
+    }
+
+    let mut series: Vec<(String, Vec<usize>)> = series.into_iter().collect();
+    series.sort();
+
+    let mut instances = Vec::new();
+    for (series_id, members) in series {
+        if members.iter().any(|&i| is_open(&tasks[i])) {
+            continue;
+        }
+        let Some((due, latest)) = members
+            .iter()
+            .filter_map(|&i| Some((parse_date(tasks[i].due_date.as_deref())?, i)))
+            .max()
+        else {
+            continue;
+        };
+        // A cancelled instance ends the series
+        if tasks[latest].priority == Priority::H {
+            continue;
+        }
+
+        let Some(rule_str) = rules[latest].clone() else {
+            continue;
+        };
+        let rule: RRule = match rule_str.parse() {
+            Ok(rule) => rule,
+            Err(e) => {
+                eprintln!("Skipping recurring task {}: {}", tasks[latest].id, e);
+                continue;
+            }
+        };
+        let rule_str = if rule.count.is_some() && rule.dtstart.is_none() {
+            pin_start(&rule_str, due)
+        } else {
+            rule_str
+        };
+
+        let lead_days = parse_date(tasks[latest].threshold_date.as_deref())
+            .map(|t| (due - t).num_days().max(0))
+            .unwrap_or(0);
+        let Some(next) = due_occurrence(&rule, due, lead_days, today) else {
+            continue;
+        };
+        let next_str = format_date(next);
+        if members
+            .iter()
+            .any(|&i| tasks[i].due_date.as_deref() == Some(next_str.as_str()))
+        {
+            continue;
+        }
+
+        instances.push(next_instance(
+            &tasks[latest],
+            &series_id,
+            rule_str,
+            next,
+            layout.unit_start(next),
+            now,
+        ));
+    }
+
+    Plan {
+        series_ids,
+        instances,
+    }
+}
+
+/// Add the recurring instances due by `today` to `active.json`. Returns
+/// `None` if nothing was created.
+pub fn materialise(data_dir: &Path, today: NaiveDate) -> Result<Option<Materialised>, String> {
+    let path = data_dir.join(DataFileType::Active.filename());
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let _lock = lock::acquire(data_dir)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let data: ActiveData = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let plan = plan(&data.tasks, &data.settings.unit_layout, today, Local::now());
+    if plan.series_ids.is_empty() && plan.instances.is_empty() {
+        return Ok(None);
+    }
+
+    let mut active: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+    if let Some(tasks) = active.get_mut("tasks").and_then(Value::as_array_mut) {
+        for (i, series_id) in &plan.series_ids {
+            if let Some(recurrence) = tasks[*i]
+                .get_mut("recurrence")
+                .and_then(Value::as_object_mut)
+            {
+                recurrence.insert("seriesId".to_string(), Value::String(series_id.clone()));
+            }
+        }
+    }
+    let instances = plan
+        .instances
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to serialize task: {}", e))?;
+    let appended = quota::append_within_quota(active, instances)?;
+    commands::write_data_file(data_dir, DataFileType::Active, &appended.content)?;
+
+    let (task_ids, deferred) = (appended.task_ids, appended.rejected);
+    if task_ids.is_empty() && deferred == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Materialised {
+        task_ids,
+        deferred,
+        quota: appended.report,
+    }))
+}
+
+/// Advance the 2-day cycle; units always begin at midnight
+fn advance_cycle(app_handle: &AppHandle, data_dir: &Path, today: NaiveDate) -> Result<(), String> {
+    let Some(advance) = cycle::run(data_dir, today)? else {
+        return Ok(());
+    };
+    println!("Advanced cycle to {}", advance.state.anchor_start);
+
+    if let Err(e) = app_handle.emit("cycle-advanced", advance) {
+        eprintln!("Failed to emit event: {}", e);
+    }
+    Ok(())
+}
+
+fn create_recurring(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    today: NaiveDate,
+) -> Result<(), String> {
+    let Some(mut materialised) = materialise(data_dir, today)? else {
+        return Ok(());
+    };
+    println!(
+        "Created {} recurring task instance(s), {} deferred by quota",
+        materialised.task_ids.len(),
+        materialised.deferred
+    );
+
+    if let Some(report) = materialised.quota.take() {
+        if let Err(e) = app_handle.emit("quota-enforced", report) {
+            eprintln!("Failed to emit event: {}", e);
+        }
+    }
+    if let Err(e) = app_handle.emit("recurring-tasks-created", materialised) {
+        eprintln!("Failed to emit event: {}", e);
+    }
+    Ok(())
+}
+
+/// The day's jobs. The cycle goes first so rolled-over tasks are in place
+/// before new instances are checked against the quotas.
+fn run_once(app_handle: &AppHandle, data_dir: &Path, today: NaiveDate) -> Result<(), String> {
+    advance_cycle(app_handle, data_dir, today)
+        .map_err(|e| format!("Failed to advance cycle: {}", e))?;
+    create_recurring(app_handle, data_dir, today)
+        .map_err(|e| format!("Failed to create recurring tasks: {}", e))
+}
+
+/// Time until the next local midnight, capped at `CHECK_INTERVAL`
+fn next_check(now: DateTime<Local>) -> Duration {
+    now.date_naive()
+        .succ_opt()
+        .and_then(|tomorrow| tomorrow.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .and_then(|midnight| (midnight - now).to_std().ok())
+        .map_or(CHECK_INTERVAL, |until| until.min(CHECK_INTERVAL))
+}
+
+/// Advance the cycle and materialise recurring tasks now and whenever the
+/// local date changes. A failed run (e.g. the data directory is locked) is retried at the next
+/// check.
+pub async fn run(app_handle: AppHandle) {
+    let mut last_run: Option<NaiveDate> = None;
+
+    loop {
+        let today = Local::now().date_naive();
+        if last_run != Some(today) {
+            match app_handle.path().app_data_dir() {
+                Ok(data_dir) => match run_once(&app_handle, &data_dir, today) {
+                    Ok(()) => last_run = Some(today),
+                    Err(e) => eprintln!("{}", e),
+                },
+                Err(e) => eprintln!("Failed to resolve data directory: {}", e),
+            }
+        }
+
+        tokio::time::sleep(next_check(Local::now())).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, due: &str, completed: bool, rule: &str) -> Task {
+        serde_json::from_value(json!({
+            "id": id,
+            "content": "Water plants",
+            "priority": if completed { "G" } else { "C" },
+            "originalPriority": if completed { Some("C") } else { None },
+            "completed": completed,
+            "createdAt": format!("{}T08:00:00+00:00", due),
+            "dueDate": due,
+            "recurrence": { "pattern": null, "rrule": rule, "nextDue": null }
+        }))
+        .unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn apply(tasks: &mut Vec<Task>, plan: Plan) {
+        for (i, id) in plan.series_ids {
+            tasks[i].recurrence.as_mut().unwrap().series_id = Some(id);
+        }
+        tasks.extend(plan.instances);
+    }
+
+    #[test]
+    fn catches_up_with_the_latest_occurrence_only() {
+        let tasks = vec![task("t1", "2026-03-02", true, "FREQ=DAILY")];
+
+        let plan = plan(
+            &tasks,
+            &UnitLayout::default(),
+            date("2026-03-06"),
+            Local::now(),
+        );
+        assert_eq!(plan.series_ids, [(0, "t1".to_string())]);
+        assert_eq!(plan.instances.len(), 1);
+        let instance = &plan.instances[0];
+        assert_eq!(instance.due_date.as_deref(), Some("2026-03-06"));
+        assert_eq!(instance.priority, Priority::C);
+        assert!(!instance.completed);
+        assert_eq!(
+            instance.recurrence.as_ref().unwrap().series_id.as_deref(),
+            Some("t1")
+        );
+    }
+
+    #[test]
+    fn is_idempotent() {
+        let mut tasks = vec![task("t1", "2026-03-02", true, "FREQ=WEEKLY")];
+        let today = date("2026-03-09");
+
+        let first = plan(&tasks, &UnitLayout::default(), today, Local::now());
+        assert_eq!(first.instances.len(), 1);
+        apply(&mut tasks, first);
+
+        let second = plan(&tasks, &UnitLayout::default(), today, Local::now());
+        assert!(second.series_ids.is_empty());
+        assert!(second.instances.is_empty());
+
+        // Completing the new instance doesn't bring back the same occurrence
+        tasks[1].completed = true;
+        tasks[1].priority = Priority::G;
+        assert!(plan(&tasks, &UnitLayout::default(), today, Local::now())
+            .instances
+            .is_empty());
+    }
+
+    #[test]
+    fn waits_while_an_instance_is_open_or_not_yet_due() {
+        let open = vec![
+            task("t1", "2026-03-02", true, "FREQ=DAILY"),
+            task("t2", "2026-03-03", false, "FREQ=DAILY"),
+        ];
+        assert!(plan(
+            &open,
+            &UnitLayout::default(),
+            date("2026-03-06"),
+            Local::now()
+        )
+        .instances
+        .is_empty());
+
+        let weekly = vec![task("t1", "2026-03-02", true, "FREQ=WEEKLY")];
+        assert!(plan(
+            &weekly,
+            &UnitLayout::default(),
+            date("2026-03-08"),
+            Local::now()
+        )
+        .instances
+        .is_empty());
+    }
+
+    #[test]
+    fn threshold_dates_bring_instances_forward() {
+        let mut latest = task("t1", "2026-03-02", true, "FREQ=WEEKLY");
+        latest.threshold_date = Some("2026-02-28".to_string());
+
+        let plan = plan(
+            &[latest],
+            &UnitLayout::default(),
+            date("2026-03-07"),
+            Local::now(),
+        );
+        let instance = &plan.instances[0];
+        assert_eq!(instance.due_date.as_deref(), Some("2026-03-09"));
+        assert_eq!(instance.threshold_date.as_deref(), Some("2026-03-07"));
+    }
+
+    #[test]
+    fn reminders_move_with_the_due_date() {
+        let mut latest = task("t1", "2026-03-02", true, "FREQ=WEEKLY");
+        latest.remind_at = Some("2026-03-01T09:30:00+00:00".to_string());
+
+        let plan = plan(
+            &[latest],
+            &UnitLayout::default(),
+            date("2026-03-09"),
+            Local::now(),
+        );
+        assert_eq!(
+            plan.instances[0].remind_at.as_deref(),
+            Some("2026-03-08T09:30:00+00:00")
+        );
+    }
+
+    #[test]
+    fn legacy_instances_join_one_series() {
+        let mut old = task("t1", "2026-03-02", true, "FREQ=DAILY");
+        old.recurrence.as_mut().unwrap().rrule = None;
+        old.recurrence.as_mut().unwrap().custom_pattern = Some("mon,tue".to_string());
+        let mut newer = old.clone();
+        newer.id = "t2".to_string();
+        newer.due_date = Some("2026-03-03".to_string());
+        newer.created_at = "2026-03-02T09:00:00+00:00".to_string();
+        newer.completed = false;
+        newer.priority = Priority::C;
+
+        let plan = plan(
+            &[old, newer],
+            &UnitLayout::default(),
+            date("2026-03-09"),
+            Local::now(),
+        );
+        assert_eq!(
+            plan.series_ids,
+            [(0, "t1".to_string()), (1, "t1".to_string())]
+        );
+        assert!(plan.instances.is_empty());
+    }
+
+    #[test]
+    fn count_is_pinned_to_the_first_instance() {
+        let tasks = vec![task("t1", "2026-03-02", true, "FREQ=DAILY;COUNT=2")];
+        let plan = plan(
+            &tasks,
+            &UnitLayout::default(),
+            date("2026-03-03"),
+            Local::now(),
+        );
+        let rule = plan.instances[0]
+            .recurrence
+            .as_ref()
+            .unwrap()
+            .rrule
+            .clone()
+            .unwrap();
+        assert_eq!(rule, "DTSTART:20260302\nFREQ=DAILY;COUNT=2");
+
+        let mut tasks = tasks;
+        apply(&mut tasks, plan);
+        tasks[1].completed = true;
+        tasks[1].priority = Priority::G;
+        assert!(super::plan(
+            &tasks,
+            &UnitLayout::default(),
+            date("2026-03-10"),
+            Local::now()
+        )
+        .instances
+        .is_empty());
+    }
+}
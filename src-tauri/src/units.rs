@@ -0,0 +1,480 @@
+//! Planning units (mirror of `src/lib/utils/unitCalc.ts`).
+//!
+//! The week is split into units by the `unitLayout` setting: days are taken
+//! in order from `weekStart`, skipping the review day, and cut into units of
+//! `unitLengths` days. The default layout has unit 1 Sunday + Monday, unit 2
+//! Tuesday + Wednesday and unit 3 Thursday + Friday, with Saturday as the
+//! weekly review day.
+//!
+//! Both implementations are checked against `src/lib/utils/units.vectors.json`;
+//! keep them in step when changing how units are laid out.
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{self, DataFileType};
+use crate::lock;
+use crate::model::ActiveData;
+
+/// How the week is split into units
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UnitLayout {
+    pub week_start: Weekday,
+    /// Days per unit, in order from `week_start`
+    pub unit_lengths: Vec<u32>,
+    /// `None` for a week without a review day
+    pub review_day: Option<Weekday>,
+}
+
+impl Default for UnitLayout {
+    fn default() -> Self {
+        Self {
+            week_start: Weekday::Sun,
+            unit_lengths: vec![2, 2, 2],
+            review_day: Some(Weekday::Sat),
+        }
+    }
+}
+
+/// A unit, or the review day, with its inclusive date range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unit {
+    /// Position in the week from 1; 0 for the review day
+    pub number: u8,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub is_review_day: bool,
+}
+
+/// A unit with its boundaries widened by `unitBoundaryFlexHours`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlexibleUnit {
+    #[serde(flatten)]
+    pub unit: Unit,
+    pub flex_start: NaiveDateTime,
+    pub flex_end: NaiveDateTime,
+}
+
+/// Where a day of the week falls in the layout
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    number: u8,
+    /// Days since the start of its unit
+    day: u32,
+    length: u32,
+}
+
+/// Days from `from` forward to `to`
+fn days_between(from: Weekday, to: Weekday) -> u32 {
+    (to.num_days_from_sunday() + 7 - from.num_days_from_sunday()) % 7
+}
+
+impl UnitLayout {
+    /// Check the units and review day cover the week exactly once, and that
+    /// the review day doesn't fall inside a unit
+    pub fn check(&self) -> Result<(), String> {
+        if self.unit_lengths.is_empty() || self.unit_lengths.contains(&0) {
+            return Err("Every unit must be at least one day long".to_string());
+        }
+        let days = 7 - u32::from(self.review_day.is_some());
+        let total = self
+            .unit_lengths
+            .iter()
+            .try_fold(0u32, |sum, &n| sum.checked_add(n));
+        if total != Some(days) {
+            return Err(format!(
+                "Unit lengths must add up to {} days, not {}",
+                days,
+                self.unit_lengths
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join("+")
+            ));
+        }
+
+        if let Some(review_day) = self.review_day {
+            // Units before the review day must end exactly before it
+            let before = days_between(self.week_start, review_day);
+            let mut boundary = 0;
+            let splits = self.unit_lengths.iter().all(|&n| {
+                let inside = boundary < before && before < boundary + n;
+                boundary += n;
+                !inside
+            });
+            if !splits {
+                return Err(format!(
+                    "The review day ({}) falls inside a unit",
+                    review_day
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Slot of each day, by days since `week_start`
+    fn slots(&self) -> [Slot; 7] {
+        let review = self.review_day.map(|d| days_between(self.week_start, d));
+        let mut slots = [Slot {
+            number: 0,
+            day: 0,
+            length: 1,
+        }; 7];
+        let mut offsets = (0..7u32).filter(|&o| Some(o) != review);
+        for (i, &length) in self.unit_lengths.iter().enumerate() {
+            for day in 0..length {
+                if let Some(offset) = offsets.next() {
+                    slots[offset as usize] = Slot {
+                        number: i as u8 + 1,
+                        day,
+                        length,
+                    };
+                }
+            }
+        }
+        slots
+    }
+
+    /// The unit `date` belongs to. An invalid layout falls back to the default.
+    pub fn unit_for(&self, date: NaiveDate) -> Unit {
+        if self.check().is_err() {
+            return Self::default().unit_for(date);
+        }
+        let slot = self.slots()[days_between(self.week_start, date.weekday()) as usize];
+        let start = date - Duration::days(slot.day.into());
+        Unit {
+            number: slot.number,
+            start,
+            end: start + Duration::days((slot.length - 1).into()),
+            is_review_day: slot.number == 0,
+        }
+    }
+
+    /// Start of the unit `date` belongs to. This is the `unitStart` of a task
+    /// created that day.
+    pub fn unit_start(&self, date: NaiveDate) -> NaiveDate {
+        self.unit_for(date).start
+    }
+
+    /// The units and review day of the layout's week containing `date`
+    pub fn week_units(&self, date: NaiveDate) -> Vec<Unit> {
+        let week_start = if self.check().is_ok() {
+            self.week_start
+        } else {
+            Self::default().week_start
+        };
+        let first = date - Duration::days(days_between(week_start, date.weekday()).into());
+        let mut units: Vec<Unit> = Vec::new();
+        for offset in 0..7 {
+            let unit = self.unit_for(first + Duration::days(offset));
+            if units.last() != Some(&unit) {
+                units.push(unit);
+            }
+        }
+        units
+    }
+}
+
+impl Unit {
+    /// The unit starting `flex_hours` early and ending `flex_hours` late
+    pub fn with_flex(self, flex_hours: f64) -> FlexibleUnit {
+        let flex = Duration::milliseconds((flex_hours.max(0.0) * 3_600_000.0) as i64);
+        let midnight = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        FlexibleUnit {
+            unit: self,
+            flex_start: midnight(self.start) - flex,
+            flex_end: midnight(self.end + Duration::days(1)) + flex,
+        }
+    }
+}
+
+/// Point a `YYYY-MM-DD` value at the start of its unit under `layout`.
+/// Returns whether it changed.
+fn relabel_date(value: &mut Value, layout: &UnitLayout) -> bool {
+    let Some(date) = value
+        .as_str()
+        .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+    else {
+        return false;
+    };
+    let canonical = layout.unit_start(date);
+    if canonical == date {
+        return false;
+    }
+    *value = Value::String(canonical.format("%Y-%m-%d").to_string());
+    true
+}
+
+/// Point every task's `unitStart`, and the period starts of the 2-day cycle,
+/// at the start of their unit under `layout`. Returns how many tasks changed.
+fn relabel(active: &mut Value, layout: &UnitLayout) -> usize {
+    let mut relabelled = 0;
+    if let Some(tasks) = active.get_mut("tasks").and_then(Value::as_array_mut) {
+        for task in tasks {
+            if let Some(unit_start) = task.get_mut("unitStart") {
+                if relabel_date(unit_start, layout) {
+                    relabelled += 1;
+                }
+            }
+        }
+    }
+
+    if let Some(state) = active.get_mut("cycleState").and_then(Value::as_object_mut) {
+        for key in ["anchorStart", "lastEvaluatedStart"] {
+            if let Some(value) = state.get_mut(key) {
+                relabel_date(value, layout);
+            }
+        }
+        if let Some(value) = state
+            .get_mut("pendingReview")
+            .and_then(|review| review.get_mut("periodStart"))
+        {
+            relabel_date(value, layout);
+        }
+        // The window is the anchor's unit, which may have a new length
+        let anchor = state
+            .get("anchorStart")
+            .and_then(Value::as_str)
+            .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+        if let Some(anchor) = anchor {
+            let end = layout.unit_for(anchor).end.format("%Y-%m-%d").to_string();
+            state.insert("windowEnd".to_string(), Value::String(end));
+        }
+    }
+
+    if let Some(history) = active.get_mut("cycleHistory").and_then(Value::as_array_mut) {
+        for entry in history {
+            if let Some(value) = entry.get_mut("periodStart") {
+                relabel_date(value, layout);
+            }
+        }
+    }
+
+    relabelled
+}
+
+fn read_active(path: &Path) -> Result<(String, Value), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read active data: {}", e))?;
+    let active = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+    Ok((content, active))
+}
+
+fn write_active(data_dir: &Path, active: &mut Value) -> Result<(), String> {
+    active["lastModified"] = Value::String(Local::now().to_rfc3339());
+    let content = serde_json::to_string_pretty(active)
+        .map_err(|e| format!("Failed to serialize active data: {}", e))?;
+    DataFileType::Active.validate_content(&content)?;
+    commands::write_data_file(data_dir, DataFileType::Active, &content)?;
+    Ok(())
+}
+
+/// The unit layout in the settings of `active.json`, or the default
+pub fn load_layout(data_dir: &Path) -> UnitLayout {
+    fs::read_to_string(data_dir.join(DataFileType::Active.filename()))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ActiveData>(&content).ok())
+        .map(|data| data.settings.unit_layout)
+        .unwrap_or_default()
+}
+
+/// Re-label `unitStart` and the cycle's periods in `active.json` for the
+/// configured layout, e.g. after it was changed outside the app. Returns how
+/// many tasks changed.
+pub fn migrate_active(data_dir: &Path) -> Result<usize, String> {
+    let path = data_dir.join(DataFileType::Active.filename());
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let _lock = lock::acquire(data_dir)?;
+    let (content, mut active) = read_active(&path)?;
+    let data: ActiveData = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse active data: {}", e))?;
+
+    let before = active.clone();
+    let relabelled = relabel(&mut active, &data.settings.unit_layout);
+    if active != before {
+        write_active(data_dir, &mut active)?;
+    }
+    Ok(relabelled)
+}
+
+/// Switch to `layout` and re-label `unitStart` and the cycle's periods in one
+/// write. A missing `active.json` is started from an empty document. Returns
+/// how many tasks changed.
+pub fn set_layout(data_dir: &Path, layout: &UnitLayout) -> Result<usize, String> {
+    layout.check()?;
+    let layout_value =
+        serde_json::to_value(layout).map_err(|e| format!("Failed to serialize layout: {}", e))?;
+
+    let _lock = lock::acquire(data_dir)?;
+    let path = data_dir.join(DataFileType::Active.filename());
+    let mut active = if path.exists() {
+        read_active(&path)?.1
+    } else {
+        serde_json::json!({ "version": "2.0", "tasks": [] })
+    };
+    let Some(obj) = active.as_object_mut() else {
+        return Err("Active data is not a JSON object".to_string());
+    };
+
+    let settings = obj
+        .entry("settings")
+        .or_insert_with(|| Value::Object(Default::default()));
+    match settings.as_object_mut() {
+        Some(settings) => {
+            settings.insert("unitLayout".to_string(), layout_value);
+        }
+        None => return Err("Settings are not a JSON object".to_string()),
+    }
+
+    let relabelled = relabel(&mut active, layout);
+    write_active(data_dir, &mut active)?;
+    Ok(relabelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: &str = include_str!("../../src/lib/utils/units.vectors.json");
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn matches_shared_vectors() {
+        let vectors: Value = serde_json::from_str(VECTORS).unwrap();
+        let mut failures = Vec::new();
+
+        for case in vectors["layouts"].as_array().unwrap() {
+            let layout: UnitLayout = serde_json::from_value(case["layout"].clone()).unwrap();
+            match (layout.check(), case["error"].as_bool().unwrap_or(false)) {
+                (Ok(()), true) => failures.push(format!("{}: accepted", case["name"])),
+                (Err(e), false) => failures.push(format!("{}: {}", case["name"], e)),
+                _ => {}
+            }
+
+            for expected in case["units"].as_array().into_iter().flatten() {
+                let on = date(expected["date"].as_str().unwrap());
+                let unit = layout.unit_for(on);
+                let actual = serde_json::json!({
+                    "date": expected["date"],
+                    "number": unit.number,
+                    "start": unit.start.format("%Y-%m-%d").to_string(),
+                    "end": unit.end.format("%Y-%m-%d").to_string(),
+                    "isReviewDay": unit.is_review_day,
+                });
+                if &actual != expected {
+                    failures.push(format!(
+                        "{} on {}\n  expected {}\n  actual   {}",
+                        case["name"], on, expected, actual
+                    ));
+                }
+            }
+        }
+
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn relabels_tasks_for_a_new_layout() {
+        let layout = UnitLayout {
+            week_start: Weekday::Mon,
+            unit_lengths: vec![3, 3],
+            review_day: Some(Weekday::Sun),
+        };
+        // Tuesday 2026-03-03 is in the Monday-Wednesday unit
+        let mut active = serde_json::json!({
+            "tasks": [
+                { "id": "a", "unitStart": "2026-03-03" },
+                { "id": "b", "unitStart": "2026-03-02" },
+                { "id": "c" }
+            ]
+        });
+
+        assert_eq!(relabel(&mut active, &layout), 1);
+        assert_eq!(active["tasks"][0]["unitStart"], "2026-03-02");
+        assert_eq!(relabel(&mut active, &layout), 0);
+    }
+
+    #[test]
+    fn relabels_the_cycle_with_the_tasks() {
+        let layout = UnitLayout {
+            week_start: Weekday::Mon,
+            unit_lengths: vec![3, 3],
+            review_day: Some(Weekday::Sun),
+        };
+        let mut active = serde_json::json!({
+            "tasks": [],
+            "cycleState": {
+                "anchorStart": "2026-03-03",
+                "windowEnd": "2026-03-04",
+                "lastEvaluatedStart": "2026-03-03",
+                "pendingReview": { "periodStart": "2026-03-04", "completion": 0.5 }
+            },
+            "cycleHistory": [{ "periodStart": "2026-03-06", "completion": 1.0 }]
+        });
+
+        assert_eq!(relabel(&mut active, &layout), 0);
+        let state = &active["cycleState"];
+        assert_eq!(state["anchorStart"], "2026-03-02");
+        assert_eq!(state["windowEnd"], "2026-03-04");
+        assert_eq!(state["lastEvaluatedStart"], "2026-03-02");
+        assert_eq!(state["pendingReview"]["periodStart"], "2026-03-02");
+        assert_eq!(active["cycleHistory"][0]["periodStart"], "2026-03-05");
+    }
+
+    #[test]
+    fn set_layout_starts_a_missing_file_from_a_valid_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = UnitLayout {
+            week_start: Weekday::Mon,
+            unit_lengths: vec![3, 3],
+            review_day: Some(Weekday::Sun),
+        };
+
+        assert_eq!(set_layout(dir.path(), &layout).unwrap(), 0);
+
+        let content = fs::read_to_string(dir.path().join("active.json")).unwrap();
+        DataFileType::Active.validate_content(&content).unwrap();
+        let data: ActiveData = serde_json::from_str(&content).unwrap();
+        assert_eq!(data.version, "2.0");
+        assert!(!data.last_modified.is_empty());
+        assert_eq!(data.settings.unit_layout, layout);
+        assert_eq!(load_layout(dir.path()), layout);
+    }
+
+    #[test]
+    fn week_units_follow_the_layout() {
+        let layout = UnitLayout {
+            week_start: Weekday::Mon,
+            unit_lengths: vec![2, 2, 1, 1],
+            review_day: Some(Weekday::Sun),
+        };
+        let starts: Vec<_> = layout
+            .week_units(date("2026-03-04"))
+            .iter()
+            .map(|u| (u.number, u.start.format("%a").to_string()))
+            .collect();
+        assert_eq!(
+            starts,
+            [
+                (1, "Mon".to_string()),
+                (2, "Wed".to_string()),
+                (3, "Fri".to_string()),
+                (4, "Sat".to_string()),
+                (0, "Sun".to_string())
+            ]
+        );
+    }
+}
@@ -1,119 +1,451 @@
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher, EventKind};
-use std::sync::atomic::Ordering;
-use std::sync::mpsc::channel;
+use chrono::Local;
+use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::sync::Arc;
-use std::time::Duration;
-use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::commands::DataFileType;
+use crate::debounce::{Debouncer, MAX_DELAY, SETTLE_DELAY, WATCHED_FILES};
+use crate::diff::{self, FileChange};
+use crate::durable;
+use crate::inbox::{self, InboxFailure};
+use crate::merge::Snapshots;
 use crate::WatcherState;
 
-/// Data files to watch
-const WATCHED_FILES: &[&str] = &["active.json", "archive.json", "pomodoro_history.json"];
+/// How often to check for expired suspension leases while changes are held back
+const REPLAY_POLL: Duration = Duration::from_millis(250);
 
-/// Start watching the data files for external changes
-pub fn start_watcher(app_handle: AppHandle, watcher_state: Arc<WatcherState>) -> notify::Result<()> {
+/// How often a running watcher checks that the data directory is still the
+/// one it is attached to
+const HEALTH_CHECK: Duration = Duration::from_secs(2);
+
+/// How often to look for the data directory while it is missing
+const DIRECTORY_POLL: Duration = Duration::from_secs(1);
+
+/// First delay before restarting a failed watcher; doubles up to `MAX_BACKOFF`
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A watcher that ran this long before failing starts over at `INITIAL_BACKOFF`
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WatcherHealth {
+    #[default]
+    Starting,
+    Running,
+    /// Failed; waiting out the backoff before restarting
+    Restarting,
+    /// The data directory is gone; re-attaching once it reappears
+    WaitingForDirectory,
+    /// Can't be restarted (no data directory could be resolved)
+    Stopped,
+}
+
+/// Health of the file watcher, returned by `watcher_status` and sent with
+/// `watcher-degraded` / `watcher-recovered`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub health: WatcherHealth,
+    pub data_dir: Option<String>,
+    /// Times the watcher has been restarted this session
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// When the watcher last attached, RFC 3339
+    pub running_since: Option<String>,
+    /// When the next restart is attempted, RFC 3339
+    pub next_retry: Option<String>,
+}
+
+impl WatcherStatus {
+    /// Auto-reload isn't working right now
+    pub fn is_degraded(&self) -> bool {
+        !matches!(
+            self.health,
+            WatcherHealth::Starting | WatcherHealth::Running
+        )
+    }
+}
+
+/// Why a watch session ended
+enum SessionEnd {
+    /// The data directory was removed or replaced
+    DirectoryGone,
+    Failed(String),
+}
+
+/// Identity of the data directory, to notice it being deleted and recreated
+#[cfg(unix)]
+fn dir_identity(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path)
+        .ok()
+        .filter(|m| m.is_dir())
+        .map(|m| (m.dev(), m.ino()))
+}
+
+/// Identity of the data directory. Elsewhere a replaced directory surfaces as
+/// a watch error instead.
+#[cfg(not(unix))]
+fn dir_identity(path: &Path) -> Option<(u64, u64)> {
+    fs::metadata(path)
+        .ok()
+        .filter(|m| m.is_dir())
+        .map(|_| (0, 0))
+}
+
+/// Apply `update` to the watcher status, emitting `watcher-degraded` or
+/// `watcher-recovered` when auto-reload stops or starts working
+fn update_status(
+    app_handle: &AppHandle,
+    watcher_state: &WatcherState,
+    update: impl FnOnce(&mut WatcherStatus),
+) {
+    let (was_degraded, status) = watcher_state.update_status(update);
+    if was_degraded == status.is_degraded() {
+        return;
+    }
+
+    let event = if status.is_degraded() {
+        "watcher-degraded"
+    } else {
+        "watcher-recovered"
+    };
+    if let Err(e) = app_handle.emit(event, status) {
+        eprintln!("Failed to emit event: {}", e);
+    }
+}
+
+/// Watch the data files for external changes for the rest of the session.
+/// The watcher is restarted with backoff when it fails, and re-attached when
+/// the data directory is deleted and recreated.
+pub fn supervise(
+    app_handle: AppHandle,
+    watcher_state: Arc<WatcherState>,
+    snapshots: Arc<Snapshots>,
+) {
+    let data_dir = match app_handle.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Failed to start file watcher: {}", e);
+            update_status(&app_handle, &watcher_state, |status| {
+                status.health = WatcherHealth::Stopped;
+                status.last_error = Some(format!("Failed to get app data dir: {}", e));
+            });
+            return;
+        }
+    };
+
+    // Ensure directory exists
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir).ok();
+    }
+
+    update_status(&app_handle, &watcher_state, |status| {
+        status.data_dir = Some(data_dir.to_string_lossy().to_string());
+    });
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attached_before = false;
+
+    loop {
+        if dir_identity(&data_dir).is_none() {
+            update_status(&app_handle, &watcher_state, |status| {
+                status.health = WatcherHealth::WaitingForDirectory;
+                status.next_retry = None;
+            });
+            thread::sleep(DIRECTORY_POLL);
+            continue;
+        }
+
+        if attached_before {
+            update_status(&app_handle, &watcher_state, |status| status.restarts += 1);
+        }
+
+        let started = Instant::now();
+        let end = panic::catch_unwind(AssertUnwindSafe(|| {
+            watch_session(
+                &app_handle,
+                &data_dir,
+                &watcher_state,
+                &snapshots,
+                attached_before,
+            )
+        }))
+        .unwrap_or_else(|_| SessionEnd::Failed("File watcher panicked".to_string()));
+        attached_before = true;
+
+        if started.elapsed() >= STABLE_AFTER {
+            backoff = INITIAL_BACKOFF;
+        }
+
+        match end {
+            SessionEnd::DirectoryGone => {
+                println!(
+                    "Data directory removed, waiting for it to reappear: {:?}",
+                    data_dir
+                );
+                update_status(&app_handle, &watcher_state, |status| {
+                    status.health = WatcherHealth::WaitingForDirectory;
+                    status.next_retry = None;
+                });
+            }
+            SessionEnd::Failed(e) => {
+                eprintln!("File watcher failed, restarting in {:?}: {}", backoff, e);
+                let retry_at = Local::now()
+                    + chrono::Duration::from_std(backoff).unwrap_or(chrono::Duration::zero());
+                update_status(&app_handle, &watcher_state, |status| {
+                    status.health = WatcherHealth::Restarting;
+                    status.last_error = Some(e);
+                    status.next_retry = Some(retry_at.to_rfc3339());
+                });
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+/// Watch `data_dir` until the directory goes away or the watch fails.
+/// `reattached` replays changes that may have been missed since the last
+/// session.
+fn watch_session(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    watcher_state: &WatcherState,
+    snapshots: &Snapshots,
+    reattached: bool,
+) -> SessionEnd {
     let (tx, rx) = channel();
 
-    let mut watcher = RecommendedWatcher::new(
+    let watcher = RecommendedWatcher::new(
         move |res| {
             if let Err(e) = tx.send(res) {
                 eprintln!("Error sending file event: {}", e);
             }
         },
         Config::default().with_poll_interval(Duration::from_secs(1)),
-    )?;
+    );
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => return SessionEnd::Failed(format!("Failed to create watcher: {}", e)),
+    };
+
+    let Some(identity) = dir_identity(data_dir) else {
+        return SessionEnd::DirectoryGone;
+    };
+
+    // Watch the data directory
+    if let Err(e) = watcher.watch(data_dir, RecursiveMode::NonRecursive) {
+        return SessionEnd::Failed(format!("Failed to watch {:?}: {}", data_dir, e));
+    }
+
+    println!("Watching for file changes in: {:?}", data_dir);
+    println!("Monitored files: {:?}", WATCHED_FILES);
+
+    // Watch the inbox drop folder; auto-reload works without it
+    let inbox_dir = inbox::inbox_dir(data_dir);
+    let inbox_watched = fs::create_dir_all(&inbox_dir)
+        .map_err(|e| e.to_string())
+        .and_then(|()| {
+            watcher
+                .watch(&inbox_dir, RecursiveMode::NonRecursive)
+                .map_err(|e| e.to_string())
+        });
+    match inbox_watched {
+        Ok(()) => println!("Watching inbox: {:?}", inbox_dir),
+        Err(e) => eprintln!("Failed to watch inbox {:?}: {}", inbox_dir, e),
+    }
+
+    update_status(app_handle, watcher_state, |status| {
+        status.health = WatcherHealth::Running;
+        status.running_since = Some(Local::now().to_rfc3339());
+        status.next_retry = None;
+    });
+
+    // Pick up anything that changed while no watcher was attached
+    if reattached {
+        for file_type in DataFileType::ALL {
+            deliver_change(
+                app_handle,
+                data_dir,
+                file_type.filename(),
+                watcher_state,
+                snapshots,
+            );
+        }
+    }
+
+    let mut debouncer = Debouncer::new(SETTLE_DELAY, MAX_DELAY);
+
+    // Inbox files wait for the same quiet period, so a file still being
+    // written isn't imported half-way. Files dropped while not watching are
+    // picked up too.
+    let mut inbox_debouncer = Debouncer::new(SETTLE_DELAY, MAX_DELAY);
+    for file_name in inbox::pending_files(&inbox_dir) {
+        inbox_debouncer.touch(file_name, Instant::now());
+    }
 
-    // Get app data directory
-    if let Ok(data_dir) = app_handle.path().app_data_dir() {
-        // Ensure directory exists
-        if !data_dir.exists() {
-            std::fs::create_dir_all(&data_dir).ok();
+    // Process events, waking early when a pending change is due
+    loop {
+        let mut wake = Instant::now() + HEALTH_CHECK;
+        for deadline in [debouncer.next_deadline(), inbox_debouncer.next_deadline()]
+            .into_iter()
+            .flatten()
+        {
+            wake = wake.min(deadline);
+        }
+        if watcher_state.has_deferred() {
+            wake = wake.min(Instant::now() + REPLAY_POLL);
         }
 
-        // Watch the data directory
-        watcher.watch(data_dir.as_path(), RecursiveMode::NonRecursive)?;
-
-        println!("Watching for file changes in: {:?}", data_dir);
-        println!("Monitored files: {:?}", WATCHED_FILES);
-
-        // Track recently emitted events to avoid duplicates
-        let mut recent_events: HashSet<String> = HashSet::new();
-
-        // Process events
-        loop {
-            match rx.recv() {
-                Ok(event) => {
-                    if let Ok(event) = event {
-                        // Check if watcher is paused (during DnD operations)
-                        if watcher_state.paused.load(Ordering::SeqCst) {
-                            continue;
-                        }
-
-                        // Only process modify/create events
-                        let is_write_event = matches!(
-                            event.kind,
-                            EventKind::Modify(_) | EventKind::Create(_)
-                        );
-
-                        if !is_write_event {
-                            continue;
-                        }
-
-                        // Check if the changed file is one of our data files
-                        for path in &event.paths {
-                            if let Some(file_name) = path.file_name() {
-                                let file_name_str = file_name.to_string_lossy().to_string();
-
-                                // Skip temp files
-                                if file_name_str.ends_with(".tmp") {
-                                    continue;
-                                }
-
-                                // Check if it's a watched file
-                                if let Some(file_type) = get_file_type(&file_name_str) {
-                                    // Double-check pause state before emitting
-                                    if watcher_state.paused.load(Ordering::SeqCst) {
-                                        println!("Skipping file change (watcher paused): {} (type: {})", file_name_str, file_type);
-                                        continue;
-                                    }
-
-                                    // Deduplicate events (same file within short period)
-                                    let event_key = format!("{}:{}", file_type, std::time::SystemTime::now()
-                                        .duration_since(std::time::UNIX_EPOCH)
-                                        .map(|d| d.as_secs() / 2) // 2-second window
-                                        .unwrap_or(0));
-
-                                    if recent_events.contains(&event_key) {
-                                        continue;
-                                    }
-                                    recent_events.insert(event_key.clone());
-
-                                    // Clean up old events
-                                    if recent_events.len() > 100 {
-                                        recent_events.clear();
-                                    }
-
-                                    println!("Data file changed externally: {} (type: {})", file_name_str, file_type);
-
-                                    // Emit event to frontend with file type
-                                    if let Err(e) = app_handle.emit("data-file-changed", file_type) {
-                                        eprintln!("Failed to emit event: {}", e);
-                                    }
-                                }
-                            }
-                        }
-                    }
+        match rx.recv_timeout(wake.saturating_duration_since(Instant::now())) {
+            Ok(Ok(event)) => {
+                let now = Instant::now();
+                debouncer.push(&event, now);
+                for file_name in inbox::changed_files(&event) {
+                    inbox_debouncer.touch(file_name, now);
                 }
-                Err(e) => {
-                    eprintln!("Watch error: {}", e);
-                    break;
+            }
+            Ok(Err(e)) => return SessionEnd::Failed(format!("Watch error: {}", e)),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return SessionEnd::Failed("Watch error: event channel closed".to_string())
+            }
+        }
+
+        if dir_identity(data_dir) != Some(identity) {
+            return SessionEnd::DirectoryGone;
+        }
+
+        for file_name in debouncer.take_due(Instant::now()) {
+            deliver_change(app_handle, data_dir, &file_name, watcher_state, snapshots);
+        }
+
+        for file_name in inbox_debouncer.take_due(Instant::now()) {
+            import_inbox_file(app_handle, data_dir, &file_name);
+        }
+
+        // Replay changes held back while suspended once every lease is gone
+        for file_name in watcher_state.take_deferred() {
+            println!("Replaying change made while suspended: {}", file_name);
+            deliver_change(app_handle, data_dir, &file_name, watcher_state, snapshots);
+        }
+    }
+}
+
+/// Turn an inbox file into tasks and tell the frontend. The tasks reach it
+/// through the usual `data-file-changed` for active.json.
+fn import_inbox_file(app_handle: &AppHandle, data_dir: &Path, file_name: &str) {
+    let result = match inbox::import_file(data_dir, file_name) {
+        Ok(None) => return,
+        Ok(Some(mut import)) => {
+            println!(
+                "Imported {} task(s) from inbox file {}",
+                import.task_ids.len(),
+                file_name
+            );
+            if let Some(report) = import.quota.take() {
+                if let Err(e) = app_handle.emit("quota-enforced", report) {
+                    eprintln!("Failed to emit event: {}", e);
                 }
             }
+            app_handle.emit("inbox-imported", import)
+        }
+        Err(error) => {
+            eprintln!("Failed to import inbox file {}: {}", file_name, error);
+            let failure = InboxFailure {
+                file_name: file_name.to_string(),
+                error,
+            };
+            app_handle.emit("inbox-import-failed", failure)
         }
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed to emit event: {}", e);
     }
+}
+
+/// Emit `data-file-changed` for a settled change to `file_name`, unless it
+/// is the app's own write or changes nothing. While suspended the change is
+/// deferred until the last lease is released or expires.
+fn deliver_change(
+    app_handle: &AppHandle,
+    data_dir: &Path,
+    file_name: &str,
+    watcher_state: &WatcherState,
+    snapshots: &Snapshots,
+) {
+    let Some(file_type) = get_file_type(file_name) else {
+        return;
+    };
 
-    Ok(())
+    // Hold the change back while suspended (during DnD operations)
+    if watcher_state.is_suspended() {
+        println!(
+            "Deferring file change (watcher suspended): {} (type: {})",
+            file_name, file_type
+        );
+        watcher_state.defer(file_name);
+        return;
+    }
+
+    // Our own write: the content is what the app last wrote
+    let is_own_write = durable::current_revision(&data_dir.join(file_name))
+        .ok()
+        .flatten()
+        .is_some_and(|revision| watcher_state.is_self_write(file_name, &revision));
+    if is_own_write {
+        return;
+    }
+
+    let change = describe_change(data_dir, file_type, snapshots);
+    if change.diff.as_ref().is_some_and(|d| d.is_empty()) {
+        println!("Skipping file change (no differences): {}", file_name);
+        return;
+    }
+
+    println!(
+        "Data file changed externally: {} (type: {})",
+        file_name, file_type
+    );
+
+    // Emit event to frontend with what changed
+    if let Err(e) = app_handle.emit("data-file-changed", change) {
+        eprintln!("Failed to emit event: {}", e);
+    }
+}
+
+/// Describe a change to a data file relative to the version the app last
+/// saw. Without a usable previous version the diff is left out, which tells
+/// the frontend to reload the whole file.
+fn describe_change(data_dir: &Path, file_type: &'static str, snapshots: &Snapshots) -> FileChange {
+    let Some(data_file) = DataFileType::from_name(file_type) else {
+        return FileChange::reload(file_type);
+    };
+
+    let diff = snapshots
+        .get(data_file)
+        .and_then(|before| serde_json::from_str(&before).ok())
+        .and_then(|before| {
+            let content = fs::read_to_string(data_dir.join(data_file.filename())).ok()?;
+            let after = serde_json::from_str(&content).ok()?;
+            Some(diff::diff_documents(data_file, &before, &after))
+        });
+
+    FileChange {
+        file_type: file_type.to_string(),
+        diff,
+    }
 }
 
 /// Get file type from filename
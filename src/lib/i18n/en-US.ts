@@ -127,6 +127,9 @@ export default {
     tags: 'Tags',
     tagsPlaceholder: 'Separate with commas',
     dueDate: 'Due Date',
+    dueTime: 'Due Time',
+    dueTimeHint: 'Without a time the task is due at 18:00',
+    remindAt: 'Remind Me',
     thresholdDate: 'Defer Until',
     thresholdHint: 'Task hidden until this date',
     recurrence: 'Recurrence',
@@ -326,9 +329,29 @@ export default {
     unitBoundaryFlex: 'Unit Boundary Flexibility',
     unitBoundaryFlexDesc: 'Hours a task can extend beyond unit boundary',
     dueReminders: 'Due reminders',
-    dueRemindersDesc: 'Daily reminder for tasks due today or overdue (desktop only)',
+    dueRemindersDesc: 'Notify before tasks are due, with a morning digest and an overdue nudge (desktop only)',
+    reminderOffsets: 'Remind before due',
+    reminderOffsetsDesc: 'A due date without a time counts as due at 18:00',
+    remindBefore15m: '15 min',
+    remindBefore1h: '1 h',
+    remindBefore3h: '3 h',
+    remindBefore1d: '1 day',
+    digestTime: 'Morning digest',
+    digestTimeDesc: 'Tasks due today or overdue; clear the time to turn it off',
+    overdueNudgeTime: 'Overdue nudge',
+    overdueNudgeTimeDesc: 'A reminder of tasks past due; clear the time to turn it off',
     lowCompletionPrompt: 'Low-completion review',
     lowCompletionPromptDesc: 'When a 2-day period ends below 30%, prompt a quick review before rolling tasks over (off = auto-merge)',
+    quotaMode: 'Over-quota saves',
+    quotaModeDesc: 'What happens when a change (including from the CLI or inbox) puts too many tasks in a zone',
+    quotaModeReject: 'Reject',
+    quotaModeDemote: 'Demote',
+    quotaModeWarn: 'Warn',
+    unitLayout: 'Unit layout',
+    unitLayoutDesc: 'How the week is split into units; switching re-files existing tasks into the new units',
+    unitLayoutDefault: 'Sun-Mon · Tue-Wed · Thu-Fri · Sat review',
+    unitLayoutWorkweek: 'Mon-Tue · Wed-Thu · Fri · Sat · Sun review',
+    unitLayoutThreeDay: 'Sun-Tue · Wed-Fri · Sat review',
     hours: 'hours',
     minutes: 'min',
     days: 'days',
@@ -425,8 +448,11 @@ export default {
   },
 
   reminders: {
-    dueTitle: 'Tasks due today',
-    dueBody: 'You have {count} tasks due today or overdue',
+    snoozeTenMinutes: '10 min',
+    snoozeOneHour: '1 hour',
+    snoozeTomorrow: 'Tomorrow',
+    complete: 'Complete',
+    snoozeFailed: 'Could not snooze the reminder',
   },
 
   lowCompletion: {
@@ -554,6 +580,12 @@ export default {
     pomodoroComplete: 'Pomodoro complete! Take a break.',
     breakComplete: 'Break complete. Ready for next session.',
     dataLoaded: 'Data loaded',
+    tasksChangedExternally: '{count} task(s) changed externally',
+    autoReloadRestored: 'Auto-reload of external changes is working again',
+    inboxImported: 'Added {count} task(s) from inbox file {file}',
+    quotaDemoted: '{count} task(s) over the priority quota were moved down a level',
+    recurringCreated: 'Added the next instance of {count} recurring task(s)',
+    cycleRolledOver: 'Last period was under 30% done; {count} unfinished task(s) carried into this one',
     dataExported: 'Data exported',
     dataImported: 'Data imported',
     backupCreated: 'Backup created',
@@ -618,6 +650,17 @@ export default {
     taskNotFound: 'Task not found',
     saveFailed: 'Save failed',
     externalChangeIgnored: 'External data change detected but ignored due to active session. Please save and reload manually to avoid conflicts.',
+    backupCorrupted: '{count} backup(s) failed verification and may not be restorable',
+    dataRecovered: 'The {file} data file was corrupted and has been restored from the last good copy',
+    dataUnrecoverable: 'The {file} data file was corrupted and no good copy was found. It was kept as {name}',
+    writeConflict: 'Data was changed by another window or tool; merged with your changes',
+    mergeConflicts: 'External changes merged; {count} field(s) edited on both sides kept your version',
+    autoReloadDegraded: 'Auto-reload is unavailable; changes made outside the app won\'t show until it recovers',
+    inboxImportFailed: 'Could not import inbox file {file}',
+    inboxRejected: '{count} task(s) from inbox file {file} were left out because their zone is full',
+    quotaRejected: 'Not saved: the {priority} zone quota would be exceeded',
+    quotaWarning: 'The {priority} zone is over its quota',
+    recurringDeferred: '{count} recurring task(s) were held back because their zone is full',
   },
 
   // Today View
@@ -656,8 +699,8 @@ export default {
     },
     rhythm: {
       unitN: 'Unit {n}',
-      day1: 'Day 1 of 2',
-      day2: 'Day 2 — wrap up',
+      dayOf: 'Day {n} of {total}',
+      lastDay: 'Day {n} — wrap up',
       reviewDay: 'Weekly Review',
       reviewHint: 'Look back, prepare the next cycle',
     },
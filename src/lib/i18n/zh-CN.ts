@@ -127,6 +127,9 @@ export default {
     tags: '标签',
     tagsPlaceholder: '多个标签用逗号分隔',
     dueDate: '截止日期',
+    dueTime: '截止时间',
+    dueTimeHint: '不设时间则在当天 18:00 到期',
+    remindAt: '提醒时间',
     thresholdDate: '延后可见',
     thresholdHint: '任务在此日期前隐藏',
     recurrence: '重复',
@@ -326,9 +329,29 @@ export default {
     unitBoundaryFlex: '周期边界弹性',
     unitBoundaryFlexDesc: '任务可延迟或提前的小时数',
     dueReminders: '截止日提醒',
-    dueRemindersDesc: '每天提醒今天到期或已逾期的任务（仅桌面端）',
+    dueRemindersDesc: '任务到期前提醒，并发送晨间汇总和逾期提醒（仅桌面端）',
+    reminderOffsets: '提前提醒',
+    reminderOffsetsDesc: '没有具体时间的截止日按当天 18:00 到期计算',
+    remindBefore15m: '15 分钟',
+    remindBefore1h: '1 小时',
+    remindBefore3h: '3 小时',
+    remindBefore1d: '1 天',
+    digestTime: '晨间汇总',
+    digestTimeDesc: '今天到期或已逾期的任务；清空时间即关闭',
+    overdueNudgeTime: '逾期提醒',
+    overdueNudgeTimeDesc: '提醒已过截止时间的任务；清空时间即关闭',
     lowCompletionPrompt: '低完成度微复盘',
     lowCompletionPromptDesc: '某2天周期完成度低于30%时，先提示复盘再决定是否顺延（关闭则自动合并）',
+    quotaMode: '超配额保存',
+    quotaModeDesc: '修改（包括来自命令行或收件箱的修改）使某区任务过多时的处理方式',
+    quotaModeReject: '拒绝',
+    quotaModeDemote: '降级',
+    quotaModeWarn: '仅提醒',
+    unitLayout: '单元划分',
+    unitLayoutDesc: '一周如何划分为单元；切换后现有任务会归入新的单元',
+    unitLayoutDefault: '周日-周一 · 周二-周三 · 周四-周五 · 周六复盘',
+    unitLayoutWorkweek: '周一-周二 · 周三-周四 · 周五 · 周六 · 周日复盘',
+    unitLayoutThreeDay: '周日-周二 · 周三-周五 · 周六复盘',
     hours: '小时',
     minutes: '分钟',
     days: '天',
@@ -425,8 +448,11 @@ export default {
   },
 
   reminders: {
-    dueTitle: '今天有任务到期',
-    dueBody: '你有 {count} 个任务今天到期或已逾期',
+    snoozeTenMinutes: '10 分钟后',
+    snoozeOneHour: '1 小时后',
+    snoozeTomorrow: '明天',
+    complete: '完成',
+    snoozeFailed: '无法推迟提醒',
   },
 
   lowCompletion: {
@@ -552,6 +578,12 @@ export default {
     pomodoroComplete: '完成了一个番茄钟，休息一下吧',
     breakComplete: '休息结束，准备开始下一个番茄钟',
     dataLoaded: '数据已加载',
+    tasksChangedExternally: '{count} 个任务被外部修改',
+    autoReloadRestored: '外部修改的自动重新加载已恢复',
+    inboxImported: '已从收件箱文件 {file} 添加 {count} 个任务',
+    quotaDemoted: '{count} 个超出优先级配额的任务已降一级',
+    recurringCreated: '已为 {count} 个重复任务添加下一次实例',
+    cycleRolledOver: '上一周期完成度不足 30%，{count} 个未完成任务已顺延到本周期',
     dataExported: '数据已导出',
     dataImported: '数据已导入',
     backupCreated: '备份已创建',
@@ -616,6 +648,17 @@ export default {
     taskNotFound: '任务未找到',
     saveFailed: '保存失败',
     externalChangeIgnored: '检测到外部数据变更，但由于当前有活动会话已忽略。请手动保存并刷新以避免冲突。',
+    backupCorrupted: '{count} 个备份校验失败，可能无法恢复',
+    dataRecovered: '{file} 数据文件已损坏，已从最近的有效副本恢复',
+    dataUnrecoverable: '{file} 数据文件已损坏且未找到有效副本，原文件已保留为 {name}',
+    writeConflict: '数据已被其他窗口或工具修改，已与你的修改合并',
+    mergeConflicts: '已合并外部修改；{count} 个两边都改过的字段保留了你的版本',
+    autoReloadDegraded: '自动重新加载暂不可用，恢复前不会显示应用外的修改',
+    inboxImportFailed: '无法导入收件箱文件 {file}',
+    inboxRejected: '收件箱文件 {file} 中有 {count} 个任务因所在区已满而未添加',
+    quotaRejected: '未保存：将超出 {priority} 区配额',
+    quotaWarning: '{priority} 区已超出配额',
+    recurringDeferred: '{count} 个重复任务因所在区已满而暂缓添加',
   },
 
   // Today View
@@ -654,8 +697,8 @@ export default {
     },
     rhythm: {
       unitN: '单元 {n}',
-      day1: '第 1 天 / 共 2 天',
-      day2: '第 2 天 / 收尾',
+      dayOf: '第 {n} 天 / 共 {total} 天',
+      lastDay: '第 {n} 天 / 收尾',
       reviewDay: '周复盘日',
       reviewHint: '回望本周，预备下一轮',
     },
@@ -1,4 +1,6 @@
-import type { PomodoroState, PomodoroSession, Task } from '$lib/types';
+import type { PomodoroState, PomodoroSession, PomodoroSnapshot, PomodoroPhaseChange, Task } from '$lib/types';
+import { invokePomodoro, isTauri, onPomodoroEvents, type PomodoroCommand } from '$lib/utils/storage';
+import { getTasksStore } from './tasks.svelte';
 
 // Pomodoro state
 let state = $state<PomodoroState>('idle');
@@ -18,6 +20,10 @@ let longBreakDuration = $state(20);
 // History
 let sessions = $state<PomodoroSession[]>([]);
 
+// Under Tauri the timer runs in the backend and this store mirrors it; the
+// interval below only runs in the browser
+let backend = false;
+
 // Timer interval
 let timerInterval: ReturnType<typeof setInterval> | null = null;
 
@@ -45,8 +51,66 @@ export function initPomodoro(settings: {
   initAudio();
 }
 
+/**
+ * Hand the timer to the backend (Tauri only), picking up a session still in
+ * progress from before a reload or crash. Returns an unlisten function.
+ */
+export async function connectPomodoro(): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  backend = true;
+  const unlisten = await onPomodoroEvents(applySnapshot, handlePhaseChange);
+  await runCommand('pomodoro_status');
+  return unlisten;
+}
+
+async function runCommand(command: PomodoroCommand, args: Record<string, unknown> = {}): Promise<void> {
+  try {
+    applySnapshot(await invokePomodoro(command, args));
+  } catch (e) {
+    console.error(`Pomodoro command ${command} failed:`, e);
+  }
+}
+
+function applySnapshot(timer: PomodoroSnapshot): void {
+  state = timer.phase;
+  isRunning = timer.isRunning;
+  timeRemaining = timer.phase === 'idle' ? workDuration * 60 : timer.remainingSecs;
+  sessionCount = timer.sessionCount;
+  interruptionCount = timer.interruptions;
+  currentInterruptionReasons = timer.interruptionReasons;
+  activeTaskId = timer.taskId;
+
+  // Play tick sound for last 5 seconds
+  if (isRunning && timeRemaining <= 5 && timeRemaining > 0 && tickSound) {
+    tickSound.play().catch(() => {});
+  }
+}
+
+function handlePhaseChange(change: PomodoroPhaseChange): void {
+  applySnapshot(change.timer);
+
+  if (change.session) {
+    sessions = [...sessions, change.session];
+    // The backend has already counted it on the task
+    window.dispatchEvent(new CustomEvent('pomodoro-complete', {
+      detail: { taskId: change.session.taskId, recorded: true }
+    }));
+  }
+  if (change.finished) {
+    notifySessionComplete(change.from);
+  }
+}
+
 // Start pomodoro for a task
 export function startPomodoro(task: Task): void {
+  if (backend) {
+    activeTaskId = task.id;
+    void runCommand('start_pomodoro', { taskId: task.id });
+    return;
+  }
   activeTaskId = task.id;
   activeTask = task;
   state = 'work';
@@ -59,12 +123,20 @@ export function startPomodoro(task: Task): void {
 
 // Pause timer
 export function pausePomodoro(): void {
+  if (backend) {
+    void runCommand('pause_pomodoro');
+    return;
+  }
   isRunning = false;
   stopTimer();
 }
 
 // Resume timer
 export function resumePomodoro(): void {
+  if (backend) {
+    void runCommand('resume_pomodoro');
+    return;
+  }
   if (state !== 'idle') {
     isRunning = true;
     startTimer();
@@ -76,6 +148,10 @@ export function togglePomodoro(): void {
   if (state === 'idle') {
     return; // Can't toggle if no session is active
   }
+  if (backend) {
+    void runCommand('toggle_pomodoro');
+    return;
+  }
   if (isRunning) {
     pausePomodoro();
   } else {
@@ -85,6 +161,10 @@ export function togglePomodoro(): void {
 
 // Record an interruption during work session
 export function recordInterruption(reason?: string): void {
+  if (backend) {
+    void runCommand('record_interruption', { reason: reason ?? null });
+    return;
+  }
   if (state === 'work') {
     interruptionCount++;
     if (reason) {
@@ -96,6 +176,10 @@ export function recordInterruption(reason?: string): void {
 
 // Stop and reset
 export function stopPomodoro(): void {
+  if (backend) {
+    void runCommand('stop_pomodoro');
+    return;
+  }
   isRunning = false;
   stopTimer();
   state = 'idle';
@@ -106,6 +190,10 @@ export function stopPomodoro(): void {
 
 // Skip current session
 export function skipSession(): void {
+  if (backend) {
+    void runCommand('skip_pomodoro');
+    return;
+  }
   stopTimer();
   handleSessionComplete();
 }
@@ -163,7 +251,7 @@ function handleSessionComplete(): void {
   }
 
   // Notify user
-  notifySessionComplete();
+  notifySessionComplete(state);
 
   // Transition to next state
   if (state === 'work') {
@@ -184,7 +272,7 @@ function handleSessionComplete(): void {
   }
 }
 
-async function notifySessionComplete(): Promise<void> {
+async function notifySessionComplete(finished: PomodoroState): Promise<void> {
   // Play completion sound
   if (completeSound) {
     completeSound.play().catch(() => {});
@@ -194,8 +282,8 @@ async function notifySessionComplete(): Promise<void> {
   if (typeof window !== 'undefined' && '__TAURI__' in window) {
     try {
       const { sendNotification } = await import('@tauri-apps/plugin-notification');
-      const title = state === 'work' ? '专注完成!' : '休息结束!';
-      const body = state === 'work'
+      const title = finished === 'work' ? '专注完成!' : '休息结束!';
+      const body = finished === 'work'
         ? `完成了一个番茄钟，休息一下吧`
         : '休息结束，准备开始下一个番茄钟';
 
@@ -266,7 +354,12 @@ export function getPomodoroStore() {
     get timeRemaining() { return timeRemaining; },
     get formattedTime() { return formatTime(timeRemaining); },
     get activeTaskId() { return activeTaskId; },
-    get activeTask() { return activeTask; },
+    // The backend timer only knows the ID; look the task up so edits and
+    // reloads show through
+    get activeTask() {
+      return backend ? getTasksStore().appData.tasks.find(t => t.id === activeTaskId) ?? null : activeTask;
+    },
+    get isBackendTimer() { return backend; },
     get sessionCount() { return sessionCount; },
     get isRunning() { return isRunning; },
     get progress() { return getProgress(); },
@@ -207,6 +207,11 @@ export function getSettingsStore() {
     get density() { return settings.density; },
     get unitBoundaryFlexHours() { return settings.unitBoundaryFlexHours; },
     get dueReminders() { return settings.dueReminders; },
-    get lowCompletionPrompt() { return settings.lowCompletionPrompt; }
+    get reminderOffsets() { return settings.reminderOffsets; },
+    get digestTime() { return settings.digestTime; },
+    get overdueNudgeTime() { return settings.overdueNudgeTime; },
+    get lowCompletionPrompt() { return settings.lowCompletionPrompt; },
+    get quotaMode() { return settings.quotaMode; },
+    get unitLayout() { return settings.unitLayout; }
   };
 }
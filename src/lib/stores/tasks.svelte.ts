@@ -1,14 +1,14 @@
-import type { Task, Subtask, Priority, FilterState, UnitInfo, AppData, ActiveData, PomodoroHistoryData } from '$lib/types';
+import type { Task, Subtask, Priority, FilterState, UnitInfo, UnitLayout, AppData, ActiveData, PomodoroHistoryData } from '$lib/types';
 import { createEmptyTask, createDefaultAppData, isThresholdPassed, calculateEZoneAge, isActivePriority, isCountedPriority, isFuturePriority, isSustainedPriority, isOperablePriority, createSubtask, ACTIVE_PRIORITIES, isWithinRetentionPeriod } from '$lib/types';
-import { loadAppData, saveAppData, reloadFile, archiveTasks } from '$lib/utils/storage';
+import { loadAppData, saveAppData, mergeExternalChanges, archiveTasks, isWriteConflict, isQuotaExceeded, isTauri } from '$lib/utils/storage';
 import { applyHighlanderRule, canAddTask, validateQuota } from '$lib/utils/quota';
 import { createTaskFromInput } from '$lib/utils/parser';
-import { processRecurringTasks, createNextOccurrence } from '$lib/utils/recurrence';
+import { processRecurringTasks, createNextRuleOccurrence } from '$lib/utils/recurrence';
 import { evaluateCycle, rollUnfinishedIntoWindow } from '$lib/utils/cycleEngine';
-import { maybeNotifyDueTasks } from '$lib/utils/reminders';
-import { getCurrentUnit, isDateInUnit, isToday, isOverdue, isThisWeek, currentUnitStartLocal, parseISODate } from '$lib/utils/unitCalc';
+import { getCurrentUnit, isDateInUnit, isToday, isOverdue, isThisWeek, currentUnitStartLocal, parseISODate, setUnitLayout } from '$lib/utils/unitCalc';
 import { t } from '$lib/i18n';
 import { getGamificationStore } from './gamification.svelte';
+import { showToast } from './ui.svelte';
 
 // Main app state
 let appData = $state<AppData>(createDefaultAppData());
@@ -81,29 +81,27 @@ export async function initializeData(): Promise<void> {
     isLoading = true;
     lastError = null;
     appData = await loadAppData();
+    setUnitLayout(appData.settings.unitLayout);
+    currentUnit = getCurrentUnit();
 
-    // One-time normalization: unitStart was historically the creation day; make it
-    // the unit's start date so the field is canonical across the app. Idempotent.
-    let normalized = false;
-    appData.tasks = appData.tasks.map(task => {
-      if (!task.unitStart) return task;
-      const canonical = currentUnitStartLocal(parseISODate(task.unitStart));
-      if (canonical !== task.unitStart) {
-        normalized = true;
-        return { ...task, unitStart: canonical };
-      }
-      return task;
-    });
+    // unitStart was historically the creation day, and the unit layout may have
+    // changed since the last run; make it the unit's start date so the field is
+    // canonical across the app. Idempotent.
+    const normalized = relabelUnitStarts();
 
     // Cleanup old tasks (Cancelled > 2 days)
     cleanupOldTasks();
 
     // Evaluate the dynamic 2-day cycle: advance to the new period, and merge
     // (roll unfinished A-E tasks forward) when the prior period was under-completed.
-    const cycleResult = evaluateCycle(appData);
-
-    // Process recurring tasks
-    const newRecurringTasks = processRecurringTasks(appData.tasks.filter(t => isActivePriority(t.priority)));
+    // On desktop the backend does this at startup and each unit boundary.
+    const cycleResult = isTauri() ? { changed: false, merged: false } : evaluateCycle(appData);
+
+    // Process recurring tasks. On desktop the backend scheduler does this at
+    // startup and each midnight; doing it here too would race it.
+    const newRecurringTasks = isTauri()
+      ? []
+      : processRecurringTasks(appData.tasks.filter(t => isActivePriority(t.priority)));
     let dataChanged = newRecurringTasks.length > 0 || cycleResult.changed || normalized;
     if (dataChanged) {
       appData.tasks = [...appData.tasks, ...newRecurringTasks];
@@ -112,9 +110,6 @@ export async function initializeData(): Promise<void> {
     if (dataChanged) {
       await saveAppData(appData);
     }
-
-    // Fire-and-forget: daily due-task summary notification (desktop only)
-    maybeNotifyDueTasks(appData).catch(err => console.error('Due reminder failed:', err));
   } catch (error) {
     lastError = error instanceof Error ? error.message : 'Failed to load data';
     console.error('Failed to initialize data:', error);
@@ -123,6 +118,31 @@ export async function initializeData(): Promise<void> {
   }
 }
 
+// Point every task's unitStart at the start of its unit under the current
+// layout. Returns whether any task changed.
+function relabelUnitStarts(): boolean {
+  let changed = false;
+  appData.tasks = appData.tasks.map(task => {
+    if (!task.unitStart) return task;
+    const canonical = currentUnitStartLocal(parseISODate(task.unitStart));
+    if (canonical !== task.unitStart) {
+      changed = true;
+      return { ...task, unitStart: canonical };
+    }
+    return task;
+  });
+  return changed;
+}
+
+// Switch to a new unit layout: re-label every task's unitStart and show the
+// current unit. The caller saves the layout with the settings, which persists
+// the tasks too.
+export function applyUnitLayout(unitLayout: UnitLayout): void {
+  setUnitLayout(unitLayout);
+  relabelUnitStarts();
+  currentUnit = getCurrentUnit();
+}
+
 // Reload specific data file (called when file changes externally)
 export async function reloadData(fileType: string): Promise<void> {
   try {
@@ -131,11 +151,12 @@ export async function reloadData(fileType: string): Promise<void> {
       return;
     }
 
-    if (fileType !== 'active' && fileType !== 'archive' && fileType !== 'pomodoro_history') {
+    // Archive isn't held in memory; nothing to refresh
+    if (fileType !== 'active' && fileType !== 'pomodoro_history') {
       return;
     }
 
-    const data = await reloadFile(fileType);
+    const { data } = await mergeExternalChanges(fileType, appData);
     if (!data) return;
 
     switch (fileType) {
@@ -153,6 +174,10 @@ export async function reloadData(fileType: string): Promise<void> {
           cycleState: activeData.cycleState ?? appData.cycleState,
           cycleHistory: activeData.cycleHistory ?? appData.cycleHistory
         };
+        // The layout may have been changed outside the app (set_unit_layout)
+        if (setUnitLayout(appData.settings.unitLayout)) {
+          currentUnit = getCurrentUnit();
+        }
         break;
       }
       case 'pomodoro_history': {
@@ -174,6 +199,21 @@ async function persist(filesToSave: ('active' | 'pomodoro_history')[] = ['active
   try {
     await saveAppData(appData, filesToSave);
   } catch (error) {
+    if (isWriteConflict(error)) {
+      // Another writer got there first: merge its changes instead of clobbering them
+      showToast(t('error.writeConflict'), 'warning');
+      for (const fileType of filesToSave) {
+        await reloadData(fileType);
+      }
+      return;
+    }
+    if (isQuotaExceeded(error)) {
+      // quotaMode is 'reject': drop the change and go back to what's on disk
+      const priorities = error.violations.map(v => v.priority).join(', ');
+      showToast(t('error.quotaRejected', { priority: priorities }), 'error');
+      appData = await loadAppData();
+      return;
+    }
     lastError = error instanceof Error ? error.message : 'Failed to save data';
     console.error('Failed to persist data:', error);
   }
@@ -265,8 +305,8 @@ export async function completeTask(taskId: string): Promise<void> {
   let nextRecurringTask: Task | null = null;
 
   // Create next occurrence before modifying the task
-  if (taskToComplete?.recurrence?.pattern && taskToComplete.dueDate) {
-    nextRecurringTask = createNextOccurrence(taskToComplete);
+  if (taskToComplete?.recurrence && taskToComplete.dueDate) {
+    nextRecurringTask = await createNextRuleOccurrence(taskToComplete);
   }
 
   // Record task completion for gamification (before modifying priority)
@@ -289,17 +329,40 @@ export async function completeTask(taskId: string): Promise<void> {
     return task;
   });
 
-  // Add next recurring task
   if (nextRecurringTask) {
-    const quotaError = nextRecurringTask.priority === 'A'
-      ? null
-      : validateQuota(appData.tasks.filter(t => isActivePriority(t.priority)), nextRecurringTask.priority);
-    if (!quotaError) {
-      if (nextRecurringTask.priority === 'A') {
-        appData.tasks = applyHighlanderRule(appData.tasks, nextRecurringTask);
-      }
-      appData.tasks = [...appData.tasks, nextRecurringTask];
+    addRecurringTask(nextRecurringTask);
+  }
+
+  await persist();
+}
+
+// Add the next occurrence of a completed recurring task, if its zone has room
+function addRecurringTask(nextRecurringTask: Task): void {
+  const quotaError = nextRecurringTask.priority === 'A'
+    ? null
+    : validateQuota(appData.tasks.filter(t => isActivePriority(t.priority)), nextRecurringTask.priority);
+  if (!quotaError) {
+    if (nextRecurringTask.priority === 'A') {
+      appData.tasks = applyHighlanderRule(appData.tasks, nextRecurringTask);
     }
+    appData.tasks = [...appData.tasks, nextRecurringTask];
+  }
+}
+
+// A task the backend completed from its reminder notification: pick up the
+// completion from disk, then record it and add the next occurrence as
+// completeTask does. `task` is the task as it was before completion.
+export async function completedFromReminder(task: Task): Promise<void> {
+  await reloadData('active');
+
+  const gamification = getGamificationStore();
+  gamification.recordTaskCompletion(task);
+
+  const nextRecurringTask = task.recurrence && task.dueDate
+    ? await createNextRuleOccurrence(task)
+    : null;
+  if (nextRecurringTask) {
+    addRecurringTask(nextRecurringTask);
   }
 
   await persist();
@@ -716,7 +779,14 @@ export async function changePriority(taskId: string, newPriority: Priority, skip
   return { success: true };
 }
 
-export async function incrementPomodoro(taskId: string): Promise<void> {
+// `recorded`: the backend timer has already counted it in active.json, so only
+// gamification is left to do
+export async function incrementPomodoro(taskId: string, recorded = false): Promise<void> {
+  if (recorded) {
+    getGamificationStore().recordPomodoro();
+    return;
+  }
+
   appData.tasks = appData.tasks.map(task => {
     if (task.id === taskId) {
       return {
@@ -1,4 +1,4 @@
-import { isCompletedInCurrentUnit, getUnitRetentionRemaining, currentUnitStartLocal } from '$lib/utils/unitCalc';
+import { isCompletedInCurrentUnit, getUnitRetentionRemaining, currentUnitStartLocal, DEFAULT_UNIT_LAYOUT } from '$lib/utils/unitCalc';
 
 // Priority levels
 // A-E are work priorities with quotas, F is the Idea Pool (unlimited)
@@ -22,6 +22,12 @@ export interface Recurrence {
   pattern: RecurrencePattern;
   // For patterns like mon,wed,fri or 1m@15
   customPattern?: string;
+  // NEW: RFC 5545 rule (FREQ=MONTHLY;BYDAY=2TU), evaluated by the backend.
+  // Tasks with a short pattern get one migrated from it on startup.
+  rrule?: string;
+  // Shared by every instance of a recurring task; the backend scheduler uses
+  // it to tell whether an occurrence already exists
+  seriesId?: string;
   nextDue: string | null;
 }
 
@@ -49,6 +55,10 @@ export interface Task {
   contexts: string[];
   customTags: string[];
   dueDate: string | null;
+  // Local HH:MM the task is due on dueDate; without it, the end of the day
+  dueTime?: string | null;
+  // ISO timestamp to remind of the task at; set by hand or by snoozing a reminder
+  remindAt?: string | null;
   // NEW: Threshold Date - task not visible until this date
   thresholdDate: string | null;
   recurrence: Recurrence | null;
@@ -173,17 +183,199 @@ export interface Settings {
   unitBoundaryFlexHours: number;
   // NEW: UI density mode — 'comfortable' (default) | 'compact' (denser layout)
   density: 'comfortable' | 'compact';
-  // NEW: send a daily summary notification for tasks due today / overdue
+  // NEW: send due-date reminders (backend, desktop only)
   dueReminders: boolean;
+  // Minutes before a task is due to remind of it; a date-only due date is due at 18:00
+  reminderOffsets: number[];
+  // Local HH:MM of the digest of tasks due today or overdue; null = off
+  digestTime: string | null;
+  // Local HH:MM of the nudge about tasks past due; null = off
+  overdueNudgeTime: string | null;
   // NEW: when a 2-day period ends under-completed, prompt a micro-review instead
   // of silently merging into the next period
   lowCompletionPrompt: boolean;
+  // NEW: how many backups backup_data keeps (grandfather-father-son tiers)
+  backupRetention: BackupRetention;
+  // NEW: write backups as a single compressed .zip (with a checksum manifest)
+  // instead of a directory of loose JSON files
+  compressBackups: boolean;
+  // NEW: what the backend does with a save that goes over a priority quota
+  quotaMode: QuotaMode;
+  // NEW: how the week is split into units (see UnitLayout)
+  unitLayout: UnitLayout;
+}
+
+export type Weekday = 'Sun' | 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat';
+
+// Days are taken in order from weekStart, skipping the review day, and cut
+// into units of unitLengths days. Lengths must add up to the days outside the
+// review day, and the review day can't fall inside a unit. Changing the
+// layout re-labels every task's unitStart.
+export interface UnitLayout {
+  weekStart: Weekday;
+  unitLengths: number[];
+  reviewDay: Weekday | null;  // null for a week without a review day
+}
+
+// 'reject' refuses the save, 'demote' keeps the most recently prioritised tasks
+// and moves the rest down a level (Highlander rule), 'warn' saves and reports
+export type QuotaMode = 'reject' | 'demote' | 'warn';
+
+// Backup retention policy. A backup is kept if any tier selects it: the last
+// `keepLast` backups, plus the newest backup of each of the last N days/weeks/months.
+export interface BackupRetention {
+  keepLast: number;
+  daily: number;
+  weekly: number;
+  monthly: number;
+}
+
+// A backup as reported by list_backups / prune_backups
+export interface BackupInfo {
+  id: string;
+  format: 'directory' | 'zip';
+  createdAt: string;
+  sizeBytes: number;
+  files: string[];
+}
+
+export interface RestoreReport {
+  backupId: string;
+  safetyBackupId: string; // snapshot of the pre-restore state
+  restored: string[];     // file types replaced from the backup
+}
+
+// Result of verify_backup: each file checked against the manifest and parsed
+export interface FileVerification {
+  name: string;
+  ok: boolean;
+  problems: string[];
+  taskCount: number | null;
+  sessionCount: number | null;
+}
+
+export interface BackupVerification {
+  backupId: string;
+  ok: boolean;
+  hasManifest: boolean;
+  files: FileVerification[];
+  problems: string[];
+}
+
+// Returned by read_data_file when a corrupt file had to be replaced.
+// recoveredFrom is null when no intact copy existed and the file was dropped.
+export interface RecoveryReport {
+  fileType: string;
+  error: string;
+  quarantinedAs: string; // name the broken file was moved to
+  recoveredFrom:
+    | { kind: 'tempFile'; fileName: string }
+    | { kind: 'backup'; backupId: string }
+    | null;
+}
+
+// What changed in a data file, relative to the version this window last saw.
+// IDs are task IDs (session IDs for pomodoro_history).
+export interface DataDiff {
+  added: string[];
+  removed: string[];
+  modified: string[];
+  settingsChanged: string[];
+  otherChanged: string[]; // other top-level keys, e.g. reviews, cycleState
+}
+
+// Payload of the data-file-changed event. diff is null when there was
+// nothing to compare against: treat the whole file as changed.
+export interface FileChange {
+  fileType: string;
+  diff: DataDiff | null;
+}
+
+// Health of the backend file watcher (watcher_status, watcher-degraded and
+// watcher-recovered). Auto-reload is down unless health is starting/running.
+export type WatcherHealth = 'starting' | 'running' | 'restarting' | 'waitingForDirectory' | 'stopped';
+
+export interface WatcherStatus {
+  health: WatcherHealth;
+  dataDir: string | null;
+  restarts: number;
+  lastError: string | null;
+  runningSince: string | null;
+  nextRetry: string | null;
+}
+
+// A file dropped into the inbox/ folder of the data directory was turned into
+// tasks (inbox-imported) or could not be parsed (inbox-import-failed).
+// rejected counts tasks left out because their priority quota is full.
+export interface InboxImport {
+  fileName: string;
+  taskIds: string[];
+  rejected: number;
+  movedTo: string;
+}
+
+// Payload of recurring-tasks-created: instances the backend scheduler added.
+// deferred counts instances held back because their priority quota is full.
+export interface RecurringTasksCreated {
+  taskIds: string[];
+  deferred: number;
+}
+
+export interface InboxFailure {
+  fileName: string;
+  error: string;
+}
+
+// A save or inbox import that went over a priority quota (quota-enforced event,
+// or the `violations` of a quotaExceeded write error). taskIds are the tasks
+// that don't fit.
+export interface QuotaViolation {
+  priority: Priority;
+  quota: number;
+  count: number;
+  taskIds: string[];
+}
+
+export interface QuotaDemotion {
+  taskId: string;
+  from: Priority;
+  to: Priority;
+}
+
+export interface QuotaReport {
+  mode: QuotaMode;
+  violations: QuotaViolation[];
+  demotions: QuotaDemotion[];
+}
+
+// Returned by quota_status; quota and remaining are null for unlimited priorities
+export interface QuotaStatus {
+  mode: QuotaMode;
+  priorities: { priority: Priority; used: number; quota: number | null; remaining: number | null }[];
+}
+
+// A field changed differently in this window and on disk since the last sync.
+// itemId is null for top-level keys (e.g. settings); field is null when one
+// side deleted the whole task/session. The merged result keeps `ours`.
+export interface MergeConflict {
+  itemId: string | null;
+  field: string | null;
+  base: unknown;
+  ours: unknown;
+  theirs: unknown;
+}
+
+export interface PruneReport {
+  policy: BackupRetention;
+  dryRun: boolean;
+  kept: BackupInfo[];
+  removed: BackupInfo[];
 }
 
 // Dynamic 2-day cycle state. The active work window normally equals the calendar
 // unit; when a period ends with low (priority-weighted) completion the next
 // period is flagged as a continued ("merged") window and the unfinished A-E
-// tasks roll into it. Saturday (review day) never participates.
+// tasks roll into it. The review day never participates.
 export interface CycleState {
   anchorStart: string;        // local YYYY-MM-DD — start of the current 2-day window
   windowEnd: string;          // local YYYY-MM-DD — inclusive end of the current window
@@ -202,6 +394,22 @@ export interface CycleHistoryEntry {
   merged: boolean;            // whether this low score triggered a merge into the next period
 }
 
+// Payload of cycle-advanced and result of evaluate_cycle: the backend moved
+// the cycle into a new window, scoring the period that ended
+export interface CycleAdvance {
+  state: CycleState;
+  scored: CycleHistoryEntry | null;
+  rolledTaskIds: string[];   // unfinished A-E tasks carried into the new window
+}
+
+// A unit's inclusive date range as computed by the backend (get_unit)
+export interface UnitRange {
+  number: number;            // 1-3; 0 for the review day
+  start: string;             // local YYYY-MM-DD
+  end: string;
+  isReviewDay: boolean;
+}
+
 // Active data file structure (hot data)
 export interface ActiveData {
   version: string;
@@ -382,6 +590,47 @@ export type ViewMode = 'today' | 'kanban' | 'list' | 'calendar';
 // Pomodoro state
 export type PomodoroState = 'idle' | 'work' | 'shortBreak' | 'longBreak';
 
+// Backend pomodoro timer (src-tauri/src/pomodoro.rs), sent with every command
+// result and as the `pomodoro-tick` event
+export interface PomodoroSnapshot {
+  phase: PomodoroState;
+  taskId: string | null;
+  isRunning: boolean;
+  remainingSecs: number;
+  totalSecs: number;
+  sessionCount: number;
+  interruptions: number;
+  interruptionReasons: string[];
+}
+
+// `pomodoro-phase` event: the timer moved from one phase to another
+export interface PomodoroPhaseChange {
+  from: PomodoroState;
+  to: PomodoroState;
+  finished: boolean; // ran out or was skipped, rather than stopped
+  session: PomodoroSession | null; // recorded to pomodoro_history.json by the backend
+  timer: PomodoroSnapshot;
+}
+
+// `task-reminder` event: a reminder for one task, shown as an in-app card with
+// snooze and complete buttons (src-tauri/src/reminders.rs)
+export interface TaskReminder {
+  taskId: string;
+  title: string;
+  body: string;
+}
+
+export type ReminderSnooze = 'tenMinutes' | 'oneHour' | 'tomorrow';
+
+// `reminder-action` event: a button was pressed on the notification itself
+// (Linux only). A snooze or completion is already written to active.json.
+export interface ReminderAction {
+  taskId: string;
+  action: ReminderSnooze | 'complete';
+  // Set when the backend completed the task: the task as it was before
+  task?: Task;
+}
+
 // Create empty task
 export function createEmptyTask(priority: Priority = 'F'): Task {
   const now = new Date().toISOString();
@@ -423,7 +672,14 @@ export function createDefaultSettings(): Settings {
     unitBoundaryFlexHours: 12, // Default: half day flexibility
     density: 'comfortable',
     dueReminders: true,
-    lowCompletionPrompt: true
+    reminderOffsets: [60, 1440],
+    digestTime: '08:30',
+    overdueNudgeTime: '18:30',
+    lowCompletionPrompt: true,
+    backupRetention: { keepLast: 10, daily: 7, weekly: 4, monthly: 6 },
+    compressBackups: false,
+    quotaMode: 'demote',
+    unitLayout: { ...DEFAULT_UNIT_LAYOUT, unitLengths: [...DEFAULT_UNIT_LAYOUT.unitLengths] }
   };
 }
 
@@ -1,7 +1,7 @@
 import type { Task, Priority, Recurrence, RecurrencePattern } from '$lib/types';
 import { createEmptyTask } from '$lib/types';
 
-interface ParsedTask {
+export interface ParsedTask {
   content: string;
   priority: Priority;
   projects: string[];
@@ -21,10 +21,14 @@ interface ParsedTask {
  * - !A !B !C !D !E !F !N !S for priority (or 【A】…【S】 with Chinese full-width brackets)
  * - ~2024-12-01 or ~tomorrow or ~today for due date
  * - thr:2024-12-01 or thr:+3d for threshold date
- * - rec:1d rec:1w rec:mon,wed,fri rec:1m@15 for recurrence
+ * - rec:1d rec:1w rec:mon,wed,fri rec:1m@15 rec:FREQ=MONTHLY;BYDAY=2TU for recurrence
  * - 🍅3 or p3 for estimated pomodoros
+ *
+ * Relative dates resolve against `now`. The Rust port (src-tauri/src/parser.rs)
+ * and this function are both checked against parser.vectors.json
+ * (`npm run test:parser`); keep them in step when changing the syntax.
  */
-export function parseTaskInput(input: string): ParsedTask {
+export function parseTaskInput(input: string, now: Date = new Date()): ParsedTask {
   let content = input.trim();
   let priority: Priority = 'F';
   const projects: string[] = [];
@@ -95,7 +99,7 @@ export function parseTaskInput(input: string): ParsedTask {
   // Extract threshold date (thr:date)
   const thresholdMatch = content.match(/thr:(\S+)/i);
   if (thresholdMatch) {
-    thresholdDate = parseDateString(thresholdMatch[1]);
+    thresholdDate = parseDateString(thresholdMatch[1], now);
     content = content.replace(/thr:\S+/gi, '').trim();
   }
 
@@ -109,7 +113,7 @@ export function parseTaskInput(input: string): ParsedTask {
   // Extract due date (~date)
   const dueDateMatch = content.match(/~(\S+)/);
   if (dueDateMatch) {
-    dueDate = parseDateString(dueDateMatch[1]);
+    dueDate = parseDateString(dueDateMatch[1], now);
     content = content.replace(/~\S+/g, '').trim();
   }
 
@@ -132,8 +136,8 @@ export function parseTaskInput(input: string): ParsedTask {
 /**
  * Parse relative or absolute date string
  */
-function parseDateString(dateStr: string): string | null {
-  const today = new Date();
+function parseDateString(dateStr: string, now: Date): string | null {
+  const today = new Date(now);
   today.setHours(0, 0, 0, 0);
 
   const lowerStr = dateStr.toLowerCase();
@@ -249,6 +253,15 @@ function parseRecurrence(patternStr: string): Recurrence | null {
     };
   }
 
+  // RFC 5545 rule: FREQ=MONTHLY;BYDAY=2TU (evaluated by the backend)
+  if (lowerStr.startsWith('freq=')) {
+    return {
+      pattern: null,
+      rrule: patternStr.toUpperCase(),
+      nextDue: null
+    };
+  }
+
   return null;
 }
 
@@ -1,5 +1,6 @@
 import type { Task, RecurrencePattern } from '$lib/types';
 import { formatDateISO, parseISODate } from './unitCalc';
+import { isTauri, nextOccurrences } from './storage';
 
 /**
  * Calculate next due date based on recurrence pattern
@@ -49,8 +50,68 @@ export function createNextOccurrence(task: Task): Task | null {
   const nextDue = calculateNextDue(task.dueDate, task.recurrence.pattern);
   if (!nextDue) return null;
 
+  return createOccurrence(task, nextDue);
+}
+
+/**
+ * Rules a task may recur by, in the order recurrence.rs `rule_of` tries them:
+ * its rrule, then its customPattern (which pattern only approximates: `1m@15`
+ * is stored with pattern `1m`), then its fixed pattern
+ */
+function recurrenceRules(task: Task): string[] {
+  const { rrule, customPattern, pattern } = task.recurrence ?? {};
+  if (rrule) return [rrule];
+  const custom = customPattern?.trim();
+  return [
+    custom && /^freq=/i.test(custom) ? custom.toUpperCase() : custom,
+    pattern
+  ].filter((rule): rule is string => !!rule);
+}
+
+/**
+ * Pin DTSTART to `start` so COUNT keeps counting from the first instance
+ * instead of restarting at every instance's due date (as in scheduler.rs)
+ */
+function pinStart(rule: string, start: string): string {
+  if (!/(^|[;:\n])COUNT=/i.test(rule) || /DTSTART/i.test(rule)) return rule;
+  return `DTSTART:${start.replace(/-/g, '')}\n${rule}`;
+}
+
+/**
+ * Create the next occurrence of a recurring task when it is completed, using
+ * the backend's recurrence engine. A pinned rule is stored on the occurrence.
+ * Without the backend (web build) only the short pattern can be followed.
+ */
+export async function createNextRuleOccurrence(task: Task): Promise<Task | null> {
+  if (!task.dueDate) {
+    return null;
+  }
+  if (!isTauri()) {
+    return createNextOccurrence(task);
+  }
+
+  for (const candidate of recurrenceRules(task)) {
+    const rule = pinStart(candidate, task.dueDate);
+    try {
+      const [nextDue] = await nextOccurrences(rule, task.dueDate, 1);
+      if (!nextDue) return null;
+      return createOccurrence(task, nextDue, rule === candidate ? undefined : rule);
+    } catch (error) {
+      console.error('Failed to evaluate recurrence rule:', rule, error);
+    }
+  }
+  return null;
+}
+
+function createOccurrence(task: Task, nextDue: string, pinnedRule?: string): Task {
+  // A reminder keeps its distance from the due date (as in scheduler.rs)
+  const shift = task.dueDate ? parseISODate(nextDue).getTime() - parseISODate(task.dueDate).getTime() : 0;
+  const remindAt = task.remindAt && task.dueDate
+    ? new Date(new Date(task.remindAt).getTime() + shift).toISOString()
+    : null;
   return {
     ...task,
+    remindAt,
     id: crypto.randomUUID(),
     completed: false,
     completedAt: null,
@@ -59,6 +120,8 @@ export function createNextOccurrence(task: Task): Task | null {
     dueDate: nextDue,
     recurrence: {
       ...task.recurrence,
+      rrule: pinnedRule ?? task.recurrence?.rrule,
+      seriesId: task.recurrence?.seriesId ?? task.id,
       nextDue
     },
     pomodoros: {
@@ -1,4 +1,4 @@
-import type { AppData, ActiveData, ArchiveData, PomodoroHistoryData, Task } from '$lib/types';
+import type { AppData, ActiveData, ArchiveData, BackupInfo, BackupRetention, BackupVerification, CycleAdvance, FileChange, InboxFailure, InboxImport, MergeConflict, PomodoroHistoryData, PomodoroPhaseChange, PomodoroSnapshot, PruneReport, QuotaReport, QuotaStatus, QuotaViolation, RecoveryReport, RecurringTasksCreated, ReminderAction, ReminderSnooze, RestoreReport, Task, TaskReminder, UnitRange, WatcherStatus } from '$lib/types';
 import {
   createDefaultAppData,
   createDefaultActiveData,
@@ -22,12 +22,21 @@ const STORAGE_KEYS = {
 // Anti-deadlock: Use a counter instead of boolean to handle concurrent saves
 let saveOperationCount = 0;
 let lastSaveCompleteTime = 0;
-const SAVE_COOLDOWN_MS = 2000; // Ignore file changes within this window after save
+const SAVE_COOLDOWN_MS = 2000; // Still report "saving" this long after a save finishes
 
 // Debounce timeout for file watcher
 let fileWatcherDebounceTimer: ReturnType<typeof setTimeout> | null = null;
 const FILE_WATCHER_DEBOUNCE_MS = 500;
 
+// Corrupt-file recoveries reported by read_data_file. Held until a listener
+// is registered, since the initial load runs before the UI is ready.
+let pendingRecoveries: RecoveryReport[] = [];
+let recoveryListener: ((report: RecoveryReport) => void) | null = null;
+
+// Revision of each data file as last read or written by this window. Sent with
+// every write so the backend can refuse to overwrite changes made elsewhere.
+const fileRevisions: Partial<Record<DataFileType, string | null>> = {};
+
 /**
  * Mark that a save operation is starting
  */
@@ -112,13 +121,81 @@ export async function savePomodoroHistoryData(data: PomodoroHistoryData): Promis
   }
 }
 
+/**
+ * Read a data file through the backend, passing on any corruption recovery
+ */
+async function readDataFile(fileType: DataFileType): Promise<string | null> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  const result = await invoke<{
+    content: string | null;
+    revision: string | null;
+    recovery: RecoveryReport | null;
+  }>('read_data_file', { fileType });
+  fileRevisions[fileType] = result.revision;
+
+  if (result.recovery) {
+    console.error('Recovered corrupt data file:', result.recovery);
+    if (recoveryListener) {
+      recoveryListener(result.recovery);
+    } else {
+      pendingRecoveries.push(result.recovery);
+    }
+  }
+
+  return result.content;
+}
+
+/**
+ * Write a data file through the backend, guarded by the last known revision
+ */
+async function writeDataFile(fileType: DataFileType, content: string): Promise<void> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  fileRevisions[fileType] = await invoke<string>('atomic_write_file', {
+    fileType,
+    content,
+    expectedRevision: fileRevisions[fileType] ?? null
+  });
+}
+
+/**
+ * Whether a save failed because the file was changed by another writer
+ * (CLI, another window) since this window last read it
+ */
+export function isWriteConflict(error: unknown): boolean {
+  return typeof error === 'object' && error !== null && (error as { kind?: string }).kind === 'conflict';
+}
+
+/**
+ * Whether a save was refused because it went over a priority quota
+ * (settings.quotaMode = 'reject')
+ */
+export function isQuotaExceeded(error: unknown): error is { kind: 'quotaExceeded'; violations: QuotaViolation[] } {
+  return typeof error === 'object' && error !== null && (error as { kind?: string }).kind === 'quotaExceeded';
+}
+
+/**
+ * Subscribe to corrupt-file recoveries. Recoveries from before the call
+ * (e.g. during the initial load) are delivered immediately.
+ */
+export function onDataFileRecovered(callback: (report: RecoveryReport) => void): () => void {
+  recoveryListener = callback;
+  const pending = pendingRecoveries;
+  pendingRecoveries = [];
+  pending.forEach(callback);
+
+  return () => {
+    if (recoveryListener === callback) {
+      recoveryListener = null;
+    }
+  };
+}
+
 /**
  * Load specific file type
  */
 export async function loadFileData<T>(fileType: DataFileType): Promise<T | null> {
   if (isTauri()) {
-    const { invoke } = await import('@tauri-apps/api/core');
-    const content = await invoke<string | null>('read_data_file', { fileType });
+    const content = await readDataFile(fileType);
 
     if (content) {
       return JSON.parse(content) as T;
@@ -155,6 +232,286 @@ export async function reloadFile(
   }
 }
 
+/**
+ * Merge an externally changed file into the in-memory state instead of
+ * replacing it: the backend three-way merges per task against the last
+ * version this window saw. A merge that keeps local edits is saved back.
+ */
+export async function mergeExternalChanges(
+  fileType: PersistedFileType,
+  data: AppData
+): Promise<{ data: ActiveData | PomodoroHistoryData | null; conflicts: MergeConflict[] }> {
+  if (!isTauri()) {
+    return { data: await reloadFile(fileType), conflicts: [] };
+  }
+
+  const { invoke } = await import('@tauri-apps/api/core');
+  const local = fileType === 'active' ? toActiveData(data) : toPomodoroHistoryData(data);
+  const result = await invoke<{
+    content: string;
+    revision: string | null;
+    needsSave: boolean;
+    conflicts: MergeConflict[];
+  }>('merge_data_file', { fileType, localContent: JSON.stringify(local, null, 2) });
+
+  fileRevisions[fileType] = result.revision;
+  const merged = JSON.parse(result.content) as ActiveData | PomodoroHistoryData;
+  if (result.needsSave) {
+    await saveFileTauri(fileType, merged);
+  }
+
+  return { data: merged, conflicts: result.conflicts };
+}
+
+/**
+ * Listen for conflicts the backend could not auto-resolve while merging
+ */
+export async function onMergeConflicts(
+  callback: (fileType: string, conflicts: MergeConflict[]) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return await listen<{ fileType: string; conflicts: MergeConflict[] }>('data-merge-conflicts', (event) => {
+    callback(event.payload.fileType, event.payload.conflicts);
+  });
+}
+
+/**
+ * Current health of the backend file watcher (desktop only)
+ */
+export async function getWatcherStatus(): Promise<WatcherStatus | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<WatcherStatus>('watcher_status');
+}
+
+/**
+ * Listen for auto-reload going down (degraded = true) and coming back
+ */
+export async function onWatcherHealthChanged(
+  callback: (status: WatcherStatus, degraded: boolean) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlistenDegraded = await listen<WatcherStatus>('watcher-degraded', (event) => {
+    callback(event.payload, true);
+  });
+  const unlistenRecovered = await listen<WatcherStatus>('watcher-recovered', (event) => {
+    callback(event.payload, false);
+  });
+  return () => {
+    unlistenDegraded();
+    unlistenRecovered();
+  };
+}
+
+/**
+ * Listen for files in the inbox/ drop folder being imported as tasks. The new
+ * tasks themselves arrive through the file watcher like any external change.
+ */
+export async function onInboxProcessed(
+  onImported: (result: InboxImport) => void,
+  onFailed: (failure: InboxFailure) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlistenImported = await listen<InboxImport>('inbox-imported', (event) => {
+    onImported(event.payload);
+  });
+  const unlistenFailed = await listen<InboxFailure>('inbox-import-failed', (event) => {
+    onFailed(event.payload);
+  });
+  return () => {
+    unlistenImported();
+    unlistenFailed();
+  };
+}
+
+/**
+ * Used and remaining capacity per priority, as enforced by the backend
+ */
+export async function getQuotaStatus(): Promise<QuotaStatus | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<QuotaStatus>('quota_status');
+}
+
+/**
+ * The next `n` dates (YYYY-MM-DD) after `from` of an RRULE or short
+ * recurrence pattern. Empty outside Tauri.
+ */
+export async function nextOccurrences(rule: string, from: string, n: number): Promise<string[]> {
+  if (!isTauri()) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<string[]>('next_occurrences', { rule, from, n });
+}
+
+/**
+ * Listen for the backend demoting tasks or warning about a save or inbox
+ * import that went over a priority quota
+ */
+export async function onQuotaEnforced(callback: (report: QuotaReport) => void): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return await listen<QuotaReport>('quota-enforced', (event) => {
+    callback(event.payload);
+  });
+}
+
+/**
+ * The unit a date (YYYY-MM-DD, default today) belongs to, from the backend
+ */
+export async function getUnit(date?: string): Promise<UnitRange | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<UnitRange>('get_unit', { date: date ?? null });
+}
+
+/**
+ * Priority-weighted A-E completion of the 2-day period starting at
+ * `periodStart`, or null if nothing was planned in it
+ */
+export async function getPeriodCompletion(periodStart: string): Promise<number | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<number | null>('period_completion', { periodStart });
+}
+
+/**
+ * Advance the 2-day cycle to today's unit now rather than at the next
+ * midnight. Null if it was already there (or outside Tauri).
+ */
+export async function evaluateCycleNow(): Promise<CycleAdvance | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<CycleAdvance | null>('evaluate_cycle');
+}
+
+/**
+ * Listen for the backend advancing the 2-day cycle at a unit boundary. The
+ * updated cycle state and rolled tasks arrive through the file watcher.
+ */
+export async function onCycleAdvanced(callback: (advance: CycleAdvance) => void): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return await listen<CycleAdvance>('cycle-advanced', (event) => {
+    callback(event.payload);
+  });
+}
+
+/**
+ * Listen for the backend scheduler adding recurring task instances. The tasks
+ * themselves arrive through the file watcher like any external change.
+ */
+export async function onRecurringTasksCreated(
+  callback: (result: RecurringTasksCreated) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return await listen<RecurringTasksCreated>('recurring-tasks-created', (event) => {
+    callback(event.payload);
+  });
+}
+
+export type PomodoroCommand =
+  | 'pomodoro_status'
+  | 'start_pomodoro'
+  | 'pause_pomodoro'
+  | 'resume_pomodoro'
+  | 'toggle_pomodoro'
+  | 'stop_pomodoro'
+  | 'skip_pomodoro'
+  | 'record_interruption';
+
+/**
+ * Drive the backend pomodoro timer (Tauri only). Returns the timer after the
+ * command.
+ */
+export async function invokePomodoro(
+  command: PomodoroCommand,
+  args: Record<string, unknown> = {}
+): Promise<PomodoroSnapshot> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<PomodoroSnapshot>(command, args);
+}
+
+/**
+ * Follow the backend pomodoro timer: `onTick` once a second while it runs,
+ * `onPhase` when it moves between work, breaks and idle
+ */
+export async function onPomodoroEvents(
+  onTick: (timer: PomodoroSnapshot) => void,
+  onPhase: (change: PomodoroPhaseChange) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlistenTick = await listen<PomodoroSnapshot>('pomodoro-tick', (event) => {
+    onTick(event.payload);
+  });
+  const unlistenPhase = await listen<PomodoroPhaseChange>('pomodoro-phase', (event) => {
+    onPhase(event.payload);
+  });
+  return () => {
+    unlistenTick();
+    unlistenPhase();
+  };
+}
+
+/**
+ * Put a task's reminder off (Tauri only). The backend sets the task's
+ * `remindAt`, which arrives through the file watcher; returns it.
+ */
+export async function snoozeReminder(taskId: string, snooze: ReminderSnooze): Promise<string> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<string>('snooze_reminder', { taskId, snooze });
+}
+
+/**
+ * Follow task reminders from the backend: `onReminder` when one is sent,
+ * `onAction` when a button is pressed on its notification
+ */
+export async function onTaskReminders(
+  onReminder: (reminder: TaskReminder) => void,
+  onAction: (action: ReminderAction) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlistenReminder = await listen<TaskReminder>('task-reminder', (event) => {
+    onReminder(event.payload);
+  });
+  const unlistenAction = await listen<ReminderAction>('reminder-action', (event) => {
+    onAction(event.payload);
+  });
+  return () => {
+    unlistenReminder();
+    unlistenAction();
+  };
+}
+
 /**
  * Load from localStorage (web/dev mode) - handles migration
  */
@@ -236,23 +593,8 @@ function loadFromLocalStorage(): AppData {
 function saveToLocalStorage(data: AppData): void {
   try {
     // Separate into different files
-    const active: ActiveData = {
-      version: data.version,
-      lastModified: data.lastModified,
-      tasks: data.tasks,
-      reviews: data.reviews,
-      customTagGroups: data.customTagGroups,
-      settings: data.settings,
-      gamification: data.gamification,
-      cycleState: data.cycleState,
-      cycleHistory: data.cycleHistory
-    };
-
-    const pomodoroHistory: PomodoroHistoryData = {
-      version: data.version,
-      lastModified: data.lastModified,
-      sessions: data.pomodoroHistory
-    };
+    const active = toActiveData(data);
+    const pomodoroHistory = toPomodoroHistoryData(data);
 
     localStorage.setItem(STORAGE_KEYS.active, JSON.stringify(active));
     localStorage.setItem(STORAGE_KEYS.pomodoroHistory, JSON.stringify(pomodoroHistory));
@@ -276,8 +618,8 @@ async function loadFromTauri(): Promise<AppData> {
     }
 
     // Read separated files
-    const activeContent = await invoke<string | null>('read_data_file', { fileType: 'active' });
-    const pomodoroContent = await invoke<string | null>('read_data_file', { fileType: 'pomodoro_history' });
+    const activeContent = await readDataFile('active');
+    const pomodoroContent = await readDataFile('pomodoro_history');
 
     const active = activeContent ? JSON.parse(activeContent) as ActiveData : createDefaultActiveData();
     const pomodoro = pomodoroContent ? JSON.parse(pomodoroContent) as PomodoroHistoryData : createDefaultPomodoroHistoryData();
@@ -292,7 +634,7 @@ async function loadFromTauri(): Promise<AppData> {
 
     // Migrate old archive file if it exists
     try {
-      const archiveContent = await invoke<string | null>('read_data_file', { fileType: 'archive' });
+      const archiveContent = await readDataFile('archive');
       if (archiveContent) {
         const archiveData = JSON.parse(archiveContent);
         const migratedArchive = migrateTasks(archiveData.tasks || []).map((t: any) => ({ ...t, priority: 'G', completed: true }));
@@ -325,6 +667,31 @@ async function loadFromTauri(): Promise<AppData> {
   }
 }
 
+/**
+ * Hot data file contents of the in-memory app state
+ */
+function toActiveData(data: AppData): ActiveData {
+  return {
+    version: data.version,
+    lastModified: data.lastModified,
+    tasks: data.tasks,
+    reviews: data.reviews,
+    customTagGroups: data.customTagGroups,
+    settings: data.settings,
+    gamification: data.gamification,
+    cycleState: data.cycleState,
+    cycleHistory: data.cycleHistory
+  };
+}
+
+function toPomodoroHistoryData(data: AppData): PomodoroHistoryData {
+  return {
+    version: data.version,
+    lastModified: data.lastModified,
+    sessions: data.pomodoroHistory
+  };
+}
+
 /**
  * Save to Tauri file system using atomic writes
  */
@@ -333,41 +700,18 @@ async function saveToTauri(data: AppData, filesToSave: PersistedFileType[] = ['a
   beginSave();
 
   try {
-    const { invoke } = await import('@tauri-apps/api/core');
-
     // Separate into different files
-    const active: ActiveData = {
-      version: data.version,
-      lastModified: data.lastModified,
-      tasks: data.tasks,
-      reviews: data.reviews,
-      customTagGroups: data.customTagGroups,
-      settings: data.settings,
-      gamification: data.gamification,
-      cycleState: data.cycleState,
-      cycleHistory: data.cycleHistory
-    };
-
-    const pomodoroHistory: PomodoroHistoryData = {
-      version: data.version,
-      lastModified: data.lastModified,
-      sessions: data.pomodoroHistory
-    };
+    const active = toActiveData(data);
+    const pomodoroHistory = toPomodoroHistoryData(data);
 
     const promises = [];
 
     if (filesToSave.includes('active')) {
-      promises.push(invoke('atomic_write_file', {
-        fileType: 'active',
-        content: JSON.stringify(active, null, 2)
-      }));
+      promises.push(writeDataFile('active', JSON.stringify(active, null, 2)));
     }
 
     if (filesToSave.includes('pomodoro_history')) {
-      promises.push(invoke('atomic_write_file', {
-        fileType: 'pomodoro_history',
-        content: JSON.stringify(pomodoroHistory, null, 2)
-      }));
+      promises.push(writeDataFile('pomodoro_history', JSON.stringify(pomodoroHistory, null, 2)));
     }
 
     // Write all files atomically (in parallel)
@@ -389,11 +733,7 @@ async function saveFileTauri(fileType: DataFileType, data: unknown): Promise<voi
   beginSave();
 
   try {
-    const { invoke } = await import('@tauri-apps/api/core');
-    await invoke('atomic_write_file', {
-      fileType,
-      content: JSON.stringify(data, null, 2)
-    });
+    await writeDataFile(fileType, JSON.stringify(data, null, 2));
   } finally {
     // Mark save operation as complete (cooldown handled by isSaving())
     endSave();
@@ -431,7 +771,11 @@ function migrateSettings(settings: any): AppData['settings'] {
     showFutureTasks: settings?.showFutureTasks ?? defaults.showFutureTasks,
     unitBoundaryFlexHours: settings?.unitBoundaryFlexHours ?? defaults.unitBoundaryFlexHours,
     dueReminders: settings?.dueReminders ?? defaults.dueReminders,
-    lowCompletionPrompt: settings?.lowCompletionPrompt ?? defaults.lowCompletionPrompt
+    reminderOffsets: settings?.reminderOffsets ?? defaults.reminderOffsets,
+    lowCompletionPrompt: settings?.lowCompletionPrompt ?? defaults.lowCompletionPrompt,
+    backupRetention: { ...defaults.backupRetention, ...settings?.backupRetention },
+    compressBackups: settings?.compressBackups ?? defaults.compressBackups,
+    unitLayout: settings?.unitLayout ?? defaults.unitLayout
   };
 }
 
@@ -503,28 +847,24 @@ export function isCurrentlySaving(): boolean {
 }
 
 /**
- * Setup file change listener with debounce and isSaving protection
+ * Setup file change listener with debounce
  */
-export async function setupFileWatcher(onFileChange: (fileType: string) => void): Promise<() => void> {
+export async function setupFileWatcher(onFileChange: (change: FileChange) => void): Promise<() => void> {
   if (!isTauri()) {
     return () => {}; // No-op for web
   }
 
-  // Track pending file changes for debounce
-  const pendingChanges = new Set<string>();
+  // Track pending file changes for debounce. Diffs are relative to the last
+  // version this window saw, so the newest one per file covers the earlier ones.
+  const pendingChanges = new Map<string, FileChange>();
 
   const { listen } = await import('@tauri-apps/api/event');
-  const unlisten = await listen<string>('data-file-changed', (event) => {
-    const fileType = event.payload;
+  const unlisten = await listen<FileChange>('data-file-changed', (event) => {
+    const fileType = event.payload.fileType;
 
-    // Skip if we're currently saving (self-triggered event)
-    if (isSaving()) {
-      console.log('Ignoring file change (isSaving):', fileType);
-      return;
-    }
-
-    // Add to pending changes
-    pendingChanges.add(fileType);
+    // Our own writes are filtered out by the backend (content hash match),
+    // so everything arriving here was changed by someone else
+    pendingChanges.set(fileType, event.payload);
 
     // Clear existing debounce timer
     if (fileWatcherDebounceTimer) {
@@ -533,17 +873,10 @@ export async function setupFileWatcher(onFileChange: (fileType: string) => void)
 
     // Set new debounce timer
     fileWatcherDebounceTimer = setTimeout(() => {
-      // Double-check isSaving after debounce
-      if (isSaving()) {
-        console.log('Ignoring debounced file changes (isSaving)');
-        pendingChanges.clear();
-        return;
-      }
-
       // Process all pending changes
-      for (const type of pendingChanges) {
-        console.log('Processing external file change:', type);
-        onFileChange(type);
+      for (const change of pendingChanges.values()) {
+        console.log('Processing external file change:', change.fileType, change.diff);
+        onFileChange(change);
       }
       pendingChanges.clear();
       fileWatcherDebounceTimer = null;
@@ -560,6 +893,26 @@ export async function setupFileWatcher(onFileChange: (fileType: string) => void)
   };
 }
 
+/**
+ * Hold back external change events (e.g. during drag-and-drop). Returns a
+ * lease ID for resumeWatcher; the lease expires on its own after timeoutMs
+ * (backend default 30 s), and changes seen meanwhile are delivered afterwards.
+ */
+export async function suspendWatcher(timeoutMs?: number): Promise<string | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<string>('suspend_watcher', { timeoutMs: timeoutMs ?? null });
+}
+
+/**
+ * Release a lease taken with suspendWatcher
+ */
+export async function resumeWatcher(leaseId: string | null): Promise<void> {
+  if (!isTauri() || !leaseId) return;
+  const { invoke } = await import('@tauri-apps/api/core');
+  await invoke('resume_watcher', { leaseId });
+}
+
 /**
  * Export data as JSON file
  */
@@ -659,3 +1012,55 @@ export async function createBackup(): Promise<string> {
     return 'Exported to downloads';
   }
 }
+
+/**
+ * List existing backups, newest first (desktop only)
+ */
+export async function listBackups(): Promise<BackupInfo[]> {
+  if (!isTauri()) return [];
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<BackupInfo[]>('list_backups');
+}
+
+/**
+ * Remove backups outside the retention policy. Without a policy the one stored
+ * in settings is used; dryRun only reports what would be removed.
+ */
+export async function pruneBackups(policy?: BackupRetention, dryRun = false): Promise<PruneReport | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<PruneReport>('prune_backups', { policy: policy ?? null, dryRun });
+}
+
+/**
+ * Restore data files from a backup. The backend snapshots the current state
+ * first and emits data-file-changed for every restored file.
+ */
+export async function restoreBackup(backupId: string): Promise<RestoreReport> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<RestoreReport>('restore_backup', { backupId });
+}
+
+/**
+ * Check that a backup is readable and matches its checksum manifest
+ */
+export async function verifyBackup(backupId: string): Promise<BackupVerification> {
+  const { invoke } = await import('@tauri-apps/api/core');
+  return await invoke<BackupVerification>('verify_backup', { backupId });
+}
+
+/**
+ * Listen for the backend's periodic backup check reporting failures
+ */
+export async function onBackupVerificationFailed(
+  callback: (failed: BackupVerification[]) => void
+): Promise<() => void> {
+  if (!isTauri()) {
+    return () => {};
+  }
+
+  const { listen } = await import('@tauri-apps/api/event');
+  return await listen<BackupVerification[]>('backup-verification-failed', (event) => {
+    callback(event.payload);
+  });
+}
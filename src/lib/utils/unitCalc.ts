@@ -1,63 +1,118 @@
-import type { UnitInfo } from '$lib/types';
+import type { UnitInfo, UnitLayout, Weekday } from '$lib/types';
+
+// Mirror of src-tauri/src/units.rs. Both are checked against
+// units.vectors.json (npm run test:units); keep them in step.
+
+const WEEKDAYS: Weekday[] = ['Sun', 'Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat'];
+
+// Sun-Mon / Tue-Wed / Thu-Fri with Saturday as the review day
+export const DEFAULT_UNIT_LAYOUT: UnitLayout = {
+  weekStart: 'Sun',
+  unitLengths: [2, 2, 2],
+  reviewDay: 'Sat'
+};
+
+// Layout used by every calculation below; set from settings.unitLayout
+let layout: UnitLayout = DEFAULT_UNIT_LAYOUT;
+
+function daysBetween(from: Weekday, to: Weekday): number {
+  return (WEEKDAYS.indexOf(to) - WEEKDAYS.indexOf(from) + 7) % 7;
+}
+
+/**
+ * Why a layout can't split the week into units, or null if it can. The units
+ * and review day must cover the week exactly once, and the review day can't
+ * fall inside a unit.
+ */
+export function checkUnitLayout(unitLayout: UnitLayout): string | null {
+  const { weekStart, unitLengths, reviewDay } = unitLayout;
+  if (!WEEKDAYS.includes(weekStart) || (reviewDay !== null && !WEEKDAYS.includes(reviewDay))) {
+    return 'Unknown weekday';
+  }
+  if (unitLengths.length === 0 || unitLengths.some(n => !Number.isInteger(n) || n < 1)) {
+    return 'Every unit must be at least one day long';
+  }
+  const days = reviewDay ? 6 : 7;
+  if (unitLengths.reduce((a, b) => a + b, 0) !== days) {
+    return `Unit lengths must add up to ${days} days, not ${unitLengths.join('+')}`;
+  }
+  if (reviewDay) {
+    const before = daysBetween(weekStart, reviewDay);
+    let boundary = 0;
+    for (const n of unitLengths) {
+      if (boundary < before && before < boundary + n) {
+        return `The review day (${reviewDay}) falls inside a unit`;
+      }
+      boundary += n;
+    }
+  }
+  return null;
+}
+
+/**
+ * Use `unitLayout` (an invalid or missing one means the default) for unit
+ * calculations. Returns whether the layout changed.
+ */
+export function setUnitLayout(unitLayout: UnitLayout | undefined): boolean {
+  const next = unitLayout && checkUnitLayout(unitLayout) === null ? unitLayout : DEFAULT_UNIT_LAYOUT;
+  const changed = JSON.stringify(next) !== JSON.stringify(layout);
+  layout = next;
+  return changed;
+}
+
+export function getUnitLayout(): UnitLayout {
+  return layout;
+}
+
+// Unit number (0 = review day), day within the unit and unit length for each
+// day of the week, indexed by days since weekStart
+function slots(unitLayout: UnitLayout): { number: number; day: number; length: number }[] {
+  const review = unitLayout.reviewDay ? daysBetween(unitLayout.weekStart, unitLayout.reviewDay) : -1;
+  const result = Array.from({ length: 7 }, () => ({ number: 0, day: 0, length: 1 }));
+  const offsets = [0, 1, 2, 3, 4, 5, 6].filter(o => o !== review);
+  let next = 0;
+  unitLayout.unitLengths.forEach((length, i) => {
+    for (let day = 0; day < length; day++) {
+      result[offsets[next++]] = { number: i + 1, day, length };
+    }
+  });
+  return result;
+}
 
 /**
- * Calculate which bi-daily unit a date belongs to
- * Unit 1: Sunday + Monday
- * Unit 2: Tuesday + Wednesday
- * Unit 3: Thursday + Friday
- * Saturday: Review day
+ * Calculate which unit a date belongs to under the layout (default: unit 1
+ * Sunday + Monday, unit 2 Tuesday + Wednesday, unit 3 Thursday + Friday,
+ * Saturday review day)
  */
-export function getUnitForDate(date: Date): UnitInfo {
-  const dayOfWeek = date.getDay(); // 0 = Sunday, 6 = Saturday
+export function getUnitForDate(date: Date, unitLayout: UnitLayout = layout): UnitInfo {
+  const effective = checkUnitLayout(unitLayout) === null ? unitLayout : DEFAULT_UNIT_LAYOUT;
 
   // Clone date to avoid mutation
   const d = new Date(date);
   d.setHours(0, 0, 0, 0);
 
-  if (dayOfWeek === 6) {
-    // Saturday - Review day
+  const slot = slots(effective)[(d.getDay() - WEEKDAYS.indexOf(effective.weekStart) + 7) % 7];
+  const startDate = new Date(d);
+  startDate.setDate(d.getDate() - slot.day);
+  const endDate = new Date(startDate);
+  endDate.setDate(startDate.getDate() + slot.length - 1);
+
+  if (slot.number === 0) {
     return {
       unitNumber: 0,
-      startDate: d,
-      endDate: d,
+      startDate,
+      endDate,
       isReviewDay: true,
       label: '周复盘'
     };
   }
 
-  let unitNumber: number;
-  let startDate: Date;
-  let endDate: Date;
-
-  if (dayOfWeek === 0 || dayOfWeek === 1) {
-    // Sunday (0) or Monday (1) -> Unit 1
-    unitNumber = 1;
-    startDate = new Date(d);
-    startDate.setDate(d.getDate() - (dayOfWeek === 0 ? 0 : 1));
-    endDate = new Date(startDate);
-    endDate.setDate(startDate.getDate() + 1);
-  } else if (dayOfWeek === 2 || dayOfWeek === 3) {
-    // Tuesday (2) or Wednesday (3) -> Unit 2
-    unitNumber = 2;
-    startDate = new Date(d);
-    startDate.setDate(d.getDate() - (dayOfWeek === 2 ? 0 : 1));
-    endDate = new Date(startDate);
-    endDate.setDate(startDate.getDate() + 1);
-  } else {
-    // Thursday (4) or Friday (5) -> Unit 3
-    unitNumber = 3;
-    startDate = new Date(d);
-    startDate.setDate(d.getDate() - (dayOfWeek === 4 ? 0 : 1));
-    endDate = new Date(startDate);
-    endDate.setDate(startDate.getDate() + 1);
-  }
-
   return {
-    unitNumber,
+    unitNumber: slot.number,
     startDate,
     endDate,
     isReviewDay: false,
-    label: `Unit ${unitNumber}: ${formatDateShort(startDate)}-${formatDateShort(endDate)}`
+    label: `Unit ${slot.number}: ${formatDateShort(startDate)}-${formatDateShort(endDate)}`
   };
 }
 
@@ -90,50 +145,37 @@ export function currentUnitStartLocal(date: Date = new Date()): string {
  * Navigate to adjacent unit
  */
 export function navigateUnit(currentUnit: UnitInfo, direction: 'prev' | 'next'): UnitInfo {
-  const offset = direction === 'next' ? 2 : -2;
-  const newDate = new Date(currentUnit.startDate);
-
-  if (currentUnit.isReviewDay) {
-    // From Saturday, go to Friday (prev) or Sunday (next)
-    newDate.setDate(newDate.getDate() + (direction === 'next' ? 1 : -1));
-  } else {
-    newDate.setDate(newDate.getDate() + offset);
-  }
-
+  // The day just before the start or just after the end belongs to the neighbour
+  const newDate = new Date(direction === 'next' ? currentUnit.endDate : currentUnit.startDate);
+  newDate.setDate(newDate.getDate() + (direction === 'next' ? 1 : -1));
   return getUnitForDate(newDate);
 }
 
 /**
- * Get all units in a week
+ * Get the units (and review day) of the week containing `date`
  */
-export function getWeekUnits(weekStart: Date): UnitInfo[] {
+export function getWeekUnits(date: Date): UnitInfo[] {
   const units: UnitInfo[] = [];
-  const d = new Date(weekStart);
-
-  // Find Sunday of this week
-  d.setDate(d.getDate() - d.getDay());
-
-  // Unit 1 (Sun-Mon)
-  units.push(getUnitForDate(d));
-
-  // Unit 2 (Tue-Wed)
-  d.setDate(d.getDate() + 2);
-  units.push(getUnitForDate(d));
+  const d = new Date(date);
+  d.setHours(0, 0, 0, 0);
 
-  // Unit 3 (Thu-Fri)
-  d.setDate(d.getDate() + 2);
-  units.push(getUnitForDate(d));
+  // Find the start of this week
+  d.setDate(d.getDate() - daysBetween(layout.weekStart, WEEKDAYS[d.getDay()]));
 
-  // Review day (Sat)
-  d.setDate(d.getDate() + 2);
-  units.push(getUnitForDate(d));
+  for (let i = 0; i < 7; i++) {
+    const unit = getUnitForDate(d);
+    if (units.length === 0 || units[units.length - 1].startDate.getTime() !== unit.startDate.getTime()) {
+      units.push(unit);
+    }
+    d.setDate(d.getDate() + 1);
+  }
 
   return units;
 }
 
 /**
  * Check if two dates fall in the same 2-day unit (by unit start date).
- * Saturday (review day) only matches another Saturday of the same date.
+ * The review day only matches itself.
  */
 export function isSameUnit(a: Date, b: Date): boolean {
   const ua = getUnitForDate(a);
@@ -336,7 +378,7 @@ export function isOverdue(dateStr: string | null): boolean {
 }
 
 /**
- * Check if a date is within this week
+ * Check if a date is within this week (starting on the layout's weekStart)
  */
 export function isThisWeek(dateStr: string | null): boolean {
   if (!dateStr) return false;
@@ -344,15 +386,15 @@ export function isThisWeek(dateStr: string | null): boolean {
   const date = parseISODate(dateStr);
   const today = new Date();
 
-  // Get Sunday of this week
-  const sunday = new Date(today);
-  sunday.setDate(today.getDate() - today.getDay());
-  sunday.setHours(0, 0, 0, 0);
+  // First day of this week
+  const weekStart = new Date(today);
+  weekStart.setDate(today.getDate() - daysBetween(layout.weekStart, WEEKDAYS[today.getDay()]));
+  weekStart.setHours(0, 0, 0, 0);
 
-  // Get Saturday of this week
-  const saturday = new Date(sunday);
-  saturday.setDate(sunday.getDate() + 6);
-  saturday.setHours(23, 59, 59, 999);
+  // Last day of this week
+  const weekEnd = new Date(weekStart);
+  weekEnd.setDate(weekStart.getDate() + 6);
+  weekEnd.setHours(23, 59, 59, 999);
 
-  return date >= sunday && date <= saturday;
+  return date >= weekStart && date <= weekEnd;
 }